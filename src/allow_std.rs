@@ -12,6 +12,10 @@ use std::{fmt, io};
 /// are safe to use in this context. However, using these types with
 /// `AllowStdIo` will cause the event loop to block, so they should be used
 /// with care.
+///
+/// Since `std::io::Write` has no counterpart to `AsyncWrite::write_bytes` or
+/// `AsyncWrite::write_buf_vectored`, those use the default implementations,
+/// which copy the data.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct AllowStdIo<T>(T);
 
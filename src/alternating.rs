@@ -0,0 +1,133 @@
+use std::{fmt, io};
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which requires decoded frames to alternate between
+/// categories.
+///
+/// Each frame decoded by the inner decoder is classified by a user supplied
+/// closure. If a frame has the same category as the frame decoded before it,
+/// the frame is discarded and an `InvalidData` error is returned instead. The
+/// expectation is unchanged by a discarded frame, so decoding may continue
+/// with a frame of another category.
+///
+/// This suits lockstep protocols, where requests and responses strictly take
+/// turns. `reset` forgets the category of the previous frame, for example
+/// when the protocol restarts an exchange.
+///
+/// Encoding is passed through to the inner codec unchanged.
+pub struct Alternating<C, F, K> {
+    inner: C,
+    classify: F,
+    last: Option<K>,
+}
+
+impl<C, F, K> Alternating<C, F, K> {
+    /// Creates a new `Alternating` which classifies frames decoded by `inner`
+    /// with `classify`.
+    pub fn new(inner: C, classify: F) -> Alternating<C, F, K> {
+        Alternating {
+            inner: inner,
+            classify: classify,
+            last: None,
+        }
+    }
+
+    /// Forgets the category of the previous frame, so that the next frame is
+    /// accepted regardless of its category.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    /// Returns the category of the previously decoded frame, if any.
+    pub fn last_category(&self) -> Option<&K> {
+        self.last.as_ref()
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `Alternating`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, F, K> Alternating<C, F, K>
+    where C: Decoder,
+          F: FnMut(&C::Item) -> K,
+          K: PartialEq,
+{
+    fn check(&mut self, item: &C::Item) -> io::Result<()> {
+        let category = (self.classify)(item);
+
+        if self.last.as_ref() == Some(&category) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "consecutive frames of the same category"));
+        }
+
+        self.last = Some(category);
+        Ok(())
+    }
+}
+
+impl<C, F, K> Decoder for Alternating<C, F, K>
+    where C: Decoder,
+          C::Error: From<io::Error>,
+          F: FnMut(&C::Item) -> K,
+          K: PartialEq,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        match try!(self.inner.decode(src)) {
+            Some(item) => {
+                try!(self.check(&item));
+                Ok(Some(item))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        match try!(self.inner.decode_eof(src)) {
+            Some(item) => {
+                try!(self.check(&item));
+                Ok(Some(item))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<C: Encoder, F, K> Encoder for Alternating<C, F, K> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug, F, K: fmt::Debug> fmt::Debug for Alternating<C, F, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Alternating")
+            .field("inner", &self.inner)
+            .field("last", &self.last)
+            .finish()
+    }
+}
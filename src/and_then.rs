@@ -0,0 +1,166 @@
+use std::error::Error as StdError;
+use std::{fmt, io};
+
+use bytes::{Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+/// A codec which layers a payload codec on top of a framing codec.
+///
+/// When decoding, the framing codec splits the byte stream into frames and
+/// each frame is then decoded in its entirety by the payload codec, using
+/// `decode_eof`. When encoding, items are encoded by the payload codec and
+/// the resulting bytes are handed to the framing codec as a single frame.
+///
+/// Errors are reported as a `LayeredError`, which tells apart a malformed
+/// frame, after which the transport may need to resynchronize, from a well
+/// formed frame whose payload couldn't be decoded, after which it's usually
+/// enough to drop the message.
+#[derive(Debug)]
+pub struct AndThen<F, P> {
+    framing: F,
+    payload: P,
+}
+
+/// The error type of `AndThen`, indicating which layer an error came from.
+#[derive(Debug)]
+pub enum LayeredError<F, P> {
+    /// An error from the framing codec, or from the underlying I/O.
+    Framing(F),
+    /// An error from the payload codec.
+    Payload(P),
+}
+
+impl<F, P> AndThen<F, P> {
+    /// Creates a new `AndThen` decoding payloads with `payload` out of frames
+    /// produced by `framing`.
+    pub fn new(framing: F, payload: P) -> AndThen<F, P> {
+        AndThen {
+            framing: framing,
+            payload: payload,
+        }
+    }
+
+    /// Returns a reference to the framing codec.
+    pub fn framing(&self) -> &F {
+        &self.framing
+    }
+
+    /// Returns a mutable reference to the framing codec.
+    pub fn framing_mut(&mut self) -> &mut F {
+        &mut self.framing
+    }
+
+    /// Returns a reference to the payload codec.
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    /// Returns a mutable reference to the payload codec.
+    pub fn payload_mut(&mut self) -> &mut P {
+        &mut self.payload
+    }
+
+    /// Consumes the `AndThen`, returning the framing and payload codecs.
+    pub fn into_inner(self) -> (F, P) {
+        (self.framing, self.payload)
+    }
+
+    fn decode_payload(&mut self, frame: Option<BytesMut>)
+                      -> Result<Option<P::Item>, LayeredError<F::Error, P::Error>>
+        where F: Decoder,
+              P: Decoder,
+    {
+        let mut frame = match frame {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        match self.payload.decode_eof(&mut frame) {
+            Ok(Some(item)) => Ok(Some(item)),
+            Ok(None) => {
+                let err = io::Error::new(io::ErrorKind::InvalidData,
+                                         "frame did not contain a complete item");
+                Err(LayeredError::Payload(err.into()))
+            }
+            Err(e) => Err(LayeredError::Payload(e)),
+        }
+    }
+}
+
+impl<F, P> Decoder for AndThen<F, P>
+    where F: Decoder<Item = BytesMut>,
+          P: Decoder,
+{
+    type Item = P::Item;
+    type Error = LayeredError<F::Error, P::Error>;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<P::Item>, Self::Error> {
+        let frame = try!(self.framing.decode(src).map_err(LayeredError::Framing));
+        self.decode_payload(frame)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<P::Item>, Self::Error> {
+        let frame = try!(self.framing.decode_eof(src).map_err(LayeredError::Framing));
+        self.decode_payload(frame)
+    }
+}
+
+impl<F, P> Encoder for AndThen<F, P>
+    where F: Encoder<Item = Bytes>,
+          P: Encoder,
+{
+    type Item = P::Item;
+    type Error = LayeredError<F::Error, P::Error>;
+
+    fn encode(&mut self, item: P::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut frame = BytesMut::new();
+        try!(self.payload.encode(item, &mut frame).map_err(LayeredError::Payload));
+        self.framing.encode(frame.freeze(), dst).map_err(LayeredError::Framing)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.framing.encode_eof(dst).map_err(LayeredError::Framing)
+    }
+}
+
+// ===== impl LayeredError =====
+
+impl<F, P> From<io::Error> for LayeredError<F, P>
+    where F: From<io::Error>,
+{
+    fn from(src: io::Error) -> LayeredError<F, P> {
+        LayeredError::Framing(src.into())
+    }
+}
+
+impl<F, P> fmt::Display for LayeredError<F, P>
+    where F: fmt::Display,
+          P: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LayeredError::Framing(ref e) => write!(f, "framing error: {}", e),
+            LayeredError::Payload(ref e) => write!(f, "payload error: {}", e),
+        }
+    }
+}
+
+impl<F, P> StdError for LayeredError<F, P>
+    where F: StdError,
+          P: StdError,
+{
+    fn description(&self) -> &str {
+        match *self {
+            LayeredError::Framing(_) => "framing error",
+            LayeredError::Payload(_) => "payload error",
+        }
+    }
+
+    fn cause(&self) -> Option<&StdError> {
+        match *self {
+            LayeredError::Framing(ref e) => Some(e),
+            LayeredError::Payload(ref e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::io;
+
+use bytes::BytesMut;
+
+use codec::Decoder;
+
+/// Bits of the first identifier octet which hold the tag number
+const TAG_NUMBER_MASK: u8 = 0x1F;
+
+/// Set on each octet of a multi-octet tag number except for the last one
+const MORE_OCTETS: u8 = 0x80;
+
+/// Length octet starting an indefinite length encoding
+const INDEFINITE_LENGTH: u8 = 0x80;
+
+/// Length octet reserved for future extensions
+const RESERVED_LENGTH: u8 = 0xFF;
+
+/// Maximum number of subsequent octets in a tag, so the tag number fits a
+/// `u32`
+const MAX_TAG_OCTETS: usize = 5;
+
+/// A `Decoder` for BER encoded TLV values using the definite length form.
+///
+/// See [module level] documentation for more detail.
+///
+/// [module level]: index.html
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct BerCodec {
+    max_frame_len: usize,
+    // Length of the whole TLV currently being received, once its identifier
+    // and length octets have been parsed
+    frame_len: Option<usize>,
+}
+
+impl BerCodec {
+    /// Returns a `BerCodec` with a max frame length of 8MB.
+    pub fn new() -> BerCodec {
+        BerCodec {
+            max_frame_len: 8 * 1_024 * 1_024,
+            frame_len: None,
+        }
+    }
+
+    /// Returns the current max frame setting
+    ///
+    /// This is the largest number of contents octets this codec will accept
+    /// from the wire. Larger values will be rejected.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the max frame setting.
+    ///
+    /// The change takes effect the next time the header of a value is
+    /// decoded.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    /// Parses the identifier and length octets at the start of `src`,
+    /// returning the length of the whole TLV, or `None` if more octets are
+    /// needed.
+    fn parse_head(&self, src: &[u8]) -> io::Result<Option<usize>> {
+        let mut pos = match src.first() {
+            Some(&b) if b & TAG_NUMBER_MASK == TAG_NUMBER_MASK => {
+                // High tag number form, continued until an octet without
+                // the high bit set
+                let rest = &src[1..];
+
+                match rest.iter().position(|b| b & MORE_OCTETS == 0) {
+                    Some(i) if i >= MAX_TAG_OCTETS => return Err(invalid("ASN.1 tag number too large")),
+                    Some(i) => i + 2,
+                    None if rest.len() >= MAX_TAG_OCTETS => return Err(invalid("ASN.1 tag number too large")),
+                    None => return Ok(None),
+                }
+            }
+            Some(_) => 1,
+            None => return Ok(None),
+        };
+
+        let content_len = match src.get(pos) {
+            Some(&INDEFINITE_LENGTH) => {
+                return Err(invalid("indefinite length ASN.1 encoding is not supported"));
+            }
+            Some(&RESERVED_LENGTH) => return Err(invalid("invalid ASN.1 length octet")),
+            Some(&b) if b & 0x80 == 0 => {
+                pos += 1;
+                b as u64
+            }
+            Some(&b) => {
+                let num = (b & 0x7F) as usize;
+                let octets = match src.get(pos + 1..pos + 1 + num) {
+                    Some(octets) => octets,
+                    None => return Ok(None),
+                };
+
+                let mut n = 0u64;
+                for &b in octets {
+                    if n > u64::max_value() >> 8 {
+                        return Err(frame_too_big());
+                    }
+                    n = (n << 8) | b as u64;
+                }
+
+                pos += 1 + num;
+                n
+            }
+            None => return Ok(None),
+        };
+
+        if content_len > self.max_frame_len as u64 {
+            return Err(frame_too_big());
+        }
+
+        // The check above ensures there is no overflow
+        Ok(Some(pos + content_len as usize))
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn frame_too_big() -> io::Error {
+    invalid("ASN.1 value exceeds the max frame length")
+}
+
+impl Decoder for BerCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        let frame_len = match self.frame_len {
+            Some(n) => n,
+            None => {
+                match try!(self.parse_head(buf)) {
+                    Some(n) => n,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        if buf.len() < frame_len {
+            self.frame_len = Some(frame_len);
+            buf.reserve(frame_len - buf.len());
+            return Ok(None);
+        }
+
+        self.frame_len = None;
+        Ok(Some(buf.split_to(frame_len)))
+    }
+
+    fn has_frame(&self, buf: &BytesMut) -> bool {
+        let frame_len = match self.frame_len {
+            Some(n) => n,
+            None => {
+                match self.parse_head(buf) {
+                    Ok(Some(n)) => n,
+                    Ok(None) => return false,
+                    // Decoding would yield the error
+                    Err(_) => return true,
+                }
+            }
+        };
+
+        buf.len() >= frame_len
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// Decoding of any number of frames from a single parse step.
+///
+/// This is for formats where one parse step naturally produces several
+/// items, such as a batch record. Wrapping a `BatchDecoder` in `Batched`
+/// turns it into a `Decoder` which yields the items one at a time, so the
+/// extra items don't need to be stashed away in the decoder's own state.
+pub trait BatchDecoder {
+    /// The type of decoded frames.
+    type Item;
+
+    /// The type of unrecoverable frame decoding errors.
+    type Error: From<io::Error>;
+
+    /// Attempts to decode any number of frames from the provided buffer of
+    /// bytes, pushing them onto `dst` and returning how many were pushed.
+    ///
+    /// Returning `Ok(0)` means that more bytes need to be read, the same as
+    /// `Decoder::decode` returning `Ok(None)`. If an error is returned, any
+    /// items pushed onto `dst` during that call are discarded.
+    fn decode_many(&mut self, src: &mut BytesMut, dst: &mut Vec<Self::Item>) -> Result<usize, Self::Error>;
+
+    /// A default method available to be called when there are no more bytes
+    /// available to be read from the underlying I/O.
+    ///
+    /// This method defaults to calling `decode_many` and returns an error if
+    /// no frames were decoded while there is unconsumed data in `buf`, the
+    /// same as `Decoder::decode_eof`.
+    fn decode_many_eof(&mut self, buf: &mut BytesMut, dst: &mut Vec<Self::Item>) -> Result<usize, Self::Error> {
+        match try!(self.decode_many(buf, dst)) {
+            0 if !buf.is_empty() => {
+                Err(io::Error::new(io::ErrorKind::Other,
+                                   "bytes remaining on stream").into())
+            }
+            n => Ok(n),
+        }
+    }
+}
+
+/// A `Decoder` which yields the frames of a `BatchDecoder` one at a time.
+///
+/// The frames of each batch are queued up and yielded by successive calls
+/// to `decode`, and the inner decoder is only called again once the queue is
+/// empty. Encoding is passed through to the inner codec unchanged.
+pub struct Batched<C: BatchDecoder> {
+    inner: C,
+    pending: VecDeque<C::Item>,
+    batch: Vec<C::Item>,
+}
+
+impl<C: BatchDecoder> Batched<C> {
+    /// Creates a new `Batched` wrapping `inner`.
+    pub fn new(inner: C) -> Batched<C> {
+        Batched {
+            inner: inner,
+            pending: VecDeque::new(),
+            batch: Vec::new(),
+        }
+    }
+
+    /// Returns the number of frames decoded but not yet yielded.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `Batched`, returning the underlying codec.
+    ///
+    /// Frames decoded but not yet yielded are dropped.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    // Moves the frames pushed onto `batch` by a call to the inner decoder to
+    // the queue, discarding them if the call failed.
+    fn queue(&mut self, res: Result<usize, C::Error>) -> Result<Option<C::Item>, C::Error> {
+        if let Err(e) = res {
+            self.batch.clear();
+            return Err(e);
+        }
+
+        self.pending.extend(self.batch.drain(..));
+        Ok(self.pending.pop_front())
+    }
+}
+
+impl<C: BatchDecoder> Decoder for Batched<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        if let Some(frame) = self.pending.pop_front() {
+            return Ok(Some(frame));
+        }
+
+        let res = self.inner.decode_many(src, &mut self.batch);
+        self.queue(res)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        if let Some(frame) = self.pending.pop_front() {
+            return Ok(Some(frame));
+        }
+
+        let res = self.inner.decode_many_eof(src, &mut self.batch);
+        self.queue(res)
+    }
+
+    fn has_frame(&self, _src: &BytesMut) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+impl<C: BatchDecoder + Encoder> Encoder for Batched<C> {
+    type Item = <C as Encoder>::Item;
+    type Error = <C as Encoder>::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C> fmt::Debug for Batched<C>
+    where C: BatchDecoder + fmt::Debug,
+          C::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Batched")
+            .field("inner", &self.inner)
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+// Compound values nested deeper than this are rejected, which bounds the
+// recursion in the decoder.
+const MAX_DEPTH: usize = 64;
+
+/// A bencoded value.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum BencodeValue {
+    /// A byte string, encoded as `<len>:<data>`.
+    Bytes(Bytes),
+    /// An integer, encoded as `i<n>e`.
+    Integer(i64),
+    /// A list of values, encoded as `l<values>e`.
+    List(Vec<BencodeValue>),
+    /// A dictionary keyed by byte strings, encoded as `d<key><value>...e`
+    /// with the keys in sorted order.
+    Dict(BTreeMap<Bytes, BencodeValue>),
+}
+
+/// A `Codec` implementation which decodes and encodes one complete bencoded
+/// value per frame.
+///
+/// See [module level] documentation for more detail.
+///
+/// [module level]: index.html
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct BencodeCodec {
+    max_frame_len: usize,
+    // The number of bytes which have to be buffered before the value being
+    // received can be complete, as found by the last scan
+    need: usize,
+}
+
+impl BencodeCodec {
+    /// Returns a `BencodeCodec` for decoding and encoding bencoded values,
+    /// with a max frame length of 8MB.
+    pub fn new() -> BencodeCodec {
+        BencodeCodec {
+            max_frame_len: 8 * 1_024 * 1_024,
+            need: 0,
+        }
+    }
+
+    /// Returns the current max frame setting
+    ///
+    /// This is the largest encoded length of a top level value this codec
+    /// will accept from the wire. Larger values will be rejected.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the max frame setting.
+    ///
+    /// The change takes effect the next time buffered data is scanned.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn frame_too_big() -> io::Error {
+    invalid("bencode value exceeds the max frame length")
+}
+
+// The outcome of scanning the buffered data for a complete value.
+enum Scanned {
+    // The value ends just before this position.
+    Complete(usize),
+    // More data is needed, and the value can't be complete until at least
+    // this many bytes are buffered.
+    Partial(usize),
+}
+
+// Parses a decimal number from `src[pos..]` up to `delim`, returning the
+// number along with the position after the delimiter, or `None` if the
+// delimiter hasn't been received yet.
+fn scan_number(src: &[u8], pos: usize, delim: u8, signed: bool)
+    -> io::Result<Option<(i64, usize)>>
+{
+    let end = match src[pos..].iter().position(|&b| b == delim) {
+        Some(i) => pos + i,
+        None => {
+            // Fail early on garbage rather than waiting for a delimiter
+            // which may never come.
+            let valid = src[pos..].iter().enumerate().all(|(i, &b)| {
+                (b >= b'0' && b <= b'9') || (signed && i == 0 && b == b'-')
+            });
+            return if valid { Ok(None) } else { Err(invalid("invalid bencode number")) };
+        }
+    };
+
+    let digits = &src[pos..end];
+    let (negative, digits) = match digits.first() {
+        Some(&b'-') if signed => (true, &digits[1..]),
+        _ => (false, digits),
+    };
+
+    if digits.is_empty() || (digits[0] == b'0' && (digits.len() > 1 || negative)) {
+        return Err(invalid("invalid bencode number"));
+    }
+
+    let mut n: i64 = 0;
+    for &b in digits {
+        if b < b'0' || b > b'9' {
+            return Err(invalid("invalid bencode number"));
+        }
+        let digit = (b - b'0') as i64;
+        n = try!(n.checked_mul(10)
+            .and_then(|n| if negative { n.checked_sub(digit) } else { n.checked_add(digit) })
+            .ok_or_else(|| invalid("bencode number out of range")));
+    }
+
+    Ok(Some((n, end + 1)))
+}
+
+// Checks that a complete and well formed value starts at `src[pos..]`,
+// returning the position just past it, or how much data is needed first.
+// Byte strings which would end past `max` are rejected.
+fn scan(src: &[u8], pos: usize, depth: usize, max: usize) -> io::Result<Scanned> {
+    if depth > MAX_DEPTH {
+        return Err(invalid("bencode value nested too deeply"));
+    }
+
+    let first = match src.get(pos) {
+        Some(&b) => b,
+        None => return Ok(Scanned::Partial(pos + 1)),
+    };
+
+    match first {
+        b'i' => {
+            match try!(scan_number(src, pos + 1, b'e', true)) {
+                Some((_, end)) => Ok(Scanned::Complete(end)),
+                None => Ok(Scanned::Partial(src.len() + 1)),
+            }
+        }
+        b'l' | b'd' => {
+            let mut pos = pos + 1;
+            let mut is_key = first == b'd';
+
+            loop {
+                match src.get(pos) {
+                    // A dictionary can't end between a key and its value
+                    Some(&b'e') if first == b'd' && !is_key => {
+                        return Err(invalid("bencode dictionary key has no value"));
+                    }
+                    Some(&b'e') => return Ok(Scanned::Complete(pos + 1)),
+                    Some(&b) if is_key && (b < b'0' || b > b'9') => {
+                        return Err(invalid("bencode dictionary key is not a string"));
+                    }
+                    Some(_) => {}
+                    None => return Ok(Scanned::Partial(pos + 1)),
+                }
+
+                pos = match try!(scan(src, pos, depth + 1, max)) {
+                    Scanned::Complete(end) => end,
+                    partial => return Ok(partial),
+                };
+
+                if first == b'd' {
+                    is_key = !is_key;
+                }
+            }
+        }
+        b if b >= b'0' && b <= b'9' => {
+            let (len, start) = match try!(scan_number(src, pos, b':', false)) {
+                Some(n) => n,
+                None => return Ok(Scanned::Partial(src.len() + 1)),
+            };
+
+            // Checked before waiting for the data, so a huge declared length
+            // fails right away instead of buffering without bound
+            let end = match (start as u64).checked_add(len as u64) {
+                Some(end) if end <= max as u64 => end as usize,
+                _ => return Err(frame_too_big()),
+            };
+
+            if src.len() < end {
+                Ok(Scanned::Partial(end))
+            } else {
+                Ok(Scanned::Complete(end))
+            }
+        }
+        _ => Err(invalid("invalid bencode value")),
+    }
+}
+
+// Builds the value starting at `src[pos..]`, which must have been validated
+// by `scan`, returning it along with the position just past it.
+fn build(src: &Bytes, pos: usize) -> (BencodeValue, usize) {
+    match src[pos] {
+        b'i' => {
+            let (n, end) = scan_number(src, pos + 1, b'e', true).unwrap().unwrap();
+            (BencodeValue::Integer(n), end)
+        }
+        b'l' => {
+            let mut items = Vec::new();
+            let mut pos = pos + 1;
+
+            while src[pos] != b'e' {
+                let (item, end) = build(src, pos);
+                items.push(item);
+                pos = end;
+            }
+
+            (BencodeValue::List(items), pos + 1)
+        }
+        b'd' => {
+            let mut dict = BTreeMap::new();
+            let mut pos = pos + 1;
+
+            while src[pos] != b'e' {
+                let (key, end) = build(src, pos);
+                let (value, end) = build(src, end);
+                if let BencodeValue::Bytes(key) = key {
+                    dict.insert(key, value);
+                }
+                pos = end;
+            }
+
+            (BencodeValue::Dict(dict), pos + 1)
+        }
+        _ => {
+            let (len, start) = scan_number(src, pos, b':', false).unwrap().unwrap();
+            let end = start + len as usize;
+            (BencodeValue::Bytes(src.slice(start, end)), end)
+        }
+    }
+}
+
+impl Decoder for BencodeCodec {
+    type Item = BencodeValue;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<BencodeValue>> {
+        // Don't rescan from the start until the value can be complete
+        if buf.len() < self.need {
+            return Ok(None);
+        }
+
+        match try!(scan(buf, 0, 0, self.max_frame_len)) {
+            Scanned::Complete(end) if end > self.max_frame_len => Err(frame_too_big()),
+            Scanned::Complete(end) => {
+                self.need = 0;
+                let src = buf.split_to(end).freeze();
+                Ok(Some(build(&src, 0).0))
+            }
+            Scanned::Partial(need) if need > self.max_frame_len => Err(frame_too_big()),
+            Scanned::Partial(need) => {
+                self.need = need;
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn encode_value(value: &BencodeValue, buf: &mut BytesMut) {
+    match *value {
+        BencodeValue::Bytes(ref data) => {
+            let len = data.len().to_string();
+            buf.reserve(len.len() + 1 + data.len());
+            buf.put(len.as_bytes());
+            buf.put_u8(b':');
+            buf.put(&data[..]);
+        }
+        BencodeValue::Integer(n) => {
+            let n = n.to_string();
+            buf.reserve(n.len() + 2);
+            buf.put_u8(b'i');
+            buf.put(n.as_bytes());
+            buf.put_u8(b'e');
+        }
+        BencodeValue::List(ref items) => {
+            buf.reserve(1);
+            buf.put_u8(b'l');
+            for item in items {
+                encode_value(item, buf);
+            }
+            buf.reserve(1);
+            buf.put_u8(b'e');
+        }
+        BencodeValue::Dict(ref dict) => {
+            buf.reserve(1);
+            buf.put_u8(b'd');
+            for (key, value) in dict {
+                encode_value(&BencodeValue::Bytes(key.clone()), buf);
+                encode_value(value, buf);
+            }
+            buf.reserve(1);
+            buf.put_u8(b'e');
+        }
+    }
+}
+
+impl Encoder for BencodeCodec {
+    type Item = BencodeValue;
+    type Error = io::Error;
+
+    fn encode(&mut self, value: BencodeValue, buf: &mut BytesMut) -> io::Result<()> {
+        encode_value(&value, buf);
+        Ok(())
+    }
+}
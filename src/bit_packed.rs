@@ -0,0 +1,338 @@
+use std::io;
+
+use bytes::{BufMut, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+/// The order in which bits are packed into bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum BitOrder {
+    /// Fields are packed starting at the most significant bit of each byte,
+    /// with the most significant bit of each field first.
+    MsbFirst,
+    /// Fields are packed starting at the least significant bit of each byte,
+    /// with the least significant bit of each field first.
+    LsbFirst,
+}
+
+fn mask(bits: u32) -> u64 {
+    if bits >= 64 { !0 } else { (1 << bits) - 1 }
+}
+
+/// Reads fields of up to 64 bits from a byte slice, across byte boundaries.
+#[derive(Clone, Debug)]
+pub struct BitReader<'a> {
+    buf: &'a [u8],
+    order: BitOrder,
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Creates a new `BitReader` reading from the start of `buf`.
+    pub fn new(buf: &'a [u8], order: BitOrder) -> BitReader<'a> {
+        BitReader {
+            buf: buf,
+            order: order,
+            pos: 0,
+        }
+    }
+
+    /// Returns the number of bits read so far.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the number of bits left to read.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() * 8 - self.pos
+    }
+
+    /// Reads a field of `bits` bits, or returns `None` if fewer bits remain,
+    /// in which case nothing is read.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bits` is greater than 64.
+    pub fn read(&mut self, bits: u32) -> Option<u64> {
+        assert!(bits <= 64, "bit field wider than 64 bits");
+
+        if (bits as usize) > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0;
+        let mut done = 0;
+
+        while done < bits {
+            let byte = self.buf[self.pos / 8] as u64;
+            let offset = (self.pos % 8) as u32;
+            let take = ::std::cmp::min(8 - offset, bits - done);
+
+            match self.order {
+                BitOrder::MsbFirst => {
+                    let chunk = (byte >> (8 - offset - take)) & mask(take);
+                    value = (value << take) | chunk;
+                }
+                BitOrder::LsbFirst => {
+                    let chunk = (byte >> offset) & mask(take);
+                    value |= chunk << done;
+                }
+            }
+
+            self.pos += take as usize;
+            done += take;
+        }
+
+        Some(value)
+    }
+}
+
+/// Writes fields of up to 64 bits into bytes, across byte boundaries.
+///
+/// Bits of the last byte which aren't written to are left as zero.
+#[derive(Clone, Debug)]
+pub struct BitWriter {
+    buf: Vec<u8>,
+    order: BitOrder,
+    len: usize,
+}
+
+impl BitWriter {
+    /// Creates a new, empty `BitWriter`.
+    pub fn new(order: BitOrder) -> BitWriter {
+        BitWriter {
+            buf: Vec::new(),
+            order: order,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of bits written so far.
+    pub fn bit_len(&self) -> usize {
+        self.len
+    }
+
+    /// Writes `value` as a field of `bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bits` is greater than 64, or if `value` doesn't
+    /// fit in `bits` bits.
+    pub fn write(&mut self, value: u64, bits: u32) {
+        assert!(bits <= 64, "bit field wider than 64 bits");
+        assert!(value & !mask(bits) == 0, "value doesn't fit in bit field");
+
+        let mut done = 0;
+
+        while done < bits {
+            let offset = (self.len % 8) as u32;
+            if offset == 0 {
+                self.buf.push(0);
+            }
+
+            let take = ::std::cmp::min(8 - offset, bits - done);
+            let last = self.buf.last_mut().unwrap();
+
+            match self.order {
+                BitOrder::MsbFirst => {
+                    let chunk = (value >> (bits - done - take)) & mask(take);
+                    *last |= (chunk << (8 - offset - take)) as u8;
+                }
+                BitOrder::LsbFirst => {
+                    let chunk = (value >> done) & mask(take);
+                    *last |= (chunk << offset) as u8;
+                }
+            }
+
+            self.len += take as usize;
+            done += take;
+        }
+    }
+
+    /// Consumes the `BitWriter`, returning the bytes written.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// A frame decoded by `BitPacked`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BitFrame {
+    /// The values of the header fields, in the order of the layout.
+    pub fields: Vec<u64>,
+    /// The payload following the header.
+    pub payload: BytesMut,
+}
+
+/// A codec for frames whose header is made up of bit packed fields.
+///
+/// The header layout is given as the width in bits of each field, such as
+/// `[3, 13]` for a 3 bit type followed by a 13 bit length. Fields are packed
+/// back to back, straddling byte boundaries as needed, in the given
+/// `BitOrder`. The header is padded with zero bits to a whole number of
+/// bytes.
+///
+/// One of the fields may be designated as the length of the payload
+/// following the header with `set_length_field`. Without one, frames consist
+/// of the header only. When encoding, the length field is filled in from the
+/// payload, and encoding fails with an `InvalidInput` error if the payload
+/// length or any field value doesn't fit its width. When decoding, a length
+/// over the max frame length is rejected with an `InvalidData` error.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BitPacked {
+    fields: Vec<u32>,
+    order: BitOrder,
+    length_field: Option<usize>,
+    header_len: usize,
+    max_frame_len: usize,
+}
+
+impl BitPacked {
+    /// Creates a new `BitPacked` codec with the given field widths, and a max
+    /// frame length of 8MB.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `fields` is empty, or if any width is zero or
+    /// greater than 64.
+    pub fn new(fields: Vec<u32>, order: BitOrder) -> BitPacked {
+        assert!(!fields.is_empty(), "bit packed layout has no fields");
+        assert!(fields.iter().all(|&w| w > 0 && w <= 64), "invalid bit field width");
+
+        let bits = fields.iter().map(|&w| w as usize).sum::<usize>();
+
+        BitPacked {
+            fields: fields,
+            order: order,
+            length_field: None,
+            header_len: (bits + 7) / 8,
+            max_frame_len: 8 * 1_024 * 1_024,
+        }
+    }
+
+    /// Returns the width in bits of each header field.
+    pub fn fields(&self) -> &[u32] {
+        &self.fields
+    }
+
+    /// Returns the bit order of the header.
+    pub fn order(&self) -> BitOrder {
+        self.order
+    }
+
+    /// Returns the length of the header in bytes.
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    /// Returns the index of the field holding the payload length, if any.
+    pub fn length_field(&self) -> Option<usize> {
+        self.length_field
+    }
+
+    /// Designates the field at `index` as holding the payload length.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is out of bounds of the layout.
+    pub fn set_length_field(&mut self, index: usize) {
+        assert!(index < self.fields.len(), "length field out of bounds");
+        self.length_field = Some(index);
+    }
+
+    /// Returns the current max frame setting
+    ///
+    /// This is the largest payload length this codec will accept from the
+    /// wire. Larger values will be rejected.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the max frame setting.
+    ///
+    /// The change takes effect the next time a header is decoded.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    fn payload_len(&self, fields: &[u64]) -> io::Result<usize> {
+        let n = match self.length_field {
+            Some(i) => fields[i],
+            None => return Ok(0),
+        };
+
+        if n > self.max_frame_len as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "bit packed frame exceeds the max frame length"));
+        }
+
+        // The check above ensures there is no overflow
+        Ok(n as usize)
+    }
+}
+
+impl Decoder for BitPacked {
+    type Item = BitFrame;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<BitFrame>> {
+        if buf.len() < self.header_len {
+            return Ok(None);
+        }
+
+        let fields = {
+            let mut rd = BitReader::new(&buf[..self.header_len], self.order);
+            self.fields.iter().map(|&w| rd.read(w).unwrap()).collect::<Vec<_>>()
+        };
+
+        let payload_len = try!(self.payload_len(&fields));
+
+        if buf.len() - self.header_len < payload_len {
+            return Ok(None);
+        }
+
+        buf.split_to(self.header_len);
+
+        Ok(Some(BitFrame {
+            fields: fields,
+            payload: buf.split_to(payload_len),
+        }))
+    }
+}
+
+impl Encoder for BitPacked {
+    type Item = BitFrame;
+    type Error = io::Error;
+
+    fn encode(&mut self, mut frame: BitFrame, buf: &mut BytesMut) -> io::Result<()> {
+        if frame.fields.len() != self.fields.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "wrong number of bit packed fields"));
+        }
+
+        match self.length_field {
+            Some(i) => frame.fields[i] = frame.payload.len() as u64,
+            None if !frame.payload.is_empty() => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "payload without a bit packed length field"));
+            }
+            None => {}
+        }
+
+        let mut wr = BitWriter::new(self.order);
+
+        for (&value, &width) in frame.fields.iter().zip(&self.fields) {
+            if value & !mask(width) != 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "value doesn't fit in bit packed field"));
+            }
+
+            wr.write(value, width);
+        }
+
+        buf.reserve(self.header_len + frame.payload.len());
+        buf.put_slice(&wr.into_bytes());
+        buf.put_slice(&frame.payload);
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::cmp;
+use std::io::{self, Write};
+
+use futures::Poll;
+
+use AsyncWrite;
+
+/// An `AsyncWrite` adapter which buffers writes and passes them on to the
+/// underlying writer in units of exactly `block_size` bytes.
+///
+/// Unlike a general purpose buffered writer, every write made to the
+/// underlying object covers one whole block, which suits writers that require
+/// or strongly prefer aligned writes. The only exception is the final partial
+/// block written out by `flush` or `shutdown`, which by default is written
+/// unaligned. With `set_pad_on_flush` enabled it is instead padded out to a
+/// full block with the filler byte, which means the filler ends up in the
+/// output stream at that point.
+///
+/// If the underlying writer only accepts part of a block, the remainder of
+/// that block is written by the next call before any new data is accepted.
+///
+/// `AsyncWrite::write_bytes` and `AsyncWrite::write_buf_vectored` aren't
+/// passed through to the underlying writer, since data is regrouped into
+/// blocks in its own buffer anyway. They use the default implementations,
+/// which copy the data.
+#[derive(Debug)]
+pub struct BlockAlign<W> {
+    inner: W,
+    block_size: usize,
+    buf: Vec<u8>,
+    written: usize,
+    pad_on_flush: bool,
+    filler: u8,
+}
+
+impl<W> BlockAlign<W> {
+    /// Creates a new `BlockAlign` writing to `inner` in blocks of
+    /// `block_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `block_size` is zero.
+    pub fn new(inner: W, block_size: usize) -> BlockAlign<W> {
+        assert!(block_size > 0, "block size must be greater than zero");
+
+        BlockAlign {
+            inner: inner,
+            block_size: block_size,
+            buf: Vec::with_capacity(block_size),
+            written: 0,
+            pad_on_flush: false,
+            filler: 0,
+        }
+    }
+
+    /// Sets whether a final partial block is padded to a full block when
+    /// flushing, rather than being written unaligned.
+    ///
+    /// This is disabled by default.
+    pub fn set_pad_on_flush(&mut self, pad: bool) {
+        self.pad_on_flush = pad;
+    }
+
+    /// Sets the byte used to pad a final partial block.
+    ///
+    /// This defaults to zero and only has an effect when padding is enabled.
+    pub fn set_filler(&mut self, filler: u8) {
+        self.filler = filler;
+    }
+
+    /// Returns the block size.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Note that writing through this reference bypasses any buffered data.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the `BlockAlign`, returning the underlying writer.
+    ///
+    /// Any buffered data which has not been flushed is lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> BlockAlign<W> {
+    // Writes out everything that is buffered.
+    fn drain(&mut self) -> io::Result<()> {
+        while self.written < self.buf.len() {
+            let n = try!(self.inner.write(&self.buf[self.written..]));
+
+            if n == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                          "failed to write block"));
+            }
+
+            self.written += n;
+        }
+
+        self.buf.clear();
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BlockAlign<W> {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        if src.is_empty() {
+            return Ok(0);
+        }
+
+        if self.buf.len() == self.block_size {
+            try!(self.drain());
+        }
+
+        let n = cmp::min(self.block_size - self.buf.len(), src.len());
+        self.buf.extend_from_slice(&src[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() && self.pad_on_flush {
+            let block_size = self.block_size;
+            let filler = self.filler;
+            self.buf.resize(block_size, filler);
+        }
+
+        try!(self.drain());
+        self.inner.flush()
+    }
+}
+
+impl<W: AsyncWrite> AsyncWrite for BlockAlign<W> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        try_nb!(self.flush());
+        self.inner.shutdown()
+    }
+}
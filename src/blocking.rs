@@ -0,0 +1,108 @@
+use std::io::{self, Read, Write};
+
+use futures::{future, Async, Future, Poll};
+
+use {AsyncRead, AsyncWrite};
+
+/// An adapter which exposes an `AsyncRead` as a blocking `std::io::Read`.
+///
+/// Each read is driven as a future on the current thread. If the underlying
+/// reader returns a "would block" error, the thread is parked until the task
+/// is notified and the read is retried. This is intended for handing an
+/// asynchronous reader to a library which requires a synchronous `Read`.
+///
+/// # Blocking
+///
+/// This must only be used on a thread which is allowed to block, such as a
+/// dedicated worker thread. Using it on an event loop thread will block the
+/// event loop, and if the event loop is what drives the underlying reader,
+/// the read will never complete.
+#[derive(Debug)]
+pub struct BlockingRead<R> {
+    inner: R,
+}
+
+/// An adapter which exposes an `AsyncWrite` as a blocking `std::io::Write`.
+///
+/// This is the writing counterpart of `BlockingRead`, and the same
+/// requirement applies: it must only be used on a thread which is allowed to
+/// block.
+#[derive(Debug)]
+pub struct BlockingWrite<W> {
+    inner: W,
+}
+
+fn block_on<T, F>(mut f: F) -> io::Result<T>
+    where F: FnMut() -> io::Result<T>,
+{
+    future::poll_fn(|| -> Poll<T, io::Error> {
+        Ok(Async::Ready(try_nb!(f())))
+    }).wait()
+}
+
+impl<R: AsyncRead> BlockingRead<R> {
+    /// Creates a new `BlockingRead` reading from `inner`.
+    pub fn new(inner: R) -> BlockingRead<R> {
+        BlockingRead { inner: inner }
+    }
+}
+
+impl<R> BlockingRead<R> {
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `BlockingRead`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead> Read for BlockingRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        block_on(|| inner.read(buf))
+    }
+}
+
+impl<W: AsyncWrite> BlockingWrite<W> {
+    /// Creates a new `BlockingWrite` writing to `inner`.
+    pub fn new(inner: W) -> BlockingWrite<W> {
+        BlockingWrite { inner: inner }
+    }
+}
+
+impl<W> BlockingWrite<W> {
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the `BlockingWrite`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: AsyncWrite> Write for BlockingWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = &mut self.inner;
+        block_on(|| inner.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let inner = &mut self.inner;
+        block_on(|| inner.flush())
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::{self, Read};
+
+use AsyncRead;
+
+/// A byte-order mark detected at the start of a text stream.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Bom {
+    /// The UTF-8 mark, `EF BB BF`.
+    Utf8,
+    /// The little-endian UTF-16 mark, `FF FE`.
+    Utf16Le,
+    /// The big-endian UTF-16 mark, `FE FF`.
+    Utf16Be,
+}
+
+impl Bom {
+    /// Returns the bytes of this byte-order mark.
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match *self {
+            Bom::Utf8 => b"\xEF\xBB\xBF",
+            Bom::Utf16Le => b"\xFF\xFE",
+            Bom::Utf16Be => b"\xFE\xFF",
+        }
+    }
+}
+
+const BOMS: [Bom; 3] = [Bom::Utf8, Bom::Utf16Le, Bom::Utf16Be];
+const MAX_BOM_LEN: usize = 3;
+
+/// An `AsyncRead` adapter which strips a leading UTF-8 or UTF-16 byte-order
+/// mark.
+///
+/// The first reads buffer just enough of the stream to decide whether it
+/// starts with a byte-order mark, so a mark split across several reads of
+/// the underlying object is still recognized. A detected mark is consumed
+/// and reported by `detected_bom`; any other leading bytes are passed
+/// through untouched. After that all reads are delegated to the underlying
+/// reader.
+#[derive(Debug)]
+pub struct BomStrip<R> {
+    inner: R,
+    head: [u8; MAX_BOM_LEN],
+    head_len: usize,
+    // Position in `head` of buffered bytes not yet returned, once detection
+    // has finished
+    head_pos: usize,
+    detecting: bool,
+    bom: Option<Bom>,
+}
+
+impl<R> BomStrip<R> {
+    /// Creates a new `BomStrip` reading from `inner`.
+    pub fn new(inner: R) -> BomStrip<R> {
+        BomStrip {
+            inner: inner,
+            head: [0; MAX_BOM_LEN],
+            head_len: 0,
+            head_pos: 0,
+            detecting: true,
+            bom: None,
+        }
+    }
+
+    /// Returns the byte-order mark which was stripped from the stream.
+    ///
+    /// This is `None` until the start of the stream has been read, or if it
+    /// didn't start with a byte-order mark.
+    pub fn detected_bom(&self) -> Option<Bom> {
+        self.bom
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Note that reading through this reference while the start of the
+    /// stream is still being inspected may cause a byte-order mark to be
+    /// missed.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `BomStrip`, returning the underlying reader.
+    ///
+    /// Any bytes buffered while inspecting the start of the stream are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    // Decides on the buffered head once it's either a complete mark, can no
+    // longer become one, or the stream has ended.
+    fn decide(&mut self, eof: bool) -> bool {
+        let head = &self.head[..self.head_len];
+
+        for bom in BOMS.iter() {
+            if head.starts_with(bom.as_bytes()) {
+                self.bom = Some(*bom);
+                self.head_pos = bom.as_bytes().len();
+                self.detecting = false;
+                return true;
+            }
+        }
+
+        let maybe_bom = BOMS.iter().any(|bom| bom.as_bytes().starts_with(head));
+
+        if !maybe_bom || eof {
+            self.head_pos = 0;
+            self.detecting = false;
+            return true;
+        }
+
+        false
+    }
+}
+
+impl<R: Read> Read for BomStrip<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.detecting {
+            let n = try!(self.inner.read(&mut self.head[self.head_len..]));
+            self.head_len += n;
+            self.decide(n == 0);
+        }
+
+        if self.head_pos < self.head_len {
+            let n = ::std::cmp::min(buf.len(), self.head_len - self.head_pos);
+            buf[..n].copy_from_slice(&self.head[self.head_pos..self.head_pos + n]);
+            self.head_pos += n;
+            return Ok(n);
+        }
+
+        self.inner.read(buf)
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for BomStrip<R> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        // The read which finishes detection may go on to read from the
+        // underlying object, so the buffer has to be zeroed until then.
+        if self.detecting {
+            for x in buf.iter_mut() {
+                *x = 0;
+            }
+            return true;
+        }
+
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
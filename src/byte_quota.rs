@@ -0,0 +1,129 @@
+use std::{fmt, io};
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which limits the total number of bytes decoded over
+/// the lifetime of a connection.
+///
+/// The number of bytes the inner decoder consumes from the read buffer is
+/// added up across calls, and a decode which takes the total past the quota
+/// returns an error of kind `InvalidData` in place of its frame. Once the
+/// quota is used up every further decode fails in the same way, as long as
+/// there's data left to decode, so framing stops for good. Unlike a maximum
+/// frame length, which bounds each frame on its own, this bounds the sum of
+/// all frames, which suits metered or quota-limited connections.
+///
+/// This assumes that the inner decoder only ever removes bytes from the
+/// front of the buffer, as decoders normally do. Encoding is passed through
+/// to the inner codec unchanged.
+pub struct ByteQuota<C> {
+    inner: C,
+    quota: u64,
+    consumed: u64,
+}
+
+impl<C> ByteQuota<C> {
+    /// Creates a new `ByteQuota` allowing `inner` to consume `quota` bytes in
+    /// total.
+    pub fn new(inner: C, quota: u64) -> ByteQuota<C> {
+        ByteQuota {
+            inner: inner,
+            quota: quota,
+            consumed: 0,
+        }
+    }
+
+    /// Returns the number of bytes consumed by the inner decoder so far.
+    pub fn bytes_consumed(&self) -> u64 {
+        self.consumed
+    }
+
+    /// Returns the number of bytes which may still be consumed before the
+    /// quota is exceeded.
+    pub fn remaining_quota(&self) -> u64 {
+        self.quota.saturating_sub(self.consumed)
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `ByteQuota`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn check<T>(&mut self, before: usize, src: &BytesMut, frame: Option<T>) -> io::Result<Option<T>> {
+        let n = before.saturating_sub(src.len()) as u64;
+        self.consumed += n;
+
+        if n > 0 && self.consumed > self.quota {
+            return Err(exceeded());
+        }
+
+        Ok(frame)
+    }
+}
+
+fn exceeded() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "connection quota exceeded")
+}
+
+impl<C> Decoder for ByteQuota<C>
+    where C: Decoder,
+          C::Error: From<io::Error>,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        if self.consumed >= self.quota && !src.is_empty() {
+            return Err(exceeded().into());
+        }
+
+        let before = src.len();
+        let frame = try!(self.inner.decode(src));
+        Ok(try!(self.check(before, src, frame)))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        if self.consumed >= self.quota && !src.is_empty() {
+            return Err(exceeded().into());
+        }
+
+        let before = src.len();
+        let frame = try!(self.inner.decode_eof(src));
+        Ok(try!(self.check(before, src, frame)))
+    }
+}
+
+impl<C: Encoder> Encoder for ByteQuota<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for ByteQuota<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ByteQuota")
+            .field("inner", &self.inner)
+            .field("quota", &self.quota)
+            .field("consumed", &self.consumed)
+            .finish()
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+use std::collections::vec_deque;
+
+use bytes::{Buf, Bytes};
+
+/// A queue of `Bytes` buffers which reads as one contiguous `Buf`.
+///
+/// This is what `AsyncWrite::write_buf_vectored` is handed. Unlike a single
+/// `BytesMut` which frames are appended to, the queued buffers are kept
+/// apart, so writers which support vectored I/O can submit each of them
+/// directly, without first copying them together.
+///
+/// Advancing the queue as a `Buf` drops buffers from the front once they've
+/// been fully consumed.
+#[derive(Clone, Debug, Default)]
+pub struct BytesQueue {
+    bufs: VecDeque<Bytes>,
+    len: usize,
+}
+
+impl BytesQueue {
+    /// Creates an empty `BytesQueue`.
+    pub fn new() -> BytesQueue {
+        BytesQueue::default()
+    }
+
+    /// Appends `buf` to the back of the queue.
+    ///
+    /// Empty buffers are ignored.
+    pub fn push(&mut self, buf: Bytes) {
+        if buf.is_empty() {
+            return;
+        }
+
+        self.len += buf.len();
+        self.bufs.push_back(buf);
+    }
+
+    /// Returns the total number of bytes in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue holds no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the queued buffers, from front to back.
+    ///
+    /// The first buffer reflects any partial advance of the queue.
+    pub fn chunks(&self) -> vec_deque::Iter<Bytes> {
+        self.bufs.iter()
+    }
+
+    /// Removes all buffers from the queue.
+    pub fn clear(&mut self) {
+        self.bufs.clear();
+        self.len = 0;
+    }
+}
+
+// Calls `f` with each of the first `n` bytes across `bufs`, one buffer at a
+// time, such as the bytes taken by a write from a snapshot of the queue.
+pub fn each_written<F: FnMut(&[u8])>(bufs: &[Bytes], mut n: usize, mut f: F) {
+    for buf in bufs {
+        if n == 0 {
+            break;
+        }
+
+        let len = ::std::cmp::min(n, buf.len());
+        f(&buf[..len]);
+        n -= len;
+    }
+}
+
+impl Buf for BytesQueue {
+    fn remaining(&self) -> usize {
+        self.len
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self.bufs.front() {
+            Some(buf) => &buf[..],
+            None => &[],
+        }
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.len, "cannot advance past the end of the queue");
+        self.len -= cnt;
+
+        while cnt > 0 {
+            let front_len = self.bufs[0].len();
+
+            if cnt < front_len {
+                self.bufs[0].advance(cnt);
+                return;
+            }
+
+            self.bufs.pop_front();
+            cnt -= front_len;
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::io::{self, Read, Write};
+
+use bytes::Bytes;
+use futures::{Async, Poll};
+
+use {AsyncRead, AsyncWrite};
+use bytes_queue::{each_written, BytesQueue};
+
+/// A running checksum over a stream of bytes, as used by `ChecksumTrailer`
+/// and `ChecksumVerify`.
+pub trait Checksum {
+    /// Feeds `data` into the checksum.
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the checksum of all data fed in so far, encoded as it appears
+    /// in a trailer.
+    fn digest(&self) -> Vec<u8>;
+
+    /// Returns the length of the encoded checksum in bytes.
+    fn digest_len(&self) -> usize;
+}
+
+// CRC-32 lookup table for one nibble at a time, using the reflected IEEE
+// polynomial
+const CRC32_TABLE: [u32; 16] = [
+    0x00000000, 0x1db71064, 0x3b6e20c8, 0x26d930ac,
+    0x76dc4190, 0x6b6b51f4, 0x4db26158, 0x5005713c,
+    0xedb88320, 0xf00f9344, 0xd6d6a3e8, 0xcb61b38c,
+    0x9b64c2b0, 0x86d3d2d4, 0xa00ae278, 0xbdbdf21c,
+];
+
+/// The CRC-32 checksum used by zlib, gzip and Ethernet, among others.
+///
+/// The digest is the checksum as a big endian `u32`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    /// Creates a new `Crc32` over no data.
+    pub fn new() -> Crc32 {
+        Crc32 { state: !0 }
+    }
+
+    /// Returns the checksum of all data fed in so far.
+    pub fn value(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Checksum for Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            let mut crc = self.state ^ b as u32;
+            crc = (crc >> 4) ^ CRC32_TABLE[(crc & 0xf) as usize];
+            crc = (crc >> 4) ^ CRC32_TABLE[(crc & 0xf) as usize];
+            self.state = crc;
+        }
+    }
+
+    fn digest(&self) -> Vec<u8> {
+        let v = self.value();
+        vec![(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, v as u8]
+    }
+
+    fn digest_len(&self) -> usize {
+        4
+    }
+}
+
+/// An `AsyncWrite` adapter which appends a checksum of everything written
+/// through it when it's shut down.
+///
+/// Every byte accepted by the underlying writer is fed into the checksum.
+/// On `shutdown`, the digest is written out first, and only once all of it
+/// has been written is the underlying writer shut down. If the writer would
+/// block in the middle of that, the rest of the digest is kept and written
+/// by the next call to `shutdown`.
+///
+/// Writing after `shutdown` has been called fails, since the data would end
+/// up after the trailer.
+#[derive(Debug)]
+pub struct ChecksumTrailer<W, C = Crc32> {
+    inner: W,
+    checksum: C,
+    // The digest being written by `shutdown`, and how much of it is written
+    trailer: Option<(Vec<u8>, usize)>,
+}
+
+impl<W> ChecksumTrailer<W> {
+    /// Creates a new `ChecksumTrailer` appending a CRC-32 of the data written
+    /// to `inner`.
+    pub fn new(inner: W) -> ChecksumTrailer<W> {
+        ChecksumTrailer::with_checksum(inner, Crc32::new())
+    }
+}
+
+impl<W, C: Checksum> ChecksumTrailer<W, C> {
+    /// Creates a new `ChecksumTrailer` appending the digest of `checksum`
+    /// over the data written to `inner`.
+    pub fn with_checksum(inner: W, checksum: C) -> ChecksumTrailer<W, C> {
+        ChecksumTrailer {
+            inner: inner,
+            checksum: checksum,
+            trailer: None,
+        }
+    }
+
+    /// Returns the digest of the data written so far.
+    pub fn current_checksum(&self) -> Vec<u8> {
+        self.checksum.digest()
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Note that data written through this reference isn't included in the
+    /// checksum.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes the `ChecksumTrailer`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, C: Checksum> Write for ChecksumTrailer<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.trailer.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "write after shutdown"));
+        }
+
+        let n = try!(self.inner.write(buf));
+        self.checksum.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: AsyncWrite, C: Checksum> AsyncWrite for ChecksumTrailer<W, C> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        if self.trailer.is_none() {
+            self.trailer = Some((self.checksum.digest(), 0));
+        }
+
+        if let Some((ref trailer, ref mut pos)) = self.trailer {
+            let inner = &mut self.inner;
+
+            while *pos < trailer.len() {
+                let n = try_nb!(::retry_interrupted(|| inner.write(&trailer[*pos..])));
+
+                if n == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                              "failed to write checksum trailer"));
+                }
+
+                *pos += n;
+            }
+        }
+
+        try_ready!(self.inner.shutdown());
+        Ok(Async::Ready(()))
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        if self.trailer.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "write after shutdown"));
+        }
+
+        // A handle to the bytes, which doesn't copy them, to checksum
+        // whatever the inner writer takes
+        let data = bytes.clone();
+        let n = try_ready!(self.inner.write_bytes(bytes));
+        self.checksum.update(&data[..n]);
+        Ok(Async::Ready(n))
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        if self.trailer.is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "write after shutdown"));
+        }
+
+        // Handles to the queued buffers, which don't copy them, to checksum
+        // whatever the inner writer takes
+        let queued = bufs.chunks().cloned().collect::<Vec<Bytes>>();
+        let n = try_ready!(self.inner.write_buf_vectored(bufs));
+
+        let checksum = &mut self.checksum;
+        each_written(&queued, n, |buf| checksum.update(buf));
+        Ok(Async::Ready(n))
+    }
+}
+
+/// An `AsyncRead` adapter which checks the checksum trailer at the end of a
+/// stream, such as one written by `ChecksumTrailer`.
+///
+/// The last `digest_len` bytes of the stream are taken to be the trailer.
+/// Since the end of the stream isn't known until EOF, that many bytes are
+/// always held back from the data read, and only passed on once more data
+/// follows them. Everything else is passed on and fed into the checksum.
+///
+/// At EOF the digest is compared with the trailer. If they match, EOF is
+/// reported as usual. Otherwise the read fails with an `InvalidData` error,
+/// or with an `UnexpectedEof` error if the stream was too short to hold a
+/// trailer at all. Reading again at EOF repeats the check.
+#[derive(Debug)]
+pub struct ChecksumVerify<R, C = Crc32> {
+    inner: R,
+    checksum: C,
+    // The last bytes read, which may turn out to be the trailer
+    tail: Vec<u8>,
+}
+
+impl<R> ChecksumVerify<R> {
+    /// Creates a new `ChecksumVerify` checking a CRC-32 trailer at the end of
+    /// `inner`.
+    pub fn new(inner: R) -> ChecksumVerify<R> {
+        ChecksumVerify::with_checksum(inner, Crc32::new())
+    }
+}
+
+impl<R, C: Checksum> ChecksumVerify<R, C> {
+    /// Creates a new `ChecksumVerify` checking a trailer holding the digest
+    /// of `checksum` at the end of `inner`.
+    pub fn with_checksum(inner: R, checksum: C) -> ChecksumVerify<R, C> {
+        let len = checksum.digest_len();
+
+        ChecksumVerify {
+            inner: inner,
+            checksum: checksum,
+            tail: Vec::with_capacity(len),
+        }
+    }
+
+    /// Returns the digest of the data passed on so far.
+    pub fn current_checksum(&self) -> Vec<u8> {
+        self.checksum.digest()
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Note that data read through this reference isn't included in the
+    /// checksum.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `ChecksumVerify`, returning the underlying reader.
+    ///
+    /// Any bytes held back as a possible trailer are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn verify(&self) -> io::Result<usize> {
+        if self.tail.len() < self.checksum.digest_len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                      "stream shorter than checksum trailer"));
+        }
+
+        if self.tail != self.checksum.digest() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "checksum mismatch"));
+        }
+
+        Ok(0)
+    }
+}
+
+impl<R: Read, C: Checksum> Read for ChecksumVerify<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let n = try!(self.inner.read(buf));
+
+            if n == 0 {
+                return self.verify();
+            }
+
+            // The held back bytes come first, followed by the new ones, of
+            // which at most `n` are now known not to be the trailer
+            self.tail.extend_from_slice(&buf[..n]);
+            let out = self.tail.len().saturating_sub(self.checksum.digest_len());
+
+            if out == 0 {
+                continue;
+            }
+
+            buf[..out].copy_from_slice(&self.tail[..out]);
+            self.checksum.update(&buf[..out]);
+            self.tail.drain(..out);
+            return Ok(out);
+        }
+    }
+}
+
+impl<R: AsyncRead, C: Checksum> AsyncRead for ChecksumVerify<R, C> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
@@ -10,10 +10,33 @@
 //! [`Stream`]: #
 //! [transports]: #
 
+pub use alternating::Alternating;
+pub use and_then::{AndThen, LayeredError};
+pub use batched::{BatchDecoder, Batched};
+pub use bit_packed::{BitFrame, BitOrder, BitPacked, BitReader, BitWriter};
+pub use byte_quota::ByteQuota;
 pub use codecs::{BytesCodec, LinesCodec};
-pub use framed::{Framed, FramedParts};
-pub use framed_read::{FramedRead, Decoder};
-pub use framed_write::{FramedWrite, Encoder};
+pub use count_errors::CountErrors;
+pub use csv::Csv;
+pub use decode_all::{decode_all, poll_decode_from};
+pub use encode_all::encode_all;
+pub use filter_frames::FilterFrames;
+pub use fragment::Fragment;
+pub use with_raw::WithRaw;
+pub use framed::{Ack, ChunkFrames, Codec, CollectFrames, EofMarker, ErrorContext, ErrorDirection, Framed, FramedParts, MaxInFlight, OnError, OneAtATime, Readiness, Release, Router, WithErrors};
+pub use framed_read::{FramedRead, Decoder, BufferSource, SliceDecoder};
+pub use framed_split::{framed_split, CodecPair, Framed2};
+pub use framed_write::{ChunkedSend, DedupConsecutive, FramedWrite, Encoder, HeaderEncoder, Heartbeat, Pace};
+pub use header_body::HeaderBody;
+pub use key_value::KeyValueHeaders;
+pub use min_frame_len::MinFrameLen;
+pub use profile_decode::ProfileDecode;
+pub use rle::Rle;
+pub use sequenced::{Sequenced, SequenceGap};
+pub use stale_frame_guard::StaleFrameGuard;
+pub use sync_framed::SyncFramed;
+pub use transform::Transform;
+pub use validated::Validated;
 
 pub mod length_delimited {
     //! Frame a stream of bytes based on a length prefix
@@ -357,3 +380,114 @@ pub mod length_delimited {
 
     pub use ::length_delimited::*;
 }
+
+pub mod asn1 {
+    //! Frame a stream of ASN.1 values encoded with BER or DER
+    //!
+    //! Each value is encoded as a TLV: identifier octets holding the tag,
+    //! length octets, and then the contents octets. Tag numbers of 31 and
+    //! above take additional identifier octets, and lengths of 128 and above
+    //! use the long form, where the first length octet gives the number of
+    //! octets holding the length.
+    //!
+    //! ```text
+    //! 04 05 68 65 6c 6c 6f        OCTET STRING "hello", short form length
+    //! 30 82 01 00 ...             SEQUENCE of 256 octets, long form length
+    //! ```
+    //!
+    //! [`BerCodec`] waits for the identifier and length octets, then for all
+    //! the contents octets, and yields the whole TLV, with its header, as a
+    //! single `BytesMut` frame. Only the definite length form is supported;
+    //! a value using the indefinite length form, or whose contents are longer
+    //! than the max frame length, results in an `InvalidData` error.
+    //!
+    //! [`BerCodec`]: struct.BerCodec.html
+
+    pub use ::asn1::*;
+}
+
+pub mod slip {
+    //! Frame a stream of bytes using SLIP (RFC 1055)
+    //!
+    //! SLIP, the Serial Line Internet Protocol, is commonly used to
+    //! encapsulate packets sent over serial links. Each frame is terminated
+    //! by an `END` byte (`0xC0`). Occurrences of `END` within the payload are
+    //! replaced by the two byte sequence `ESC ESC_END` (`0xDB 0xDC`), and
+    //! occurrences of `ESC` by `ESC ESC_ESC` (`0xDB 0xDD`).
+    //!
+    //! [`SlipCodec`] decodes such frames into `BytesMut` values with the
+    //! escaping removed. Empty frames, such as those produced by back to back
+    //! `END` bytes, are skipped. A frame containing an invalid escape sequence
+    //! results in an `InvalidData` error, after which decoding resumes at the
+    //! following frame.
+    //!
+    //! When encoding, each frame is both preceded and followed by an `END`
+    //! byte, which flushes out any line noise received before the frame.
+    //!
+    //! ```text
+    //!        INPUT                              ENCODED
+    //! +------------------+     +------+--------------------------+------+
+    //! | \x01\xC0\x02\xDB | --> | \xC0 | \x01\xDB\xDC\x02\xDB\xDD | \xC0 |
+    //! +------------------+     +------+--------------------------+------+
+    //! ```
+    //!
+    //! [`SlipCodec`]: struct.SlipCodec.html
+
+    pub use ::slip::*;
+}
+
+pub mod text_command {
+    //! Frame a stream of bytes as STOMP-like text commands
+    //!
+    //! Each frame starts with a command line, followed by any number of
+    //! `key:value` header lines, an empty line, and a body. The frame is
+    //! terminated by a NUL byte. Lines may end in either `\n` or `\r\n`, and
+    //! end of line bytes between frames, as sent for heartbeats, are skipped.
+    //!
+    //! ```text
+    //! SEND\n
+    //! destination:/queue/a\n
+    //! \n
+    //! hello queue a\0
+    //! ```
+    //!
+    //! [`TextCommandCodec`] decodes such frames into [`Frame`] values, and
+    //! encodes them back with `\n` line endings. Header values are taken as
+    //! is, without unescaping, and since the NUL byte terminates a frame, it
+    //! can't appear in a body.
+    //!
+    //! A frame which is larger than the max frame length, has more headers
+    //! than the max header count, or is otherwise malformed results in an
+    //! `InvalidData` error, after which decoding resumes at the following
+    //! frame.
+    //!
+    //! [`TextCommandCodec`]: struct.TextCommandCodec.html
+    //! [`Frame`]: struct.Frame.html
+
+    pub use ::text_command::*;
+}
+
+#[cfg(feature = "bencode")]
+pub mod bencode {
+    //! Frame a stream of bencoded values
+    //!
+    //! Bencode is the encoding used by BitTorrent. Every value is self
+    //! delimiting: byte strings are length prefixed (`4:spam`), integers are
+    //! wrapped in `i` and `e` (`i42e`), and lists and dictionaries are wrapped
+    //! in `l` or `d` and `e`. Since compound values carry no length prefix,
+    //! [`BencodeCodec`] scans the buffered data structurally and only decodes
+    //! once a complete value has been received. Each frame is one top level
+    //! [`BencodeValue`].
+    //!
+    //! Malformed input, such as an integer with leading zeros or a dictionary
+    //! key which isn't a byte string, results in an `InvalidData` error.
+    //! So does a value longer than the codec's max frame length, which is
+    //! checked as soon as a byte string's length prefix has been received.
+    //!
+    //! This module is only available with the `bencode` feature enabled.
+    //!
+    //! [`BencodeCodec`]: struct.BencodeCodec.html
+    //! [`BencodeValue`]: enum.BencodeValue.html
+
+    pub use ::bencode::*;
+}
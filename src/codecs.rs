@@ -1,6 +1,7 @@
 use bytes::{Bytes, BufMut, BytesMut};
-use codec::{Encoder, Decoder};
+use codec::{Encoder, Decoder, SliceDecoder};
 use std::{io, str};
+use std::ops::Range;
 
 /// A simple `Codec` implementation that just ships bytes around.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -25,6 +26,16 @@ impl Decoder for BytesCodec {
     }
 }
 
+impl SliceDecoder for BytesCodec {
+    fn decode_slice(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, io::Error> {
+        if src.len() > 0 {
+            Ok(Some((0..src.len(), src.len())))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 impl Encoder for BytesCodec {
     type Item = Bytes;
     type Error = io::Error;
@@ -110,6 +121,40 @@ impl Decoder for LinesCodec {
     }
 }
 
+impl SliceDecoder for LinesCodec {
+    fn decode_slice(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, io::Error> {
+        if let Some(newline_offset) =
+            src[self.next_index..].iter().position(|b| *b == b'\n')
+        {
+            let newline_index = newline_offset + self.next_index;
+            let line = without_carriage_return(&src[..newline_index]);
+            utf8(line)?;
+            self.next_index = 0;
+            Ok(Some((0..line.len(), newline_index + 1)))
+        } else {
+            self.next_index = src.len();
+            Ok(None)
+        }
+    }
+
+    fn decode_slice_eof(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, io::Error> {
+        Ok(match self.decode_slice(src)? {
+            Some(frame) => Some(frame),
+            None => {
+                // No terminating newline - return remaining data, if any
+                if src.is_empty() || src == b"\r" {
+                    None
+                } else {
+                    let line = without_carriage_return(src);
+                    utf8(line)?;
+                    self.next_index = 0;
+                    Some((0..line.len(), src.len()))
+                }
+            }
+        })
+    }
+}
+
 impl Encoder for LinesCodec {
     type Item = String;
     type Error = io::Error;
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::io::{self, Read};
+
+use futures::{Async, Stream};
+
+use AsyncRead;
+
+/// A reader which reads from each reader yielded by a stream in turn.
+///
+/// Created by the [`concat_readers`] function.
+///
+/// [`concat_readers`]: fn.concat_readers.html
+pub struct ConcatReaders<S: Stream> {
+    readers: S,
+    current: Option<S::Item>,
+    done: bool,
+}
+
+/// Creates a reader which presents the readers yielded by `readers` as one
+/// continuous stream of data.
+///
+/// Each reader is read until EOF before the next one is pulled from the
+/// stream, and the returned reader hits EOF once the stream has ended. If
+/// the stream isn't ready to yield the next reader, the read returns a
+/// "would block" error, with the current task registered to be notified by
+/// the stream. An error from a reader, or from the stream itself, is
+/// returned from the read during which it occurred; reading again continues
+/// with the same reader, or polls the stream again, respectively.
+///
+/// # Panics
+///
+/// Reading from the returned reader will panic if not called within the
+/// context of a future's task, whenever the stream needs to be polled.
+pub fn concat_readers<S>(readers: S) -> ConcatReaders<S>
+    where S: Stream,
+          S::Item: AsyncRead,
+          S::Error: Into<io::Error>,
+{
+    ConcatReaders {
+        readers: readers,
+        current: None,
+        done: false,
+    }
+}
+
+impl<S: Stream> ConcatReaders<S> {
+    /// Returns a reference to the reader currently being read from, if any.
+    pub fn current(&self) -> Option<&S::Item> {
+        self.current.as_ref()
+    }
+
+    /// Returns a reference to the stream of readers.
+    pub fn get_ref(&self) -> &S {
+        &self.readers
+    }
+
+    /// Returns a mutable reference to the stream of readers.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.readers
+    }
+
+    /// Consumes the `ConcatReaders`, returning the stream of readers.
+    ///
+    /// The reader currently being read from, if any, is dropped.
+    pub fn into_inner(self) -> S {
+        self.readers
+    }
+}
+
+impl<S> Read for ConcatReaders<S>
+    where S: Stream,
+          S::Item: AsyncRead,
+          S::Error: Into<io::Error>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Otherwise a reader's zero length read would be taken for its EOF
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if self.done {
+                return Ok(0);
+            }
+
+            if self.current.is_none() {
+                match self.readers.poll() {
+                    Ok(Async::Ready(Some(rd))) => self.current = Some(rd),
+                    Ok(Async::Ready(None)) => {
+                        self.done = true;
+                        return Ok(0);
+                    }
+                    Ok(Async::NotReady) => {
+                        return Err(io::Error::new(io::ErrorKind::WouldBlock,
+                                                  "next reader not ready"));
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            let n = try!(self.current.as_mut().unwrap().read(buf));
+
+            if n > 0 {
+                return Ok(n);
+            }
+
+            self.current = None;
+        }
+    }
+}
+
+// The readers may each prepare buffers differently, so the default of
+// zeroing them is kept.
+impl<S> AsyncRead for ConcatReaders<S>
+    where S: Stream,
+          S::Item: AsyncRead,
+          S::Error: Into<io::Error>,
+{
+}
+
+impl<S> fmt::Debug for ConcatReaders<S>
+    where S: Stream + fmt::Debug,
+          S::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConcatReaders")
+            .field("readers", &self.readers)
+            .field("current", &self.current)
+            .field("done", &self.done)
+            .finish()
+    }
+}
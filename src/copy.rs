@@ -1,8 +1,9 @@
-use std::io;
+use std::{fmt, io};
 
-use futures::{Future, Poll};
+use futures::{Async, Future, Poll};
 
 use {AsyncRead, AsyncWrite};
+use instrument::{self, Site};
 
 /// A future which will copy all data from a reader into a writer.
 ///
@@ -10,8 +11,7 @@ use {AsyncRead, AsyncWrite};
 /// bytes copied or an error if one happens.
 ///
 /// [`copy`]: fn.copy.html
-#[derive(Debug)]
-pub struct Copy<R, W> {
+pub struct Copy<R, W, F = fn()> {
     reader: Option<R>,
     read_done: bool,
     writer: Option<W>,
@@ -19,6 +19,7 @@ pub struct Copy<R, W> {
     cap: usize,
     amt: u64,
     buf: Box<[u8]>,
+    on_idle: Option<F>,
 }
 
 /// Creates a future which represents copying all the bytes from one object to
@@ -44,23 +45,63 @@ pub fn copy<R, W>(reader: R, writer: W) -> Copy<R, W>
         pos: 0,
         cap: 0,
         buf: Box::new([0; 2048]),
+        on_idle: None,
     }
 }
 
-impl<R, W> Future for Copy<R, W>
+impl<R, W, F> Copy<R, W, F> {
+    /// Registers a callback which is invoked whenever this copy is idle.
+    ///
+    /// The copy is considered idle when the reader has no data available and
+    /// all data read so far has been written out, in other words when no
+    /// progress can be made until the reader becomes readable again. The
+    /// callback is invoked at most once per call to `poll`, and never while
+    /// there is still data waiting to be written to a writer that isn't ready
+    /// to accept it.
+    pub fn on_idle<G>(self, f: G) -> Copy<R, W, G>
+        where G: FnMut(),
+    {
+        Copy {
+            reader: self.reader,
+            read_done: self.read_done,
+            writer: self.writer,
+            pos: self.pos,
+            cap: self.cap,
+            amt: self.amt,
+            buf: self.buf,
+            on_idle: Some(f),
+        }
+    }
+}
+
+impl<R, W, F> Future for Copy<R, W, F>
     where R: AsyncRead,
           W: AsyncWrite,
+          F: FnMut(),
 {
     type Item = (u64, R, W);
     type Error = io::Error;
 
     fn poll(&mut self) -> Poll<(u64, R, W), io::Error> {
+        instrument::poll(Site::Copy);
+
         loop {
             // If our buffer is empty, then we need to read some data to
             // continue.
             if self.pos == self.cap && !self.read_done {
                 let reader = self.reader.as_mut().unwrap();
-                let n = try_nb!(reader.read(&mut self.buf));
+                let n = match reader.read(&mut self.buf) {
+                    Ok(n) => n,
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        instrument::would_block(Site::Copy);
+                        trace!("copy: read would block; amt={}", self.amt);
+                        if let Some(ref mut on_idle) = self.on_idle {
+                            on_idle();
+                        }
+                        return Ok(Async::NotReady);
+                    }
+                    Err(e) => return Err(e),
+                };
                 if n == 0 {
                     self.read_done = true;
                 } else {
@@ -72,7 +113,10 @@ impl<R, W> Future for Copy<R, W>
             // If our buffer has some data, let's write it out!
             while self.pos < self.cap {
                 let writer = self.writer.as_mut().unwrap();
-                let i = try_nb!(writer.write(&self.buf[self.pos..self.cap]));
+                let buf = &self.buf[self.pos..self.cap];
+                let i = try_nb_traced!(Site::Copy,
+                                       ::retry_interrupted(|| writer.write(buf)),
+                                       "copy: write would block; amt={}", self.amt);
                 if i == 0 {
                     return Err(io::Error::new(io::ErrorKind::WriteZero,
                                               "write zero byte into writer"));
@@ -86,7 +130,9 @@ impl<R, W> Future for Copy<R, W>
             // data and finish the transfer.
             // done with the entire transfer.
             if self.pos == self.cap && self.read_done {
-                try_nb!(self.writer.as_mut().unwrap().flush());
+                try_nb_traced!(Site::Copy,
+                               ::retry_interrupted(|| self.writer.as_mut().unwrap().flush()),
+                               "copy: flush would block; amt={}", self.amt);
                 let reader = self.reader.take().unwrap();
                 let writer = self.writer.take().unwrap();
                 return Ok((self.amt, reader, writer).into())
@@ -94,3 +140,20 @@ impl<R, W> Future for Copy<R, W>
         }
     }
 }
+
+impl<R, W, F> fmt::Debug for Copy<R, W, F>
+    where R: fmt::Debug,
+          W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Copy")
+            .field("reader", &self.reader)
+            .field("read_done", &self.read_done)
+            .field("writer", &self.writer)
+            .field("pos", &self.pos)
+            .field("cap", &self.cap)
+            .field("amt", &self.amt)
+            .field("buf", &self.buf)
+            .finish()
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which counts the errors returned by the inner decoder.
+///
+/// Errors are still passed through unchanged, so on their own they end a
+/// `Framed` stream as usual. Combined with `Framed::with_errors`, or with an
+/// inner decoder which resynchronizes after bad input, decoding carries on
+/// past errors while `error_count` keeps track of how many were seen, which
+/// is useful for monitoring connections that tolerate occasional garbage.
+///
+/// Encoding is passed through to the inner codec unchanged.
+pub struct CountErrors<C> {
+    inner: C,
+    errors: u64,
+}
+
+impl<C> CountErrors<C> {
+    /// Creates a new `CountErrors` wrapping `inner`.
+    pub fn new(inner: C) -> CountErrors<C> {
+        CountErrors {
+            inner: inner,
+            errors: 0,
+        }
+    }
+
+    /// Returns the number of errors returned by the inner decoder so far.
+    pub fn error_count(&self) -> u64 {
+        self.errors
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `CountErrors`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn count<T, E>(&mut self, res: Result<T, E>) -> Result<T, E> {
+        if res.is_err() {
+            self.errors += 1;
+        }
+        res
+    }
+}
+
+impl<C: Decoder> Decoder for CountErrors<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let res = self.inner.decode(src);
+        self.count(res)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let res = self.inner.decode_eof(src);
+        self.count(res)
+    }
+}
+
+impl<C: Encoder> Encoder for CountErrors<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for CountErrors<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CountErrors")
+            .field("inner", &self.inner)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
@@ -0,0 +1,268 @@
+use std::io;
+
+use bytes::{BufMut, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+/// A codec for CSV records, decoding each record into its fields.
+///
+/// Records are terminated by `\n` or `\r\n`. Fields are separated by the
+/// delimiter, `,` by default, and may be enclosed in the quote character,
+/// `"` by default. A quoted field may contain delimiters and line breaks,
+/// and a quote character within it is written as two quote characters.
+///
+/// Since a quoted field may span several lines, the decoder keeps track of
+/// whether it is within quotes across calls to `decode`, rather than
+/// splitting the stream into lines first. An empty line is decoded as a
+/// record without any fields.
+///
+/// Decoding fails with an `InvalidData` error if a record has a quote
+/// character within an unquoted field, anything other than a delimiter
+/// following a closing quote, or a field that isn't valid UTF-8. The record
+/// is discarded when this happens. Encoding quotes only those fields which
+/// need it, and ends each record with `\n`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Csv {
+    delimiter: u8,
+    quote: u8,
+    // Index of the next byte to scan for the end of the record, along with
+    // where in the record that byte is
+    next_index: usize,
+    scan: Scan,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum Scan {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    // A quote within a quoted field, which either closes it or is followed by
+    // another quote
+    QuotedQuote,
+}
+
+impl Csv {
+    /// Creates a new `Csv` codec with `,` as the delimiter and `"` as the
+    /// quote character.
+    pub fn new() -> Csv {
+        Csv {
+            delimiter: b',',
+            quote: b'"',
+            next_index: 0,
+            scan: Scan::FieldStart,
+        }
+    }
+
+    /// Returns the field delimiter.
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    /// Updates the field delimiter.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `val` is the quote character or a line break.
+    pub fn set_delimiter(&mut self, val: u8) {
+        assert!(val != self.quote && val != b'\r' && val != b'\n', "invalid CSV delimiter");
+        self.delimiter = val;
+    }
+
+    /// Returns the quote character.
+    pub fn quote(&self) -> u8 {
+        self.quote
+    }
+
+    /// Updates the quote character.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `val` is the delimiter or a line break.
+    pub fn set_quote(&mut self, val: u8) {
+        assert!(val != self.delimiter && val != b'\r' && val != b'\n', "invalid CSV quote character");
+        self.quote = val;
+    }
+
+    fn parse(&self, mut record: &[u8]) -> io::Result<Vec<String>> {
+        if let Some(&b'\r') = record.last() {
+            record = &record[..record.len() - 1];
+        }
+
+        let mut fields = Vec::new();
+
+        if record.is_empty() {
+            return Ok(fields);
+        }
+
+        let mut pos = 0;
+
+        loop {
+            let mut field = Vec::new();
+
+            if record.get(pos) == Some(&self.quote) {
+                pos += 1;
+
+                loop {
+                    match record.get(pos) {
+                        Some(&b) if b == self.quote => {
+                            if record.get(pos + 1) == Some(&self.quote) {
+                                field.push(b);
+                                pos += 2;
+                            } else {
+                                pos += 1;
+                                break;
+                            }
+                        }
+                        Some(&b) => {
+                            field.push(b);
+                            pos += 1;
+                        }
+                        None => return Err(invalid("unterminated quoted CSV field")),
+                    }
+                }
+
+                match record.get(pos) {
+                    Some(&b) if b == self.delimiter => {}
+                    Some(_) => return Err(invalid("unexpected character after closing quote")),
+                    None => {}
+                }
+            } else {
+                while let Some(&b) = record.get(pos) {
+                    if b == self.delimiter {
+                        break;
+                    }
+
+                    if b == self.quote {
+                        return Err(invalid("quote character in unquoted CSV field"));
+                    }
+
+                    field.push(b);
+                    pos += 1;
+                }
+            }
+
+            match String::from_utf8(field) {
+                Ok(field) => fields.push(field),
+                Err(_) => return Err(invalid("CSV field is not valid UTF-8")),
+            }
+
+            // Skip the delimiter, if any
+            if pos >= record.len() {
+                return Ok(fields);
+            }
+            pos += 1;
+        }
+    }
+
+    fn needs_quotes(&self, field: &str) -> bool {
+        field.bytes().any(|b| {
+            b == self.delimiter || b == self.quote || b == b'\r' || b == b'\n'
+        })
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Decoder for Csv {
+    type Item = Vec<String>;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Vec<String>>> {
+        let mut end = None;
+
+        // Only track enough state to find the end of the record; malformed
+        // quoting is reported when the record is parsed.
+        for (i, &b) in buf[self.next_index..].iter().enumerate() {
+            self.scan = match self.scan {
+                Scan::Quoted if b == self.quote => Scan::QuotedQuote,
+                Scan::Quoted => Scan::Quoted,
+                Scan::QuotedQuote if b == self.quote => Scan::Quoted,
+                Scan::FieldStart if b == self.quote => Scan::Quoted,
+                _ if b == b'\n' => {
+                    end = Some(self.next_index + i);
+                    break;
+                }
+                _ if b == self.delimiter => Scan::FieldStart,
+                _ => Scan::Unquoted,
+            };
+        }
+
+        let end = match end {
+            Some(end) => end,
+            None => {
+                self.next_index = buf.len();
+                return Ok(None);
+            }
+        };
+
+        self.next_index = 0;
+        self.scan = Scan::FieldStart;
+
+        let record = buf.split_to(end + 1);
+        self.parse(&record[..end]).map(Some)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<Vec<String>>> {
+        match try!(self.decode(buf)) {
+            Some(record) => Ok(Some(record)),
+            None => {
+                if buf.is_empty() {
+                    return Ok(None);
+                }
+
+                let in_quotes = self.scan == Scan::Quoted;
+                self.next_index = 0;
+                self.scan = Scan::FieldStart;
+
+                let record = buf.take();
+
+                if in_quotes {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                              "stream ended within a quoted CSV field"));
+                }
+
+                self.parse(&record).map(Some)
+            }
+        }
+    }
+}
+
+impl Encoder for Csv {
+    type Item = Vec<String>;
+    type Error = io::Error;
+
+    fn encode(&mut self, fields: Vec<String>, buf: &mut BytesMut) -> io::Result<()> {
+        let len = fields.iter().map(|f| f.len() + 3).sum::<usize>();
+        buf.reserve(len + 1);
+
+        // A lone empty field is quoted to tell it apart from an empty record
+        let lone_empty = fields.len() == 1 && fields[0].is_empty();
+
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                buf.put_u8(self.delimiter);
+            }
+
+            if lone_empty || self.needs_quotes(field) {
+                buf.put_u8(self.quote);
+
+                for b in field.bytes() {
+                    if b == self.quote {
+                        buf.reserve(1);
+                        buf.put_u8(b);
+                    }
+                    buf.put_u8(b);
+                }
+
+                buf.put_u8(self.quote);
+            } else {
+                buf.put_slice(field.as_bytes());
+            }
+        }
+
+        buf.put_u8(b'\n');
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use bytes::BytesMut;
+
+use codec::Decoder;
+
+/// Decodes all frames from a byte slice, as if it were the entire contents
+/// of a stream.
+///
+/// The whole slice is copied into a buffer and `decode` is called until it
+/// returns `None`, after which `decode_eof` is called until it does too,
+/// mirroring what `FramedRead` does when it reaches EOF. This means that
+/// trailing bytes which don't form a frame are reported as an error by the
+/// default `decode_eof`.
+///
+/// This is mostly useful to test decoders without driving a `FramedRead`
+/// over a mock I/O object.
+///
+/// # Errors
+///
+/// The first error returned by the decoder is returned, and any frames
+/// decoded before it are discarded.
+pub fn decode_all<D: Decoder>(mut codec: D, bytes: &[u8]) -> Result<Vec<D::Item>, D::Error> {
+    let mut buf = BytesMut::from(bytes);
+    let mut frames = Vec::new();
+
+    while let Some(frame) = try!(poll_decode_from(&mut codec, &mut buf, false)) {
+        frames.push(frame);
+    }
+
+    while let Some(frame) = try!(poll_decode_from(&mut codec, &mut buf, true)) {
+        frames.push(frame);
+    }
+
+    Ok(frames)
+}
+
+/// Decodes the next frame from a buffer managed by the caller, following
+/// the same rules as `FramedRead`.
+///
+/// This is for driving a decoder directly against a buffer which is filled
+/// outside of it, such as a ring buffer shared with the code doing the
+/// reads, without reimplementing the state machine of `FramedRead`:
+///
+/// * While `eof` is `false`, this calls `decode`. `None` means that more
+///   bytes need to be appended to `buf` before calling this again.
+/// * Once the underlying source has reached EOF, `eof` should be `true`, and
+///   this calls `decode_eof` instead, even if `buf` is empty, so decoders can
+///   yield final frames. It should be called until it returns `None`, which
+///   means that the stream has ended. With the default `decode_eof`, bytes
+///   left in `buf` which don't form a frame are reported as an error.
+///
+/// Bytes may be appended to `buf` between calls, and are picked up by the
+/// next one.
+pub fn poll_decode_from<D: Decoder>(codec: &mut D,
+                                    buf: &mut BytesMut,
+                                    eof: bool)
+                                    -> Result<Option<D::Item>, D::Error>
+{
+    if eof {
+        codec.decode_eof(buf)
+    } else {
+        codec.decode(buf)
+    }
+}
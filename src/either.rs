@@ -0,0 +1,104 @@
+use std::io::{self, Read, Write};
+
+use bytes::{Buf, BufMut, Bytes};
+use futures::Poll;
+
+use {AsyncRead, AsyncWrite};
+use bytes_queue::BytesQueue;
+
+/// An I/O object which is one of two possible types.
+///
+/// This is useful when the concrete I/O object is only known at runtime, such
+/// as a connection which may or may not be encrypted. All reads and writes,
+/// including the `AsyncRead` and `AsyncWrite` specific methods, are forwarded
+/// to whichever variant is active.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Either<A, B> {
+    /// The first possible I/O object.
+    Left(A),
+    /// The second possible I/O object.
+    Right(B),
+}
+
+impl<A, B> Read for Either<A, B>
+    where A: Read,
+          B: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            Either::Left(ref mut a) => a.read(buf),
+            Either::Right(ref mut b) => b.read(buf),
+        }
+    }
+}
+
+impl<A, B> AsyncRead for Either<A, B>
+    where A: AsyncRead,
+          B: AsyncRead,
+{
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        match *self {
+            Either::Left(ref a) => a.prepare_uninitialized_buffer(buf),
+            Either::Right(ref b) => b.prepare_uninitialized_buffer(buf),
+        }
+    }
+
+    fn read_buf<T: BufMut>(&mut self, buf: &mut T) -> Poll<usize, io::Error> {
+        match *self {
+            Either::Left(ref mut a) => a.read_buf(buf),
+            Either::Right(ref mut b) => b.read_buf(buf),
+        }
+    }
+}
+
+impl<A, B> Write for Either<A, B>
+    where A: Write,
+          B: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            Either::Left(ref mut a) => a.write(buf),
+            Either::Right(ref mut b) => b.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            Either::Left(ref mut a) => a.flush(),
+            Either::Right(ref mut b) => b.flush(),
+        }
+    }
+}
+
+impl<A, B> AsyncWrite for Either<A, B>
+    where A: AsyncWrite,
+          B: AsyncWrite,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match *self {
+            Either::Left(ref mut a) => a.shutdown(),
+            Either::Right(ref mut b) => b.shutdown(),
+        }
+    }
+
+    fn write_buf<T: Buf>(&mut self, buf: &mut T) -> Poll<usize, io::Error> {
+        match *self {
+            Either::Left(ref mut a) => a.write_buf(buf),
+            Either::Right(ref mut b) => b.write_buf(buf),
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        match *self {
+            Either::Left(ref mut a) => a.write_bytes(bytes),
+            Either::Right(ref mut b) => b.write_bytes(bytes),
+        }
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        match *self {
+            Either::Left(ref mut a) => a.write_buf_vectored(bufs),
+            Either::Right(ref mut b) => b.write_buf_vectored(bufs),
+        }
+    }
+}
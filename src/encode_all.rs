@@ -0,0 +1,27 @@
+use bytes::BytesMut;
+
+use codec::Encoder;
+
+/// Encodes all `items` into a single buffer, as if they were the entire
+/// contents of a stream.
+///
+/// `encode` is called for each item in order, followed by `encode_eof`,
+/// mirroring what `FramedWrite` writes out before it's closed.
+///
+/// This is mostly useful to test encoders by asserting the exact bytes they
+/// produce, without driving a `FramedWrite` over a mock I/O object.
+///
+/// # Errors
+///
+/// The first error returned by the encoder is returned.
+pub fn encode_all<E: Encoder>(mut codec: E, items: Vec<E::Item>) -> Result<BytesMut, E::Error> {
+    let mut buf = BytesMut::new();
+
+    for item in items {
+        try!(codec.encode(item, &mut buf));
+    }
+
+    try!(codec.encode_eof(&mut buf));
+
+    Ok(buf)
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which drops decoded frames that don't match a
+/// predicate.
+///
+/// Each frame produced by the inner decoder is passed to the predicate. If it
+/// returns `false` the frame is discarded and decoding continues with the
+/// remaining contents of the buffer, so filtered out frames are never
+/// yielded to the consumer of a `FramedRead` or `Framed`.
+///
+/// Encoding is passed through to the inner codec unchanged.
+pub struct FilterFrames<C, F> {
+    inner: C,
+    f: F,
+}
+
+impl<C, F> FilterFrames<C, F>
+    where C: Decoder,
+          F: FnMut(&C::Item) -> bool,
+{
+    /// Creates a new `FilterFrames` which only yields frames decoded by
+    /// `inner` for which `f` returns `true`.
+    pub fn new(inner: C, f: F) -> FilterFrames<C, F> {
+        FilterFrames {
+            inner: inner,
+            f: f,
+        }
+    }
+}
+
+impl<C, F> FilterFrames<C, F> {
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `FilterFrames`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, F> Decoder for FilterFrames<C, F>
+    where C: Decoder,
+          F: FnMut(&C::Item) -> bool,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        loop {
+            match try!(self.inner.decode(src)) {
+                Some(item) => {
+                    if (self.f)(&item) {
+                        return Ok(Some(item));
+                    }
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        loop {
+            match try!(self.inner.decode_eof(src)) {
+                Some(item) => {
+                    if (self.f)(&item) {
+                        return Ok(Some(item));
+                    }
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<C: Encoder, F> Encoder for FilterFrames<C, F> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug, F> fmt::Debug for FilterFrames<C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FilterFrames")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
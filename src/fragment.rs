@@ -0,0 +1,99 @@
+use std::fmt;
+
+use bytes::{Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+/// An `Encoder` wrapper which splits messages larger than a maximum fragment
+/// size into several frames.
+///
+/// The inner encoder receives `(fin, fragment)` pairs, where `fin` is `true`
+/// only for the last fragment of a message. Messages no larger than the
+/// maximum fragment size are passed on as a single fragment with `fin` set,
+/// and an empty message is encoded as one empty final fragment. The inner
+/// encoder is responsible for putting the FIN bit on the wire so the peer can
+/// reassemble the message.
+///
+/// Decoding is passed through to the inner codec unchanged.
+pub struct Fragment<C> {
+    inner: C,
+    max_fragment_size: usize,
+}
+
+impl<C> Fragment<C> {
+    /// Creates a new `Fragment` which passes fragments of at most
+    /// `max_fragment_size` bytes to `inner`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `max_fragment_size` is zero.
+    pub fn new(inner: C, max_fragment_size: usize) -> Fragment<C> {
+        assert!(max_fragment_size > 0, "max fragment size must be greater than zero");
+        Fragment {
+            inner: inner,
+            max_fragment_size: max_fragment_size,
+        }
+    }
+
+    /// Returns the maximum size of a single fragment.
+    pub fn max_fragment_size(&self) -> usize {
+        self.max_fragment_size
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `Fragment`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Decoder> Decoder for Fragment<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        self.inner.decode(src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        self.inner.decode_eof(src)
+    }
+}
+
+impl<C> Encoder for Fragment<C>
+    where C: Encoder<Item = (bool, Bytes)>,
+{
+    type Item = Bytes;
+    type Error = C::Error;
+
+    fn encode(&mut self, mut item: Bytes, dst: &mut BytesMut) -> Result<(), C::Error> {
+        while item.len() > self.max_fragment_size {
+            let fragment = item.split_to(self.max_fragment_size);
+            try!(self.inner.encode((false, fragment), dst));
+        }
+
+        self.inner.encode((true, item), dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Fragment<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Fragment")
+            .field("inner", &self.inner)
+            .field("max_fragment_size", &self.max_fragment_size)
+            .finish()
+    }
+}
@@ -1,12 +1,40 @@
+use std::collections::VecDeque;
 use std::io::{self, Read, Write};
 use std::fmt;
+use std::mem;
+use std::ops::{self, Range};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use {AsyncRead, AsyncWrite};
-use framed_read::{framed_read2, framed_read2_with_buffer, FramedRead2, Decoder};
+use framed_read::{framed_read2, framed_read2_with_buffer, framed_read2_with_source};
+use framed_read::{BufferSource, FramedRead2, Decoder, SliceDecoder};
 use framed_write::{framed_write2, framed_write2_with_buffer, FramedWrite2, Encoder};
+use profile_decode::ProfileDecode;
+use transform::Transform;
+use bytes_queue::BytesQueue;
+use with_raw::WithRaw;
 
-use futures::{Stream, Sink, StartSend, Poll};
-use bytes::{BytesMut};
+use futures::{Async, AsyncSink, Future, Stream, Sink, StartSend, Poll};
+use futures::task::{self, Task};
+use bytes::{Bytes, BytesMut};
+
+/// A codec which both decodes and encodes the same type of item, with the
+/// same error type.
+///
+/// This is implemented automatically for every type implementing both
+/// `Decoder` and `Encoder` with matching `Item` and `Error` types, and saves
+/// spelling out both traits and their equalities in generic code that works
+/// with "some codec". `Framed` itself doesn't require it, since its incoming
+/// and outgoing frame types may differ.
+pub trait Codec: Decoder + Encoder<Item = <Self as Decoder>::Item,
+                                   Error = <Self as Decoder>::Error> {
+}
+
+impl<T> Codec for T
+    where T: Decoder + Encoder<Item = <T as Decoder>::Item,
+                               Error = <T as Decoder>::Error>,
+{
+}
 
 /// A unified `Stream` and `Sink` interface to an underlying I/O object, using
 /// the `Encoder` and `Decoder` traits to encode and decode frames.
@@ -16,6 +44,179 @@ pub struct Framed<T, U> {
     inner: FramedRead2<FramedWrite2<Fuse<T, U>>>,
 }
 
+/// A `Stream` which yields decoding errors of a `Framed` as items rather than
+/// terminating.
+///
+/// Created by the `Framed::with_errors` method.
+pub struct WithErrors<T, U> {
+    inner: Framed<T, U>,
+}
+
+/// A `Framed` which stops yielding frames once a maximum number of them are
+/// in flight.
+///
+/// Each frame is yielded along with a `Release` handle, and counts as in
+/// flight until the handle is dropped, or consumed with `Release::release`.
+/// Once the limit is reached the stream returns `NotReady` without reading,
+/// and the task is notified when a frame is released. Since the handles are
+/// independent of the stream, frames can be released even after the stream
+/// has been moved into a combinator.
+///
+/// Created by the `Framed::with_max_in_flight` method.
+pub struct MaxInFlight<T, U> {
+    inner: Framed<T, U>,
+    max: usize,
+    state: Arc<Mutex<InFlightState>>,
+}
+
+/// A future which reads all frames from a `Framed` until EOF, resolving to
+/// the frames and the underlying I/O object.
+///
+/// Created by the `Framed::collect_frames` method.
+pub struct CollectFrames<T, U: Decoder> {
+    inner: Option<Framed<T, U>>,
+    frames: Vec<U::Item>,
+}
+
+/// A `Stream` which tags each frame of a `Framed` with a routing key.
+///
+/// As a `Stream`, this yields every frame in the order it was decoded, along
+/// with the key extracted from it. Consumers interested in a single key can
+/// use `poll_key` instead, which buffers frames for other keys until they are
+/// asked for. Frames with the same key are always yielded in the order they
+/// were decoded.
+///
+/// Buffered frames aren't limited in number, so every key that is received
+/// needs to be polled for eventually.
+///
+/// Created by the `Framed::route_by` method.
+pub struct Router<T, U: Decoder, F, K> {
+    inner: Framed<T, U>,
+    key_fn: F,
+    buffered: VecDeque<(K, U::Item)>,
+}
+
+/// A `Stream` which yields one final, synthetic frame when a `Framed` reaches
+/// the end of its stream.
+///
+/// Once the underlying I/O stream hits EOF and every buffered frame has been
+/// decoded and yielded, the frame built by the closure is yielded, and only
+/// then does the stream end. The closure is only called at a clean end of
+/// stream, and never more than once; errors end the stream as usual.
+///
+/// Created by the `Framed::with_eof_marker` method.
+pub struct EofMarker<T, U, F> {
+    inner: Framed<T, U>,
+    make: Option<F>,
+}
+
+/// A `Stream` which yields one frame of a `Framed` at a time, each along with
+/// an `Ack` handle.
+///
+/// Once a frame has been yielded, the stream returns `NotReady` without
+/// decoding anything further until its `Ack` is dropped, or consumed with
+/// `Ack::ack`, at which point the task is notified. This serializes the
+/// processing of frames, with exactly one frame in flight at a time and
+/// nothing decoded ahead of it.
+///
+/// Created by the `Framed::one_at_a_time` method.
+pub struct OneAtATime<T, U> {
+    inner: Framed<T, U>,
+    pending: Option<Arc<Mutex<AckState>>>,
+}
+
+/// A `Stream` which yields the frames of a `Framed` in batches.
+///
+/// Decoded frames are buffered until `n` of them have been collected, and
+/// then yielded together as one `Vec`. Batches are driven by count alone,
+/// so a partial batch is held back while the underlying stream isn't ready,
+/// and only yielded early once the stream ends, right before `None`.
+///
+/// If decoding fails, the error is returned right away, and frames decoded
+/// before it stay buffered for the next batch.
+///
+/// Created by the `Framed::chunk_frames` method.
+pub struct ChunkFrames<T, U: Decoder> {
+    inner: Framed<T, U>,
+    n: usize,
+    batch: Vec<U::Item>,
+    done: bool,
+}
+
+/// A `Framed` which calls a hook with an `ErrorContext` whenever its codec
+/// fails.
+///
+/// The hook is called with the error context right before the error is
+/// returned, for decode errors from `poll` and encode errors from
+/// `start_send`. Errors from the underlying I/O object, and from
+/// `encode_eof` while closing, are returned without calling it.
+///
+/// Created by the `Framed::on_error` method.
+pub struct OnError<T, U, F> {
+    inner: Framed<T, U>,
+    hook: F,
+    preview_len: usize,
+}
+
+/// Whether a codec error happened while decoding or encoding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ErrorDirection {
+    /// The error was returned by the decoder.
+    Decode,
+    /// The error was returned by the encoder.
+    Encode,
+}
+
+/// Context about a codec error, passed to the hook of `OnError`.
+///
+/// The buffer is the read buffer for decode errors, and the write buffer for
+/// encode errors, as the codec left it when it failed.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorContext<'a> {
+    direction: ErrorDirection,
+    buffer_len: usize,
+    preview: &'a [u8],
+}
+
+const DEFAULT_PREVIEW_LEN: usize = 32;
+
+/// A handle marking a frame yielded by `OneAtATime` as in flight.
+///
+/// Dropping the handle acknowledges the frame, which lets the stream decode
+/// the next one.
+pub struct Ack {
+    state: Arc<Mutex<AckState>>,
+}
+
+#[derive(Debug)]
+struct AckState {
+    acked: bool,
+    task: Option<Task>,
+}
+
+/// A handle marking a frame yielded by `MaxInFlight` as in flight.
+///
+/// Dropping the handle releases the frame, which lets the stream yield
+/// another one once it was stopped at the limit.
+pub struct Release {
+    state: Arc<Mutex<InFlightState>>,
+}
+
+#[derive(Debug)]
+struct InFlightState {
+    in_flight: usize,
+    task: Option<Task>,
+}
+
+/// A set of directions in which a `Framed` can make progress.
+///
+/// Returned by the `Framed::poll_ready` method.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Readiness(u8);
+
+const READABLE: u8 = 0b01;
+const WRITABLE: u8 = 0b10;
+
 pub struct Fuse<T, U>(pub T, pub U);
 
 pub fn framed<T, U>(inner: T, codec: U) -> Framed<T, U>
@@ -27,6 +228,33 @@ pub fn framed<T, U>(inner: T, codec: U) -> Framed<T, U>
     }
 }
 
+impl<T, U> Framed<T, U>
+    where T: AsyncRead + AsyncWrite,
+          U: Decoder + Encoder,
+{
+    /// Creates a new `Framed` over `inner` using `codec`.
+    ///
+    /// This is the same as the `AsyncRead::framed` adapter. The returned
+    /// `Stream` and `Sink` need both of the codec's error types to be
+    /// convertible from `io::Error`, which the `Decoder` and `Encoder` traits
+    /// already require of every codec.
+    pub fn new(inner: T, codec: U) -> Framed<T, U> {
+        framed(inner, codec)
+    }
+
+    /// Creates a new `Framed` over `inner` using `codec`, drawing its read
+    /// buffers from `source`.
+    ///
+    /// This is otherwise the same as the `AsyncRead::framed` adapter.
+    pub fn with_buffer_source<S>(inner: T, codec: U, source: S) -> Framed<T, U>
+        where S: BufferSource + Send + Sync + 'static,
+    {
+        Framed {
+            inner: framed_read2_with_source(framed_write2(Fuse(inner, codec)), Box::new(source)),
+        }
+    }
+}
+
 impl<T, U> Framed<T, U> {
     /// Provides a `Stream` and `Sink` interface for reading and writing to this
     /// `Io` object, using `Decode` and `Encode` to read and write the raw data.
@@ -75,6 +303,122 @@ impl<T, U> Framed<T, U> {
         &mut self.inner.get_mut().get_mut().0
     }
 
+    /// Pauses or resumes reading from the underlying I/O stream.
+    ///
+    /// While paused, frames that are already buffered continue to be yielded,
+    /// but once the buffer no longer contains a complete frame `poll` returns
+    /// `NotReady` instead of reading more data. This can be used to push
+    /// backpressure onto the peer when downstream consumers are full. The
+    /// task that observed this is notified when reading is resumed.
+    pub fn pause_reading(&mut self, paused: bool) {
+        self.inner.pause_reading(paused)
+    }
+
+    /// Converts this `Framed` into a `Stream` which yields decoding errors as
+    /// items instead of terminating.
+    ///
+    /// When the decoder returns an error, it is yielded as `Some(Err(e))` and
+    /// the next `poll` resumes decoding from whatever state the decoder left
+    /// the read buffer in. It is the decoder's responsibility to leave the
+    /// buffer in a state it can resynchronize from, for example by discarding
+    /// the offending bytes. If it doesn't, the next call to `poll` will most
+    /// likely yield the same error again.
+    ///
+    /// Errors from the underlying I/O stream still terminate the stream.
+    pub fn with_errors(self) -> WithErrors<T, U> {
+        WithErrors { inner: self }
+    }
+
+    /// Converts this `Framed` into one which allows at most `max` yielded
+    /// frames to be outstanding at a time.
+    ///
+    /// See `MaxInFlight` for details.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `max` is zero.
+    pub fn with_max_in_flight(self, max: usize) -> MaxInFlight<T, U> {
+        assert!(max > 0, "max in flight frames must be greater than zero");
+        MaxInFlight {
+            inner: self,
+            max: max,
+            state: Arc::new(Mutex::new(InFlightState {
+                in_flight: 0,
+                task: None,
+            })),
+        }
+    }
+
+    /// Converts this `Framed` into one which calls `hook` with the context of
+    /// every decode and encode error before returning it.
+    ///
+    /// See `OnError` for details.
+    pub fn on_error<F>(self, hook: F) -> OnError<T, U, F>
+        where F: FnMut(&ErrorContext),
+    {
+        OnError {
+            inner: self,
+            hook: hook,
+            preview_len: DEFAULT_PREVIEW_LEN,
+        }
+    }
+
+    /// Converts this `Framed` into a `Stream` which doesn't decode another
+    /// frame until the previous one has been acknowledged.
+    ///
+    /// See `OneAtATime` for details.
+    pub fn one_at_a_time(self) -> OneAtATime<T, U> {
+        OneAtATime {
+            inner: self,
+            pending: None,
+        }
+    }
+
+    /// Converts this `Framed` into one which yields each decoded frame along
+    /// with the raw bytes that were consumed from the read buffer to produce
+    /// it.
+    ///
+    /// Any data already buffered is preserved. See `WithRaw` for details on
+    /// how the raw bytes are captured.
+    pub fn with_raw(self) -> Framed<T, WithRaw<U>>
+        where U: Decoder,
+    {
+        let (parts, codec) = self.into_parts_and_codec();
+        Framed::from_parts(parts, WithRaw::new(codec))
+    }
+
+    /// Converts this `Framed` into one which times each call to the decoder.
+    ///
+    /// `clock` returns the current time in nanoseconds, and `sink` is called
+    /// with the nanoseconds elapsed during each decode, for example to feed a
+    /// latency histogram. Any data already buffered is preserved. See
+    /// `ProfileDecode` for details.
+    pub fn profile_decode<K, S>(self, clock: K, sink: S) -> Framed<T, ProfileDecode<U, K, S>>
+        where U: Decoder,
+              K: Fn() -> u64,
+              S: FnMut(u64),
+    {
+        let (parts, codec) = self.into_parts_and_codec();
+        Framed::from_parts(parts, ProfileDecode::new(codec, clock, sink))
+    }
+
+    /// Converts this `Framed` into one which converts decoded frames with
+    /// `decode_map` and items to send with `encode_map`.
+    ///
+    /// This is the same as calling `map` on the stream side and `with` on
+    /// the sink side, but produces a `Framed` again, so the result can still
+    /// be split, reconfigured, or taken apart. Any data already buffered is
+    /// preserved. See `Transform` for details.
+    pub fn transform<I, O, FD, FE>(self, decode_map: FD, encode_map: FE)
+                                   -> Framed<T, Transform<U, FD, FE, O>>
+        where U: Decoder + Encoder,
+              FD: FnMut(<U as Decoder>::Item) -> I,
+              FE: FnMut(O) -> <U as Encoder>::Item,
+    {
+        let (parts, codec) = self.into_parts_and_codec();
+        Framed::from_parts(parts, Transform::new(codec, decode_map, encode_map))
+    }
+
     /// Consumes the `Frame`, returning its underlying I/O stream.
     ///
     /// Note that care should be taken to not tamper with the underlying stream
@@ -87,6 +431,12 @@ impl<T, U> Framed<T, U> {
     /// Consumes the `Frame`, returning its underlying I/O stream and the buffer
     /// with unprocessed data.
     ///
+    /// The `readbuf` of the returned parts holds the bytes that were already
+    /// read from the I/O stream but not yet decoded. When switching from
+    /// frames to raw bytes, for example to stream a body following some
+    /// headers, reading should resume from that buffer before the stream,
+    /// such as with `Cursor::new(parts.readbuf).chain(parts.inner)`.
+    ///
     /// Note that care should be taken to not tamper with the underlying stream
     /// of data coming in as it may corrupt the stream of frames otherwise
     /// being worked with.
@@ -113,6 +463,178 @@ impl<T, U> Framed<T, U> {
     }
 }
 
+impl<T, U: Decoder> Framed<T, U> {
+    /// Returns whether a complete frame is buffered, so that polling the
+    /// stream would yield it without reading from the underlying I/O stream.
+    ///
+    /// This is a cheap check for scheduling decisions which doesn't decode or
+    /// consume anything. It relies on `Decoder::has_frame`, so for decoders
+    /// which don't implement it `false` means that the stream has to be
+    /// polled to find out.
+    pub fn has_complete_frame(&self) -> bool {
+        self.inner.has_frame()
+    }
+
+    /// Returns a future which reads frames until the stream ends, resolving to
+    /// all of them along with the underlying I/O object.
+    ///
+    /// This is like `Stream::collect`, but hands back the I/O object for
+    /// reuse. The future fails with the first error the stream yields, and
+    /// anything left in the read and write buffers is discarded when it
+    /// resolves.
+    pub fn collect_frames(self) -> CollectFrames<T, U> {
+        CollectFrames {
+            inner: Some(self),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Converts this `Framed` into a `Stream` of frames tagged with a key
+    /// extracted from each frame by `key_fn`.
+    ///
+    /// See `Router` for details.
+    pub fn route_by<K, F>(self, key_fn: F) -> Router<T, U, F, K>
+        where F: FnMut(&U::Item) -> K,
+    {
+        Router {
+            inner: self,
+            key_fn: key_fn,
+            buffered: VecDeque::new(),
+        }
+    }
+
+    /// Converts this `Framed` into a `Stream` which yields frames in batches
+    /// of `n`.
+    ///
+    /// See `ChunkFrames` for details.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `n` is zero.
+    pub fn chunk_frames(self, n: usize) -> ChunkFrames<T, U> {
+        assert!(n > 0, "batch size must be greater than zero");
+        ChunkFrames {
+            inner: self,
+            n: n,
+            batch: Vec::with_capacity(n),
+            done: false,
+        }
+    }
+
+    /// Converts this `Framed` into a `Stream` which yields the frame built by
+    /// `make` right before it ends.
+    ///
+    /// This gives downstream state machines an explicit end of stream item to
+    /// act on, such as to flush their own state. See `EofMarker` for details.
+    pub fn with_eof_marker<F>(self, make: F) -> EofMarker<T, U, F>
+        where F: FnOnce() -> U::Item,
+    {
+        EofMarker {
+            inner: self,
+            make: Some(make),
+        }
+    }
+}
+
+impl<T, U> Framed<T, U>
+    where T: AsyncRead + AsyncWrite,
+          U: Decoder,
+{
+    /// Polls for read and write readiness at once, without decoding or
+    /// encoding any frames.
+    ///
+    /// The returned `Readiness` is readable if data is buffered or has just
+    /// been read from the underlying I/O stream, or EOF has been reached, so
+    /// the next call to `Stream::poll` has something to decode. A complete
+    /// frame that is already buffered is therefore always readable, though a
+    /// readable `Framed` may still return `NotReady` from `poll` if the data
+    /// doesn't add up to a whole frame yet. It is writable if `start_send`
+    /// will accept a frame without applying backpressure, flushing buffered
+    /// frames first if necessary.
+    ///
+    /// If neither direction is ready, `NotReady` is returned and the current
+    /// task is notified once either of them may be.
+    pub fn poll_ready(&mut self) -> Poll<Readiness, io::Error> {
+        let mut ready = Readiness::empty();
+
+        if try!(self.inner.poll_read_ready()).is_ready() {
+            ready = ready | Readiness::readable();
+        }
+
+        if try!(self.inner.get_mut().poll_write_ready()).is_ready() {
+            ready = ready | Readiness::writable();
+        }
+
+        if ready.is_empty() {
+            Ok(Async::NotReady)
+        } else {
+            Ok(Async::Ready(ready))
+        }
+    }
+}
+
+impl<T, U: Encoder> Framed<T, U> {
+    /// Encodes `item` into the write buffer, returning the number of bytes
+    /// appended.
+    ///
+    /// Unlike `Sink::start_send` this never applies backpressure and never
+    /// writes to the underlying I/O stream, so the bytes are only buffered.
+    /// Combined with `write_buffer_len` this allows deciding exactly when to
+    /// call `poll_complete` based on how much data has accumulated.
+    pub fn encode_into_buffer(&mut self, item: <U as Encoder>::Item)
+                              -> Result<usize, <U as Encoder>::Error>
+    {
+        self.inner.get_mut().encode_item(item)
+    }
+}
+
+impl<T, U> Framed<T, U> {
+    /// Returns the number of encoded bytes waiting in the write buffer.
+    pub fn write_buffer_len(&self) -> usize {
+        self.inner.get_ref().buffer_len()
+    }
+}
+
+impl<T: AsyncWrite, U> Framed<T, U> {
+    /// Writes out all buffered frames and flushes the underlying I/O stream.
+    ///
+    /// Returns `NotReady` until the write buffer is empty and the flush has
+    /// completed. Unlike `Sink::close` this does not shut down the underlying
+    /// I/O stream, which makes it suitable for connections that will be
+    /// reused after this `Framed` is dropped or taken apart.
+    ///
+    /// Frames still sitting in the write buffer when a `Framed` is dropped are
+    /// lost, so this should be polled to completion before doing so.
+    pub fn ensure_flushed(&mut self) -> Poll<(), io::Error> {
+        self.inner.get_mut().poll_flush()
+    }
+}
+
+impl<T: AsyncRead, U: SliceDecoder> Framed<T, U> {
+    /// Calls `f` with each frame as a slice of the read buffer, until the
+    /// stream ends.
+    ///
+    /// This is an alternative to the `Stream` interface for parsers which
+    /// can work on borrowed data: frames are located with
+    /// `SliceDecoder::decode_slice` and never split off into buffers of
+    /// their own, so nothing is allocated per frame.
+    ///
+    /// Returns `Ready` once the underlying I/O stream has reached EOF and
+    /// every frame has been passed to `f`, and `NotReady` when more data
+    /// needs to be read, after passing every frame already buffered. Errors
+    /// returned by the decoder, the I/O stream or `f` are returned as is;
+    /// frames before an error have already been consumed.
+    ///
+    /// This can be freely mixed with the `Stream` interface, as both take
+    /// frames from the same read buffer: frames already buffered but not yet
+    /// taken through `poll` are passed to `f`.
+    pub fn for_each_frame<F>(&mut self, mut f: F) -> Poll<(), U::Error>
+        where F: FnMut(&[u8]) -> Result<(), U::Error>,
+    {
+        self.inner.poll_for_each_slice(&mut f)
+    }
+}
+
 impl<T, U> Stream for Framed<T, U>
     where T: AsyncRead,
           U: Decoder,
@@ -161,55 +683,881 @@ impl<T, U> fmt::Debug for Framed<T, U>
     }
 }
 
-// ===== impl Fuse =====
+// ===== impl WithErrors =====
 
-impl<T: Read, U> Read for Fuse<T, U> {
-    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
-        self.0.read(dst)
+impl<T, U> WithErrors<T, U> {
+    /// Returns a reference to the underlying `Framed`.
+    pub fn get_ref(&self) -> &Framed<T, U> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `Framed`.
+    pub fn get_mut(&mut self) -> &mut Framed<T, U> {
+        &mut self.inner
+    }
+
+    /// Consumes the `WithErrors`, returning the underlying `Framed`.
+    pub fn into_inner(self) -> Framed<T, U> {
+        self.inner
     }
 }
 
-impl<T: AsyncRead, U> AsyncRead for Fuse<T, U> {
-    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
-        self.0.prepare_uninitialized_buffer(buf)
+impl<T, U> Stream for WithErrors<T, U>
+    where T: AsyncRead,
+          U: Decoder,
+{
+    type Item = Result<U::Item, U::Error>;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, io::Error> {
+        self.inner.inner.poll_decode()
     }
 }
 
-impl<T: Write, U> Write for Fuse<T, U> {
-    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
-        self.0.write(src)
+impl<T, U> Sink for WithErrors<T, U>
+    where T: AsyncWrite,
+          U: Encoder,
+          U::Error: From<io::Error>,
+{
+    type SinkItem = U::Item;
+    type SinkError = U::Error;
+
+    fn start_send(&mut self,
+                  item: Self::SinkItem)
+                  -> StartSend<Self::SinkItem, Self::SinkError>
+    {
+        self.inner.start_send(item)
     }
 
-    fn flush(&mut self) -> io::Result<()> {
-        self.0.flush()
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
     }
 }
 
-impl<T: AsyncWrite, U> AsyncWrite for Fuse<T, U> {
-    fn shutdown(&mut self) -> Poll<(), io::Error> {
-        self.0.shutdown()
+impl<T, U> fmt::Debug for WithErrors<T, U>
+    where T: fmt::Debug,
+          U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WithErrors")
+         .field("inner", &self.inner)
+         .finish()
     }
 }
 
-impl<T, U: Decoder> Decoder for Fuse<T, U> {
-    type Item = U::Item;
-    type Error = U::Error;
+// ===== impl Readiness =====
 
-    fn decode(&mut self, buffer: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        self.1.decode(buffer)
+impl Readiness {
+    /// Returns a `Readiness` with no directions set.
+    pub fn empty() -> Readiness {
+        Readiness(0)
     }
 
-    fn decode_eof(&mut self, buffer: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        self.1.decode_eof(buffer)
+    /// Returns a `Readiness` with only the readable direction set.
+    pub fn readable() -> Readiness {
+        Readiness(READABLE)
+    }
+
+    /// Returns a `Readiness` with only the writable direction set.
+    pub fn writable() -> Readiness {
+        Readiness(WRITABLE)
+    }
+
+    /// Returns `true` if no directions are set.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if the readable direction is set.
+    pub fn is_readable(&self) -> bool {
+        self.0 & READABLE != 0
+    }
+
+    /// Returns `true` if the writable direction is set.
+    pub fn is_writable(&self) -> bool {
+        self.0 & WRITABLE != 0
     }
 }
 
-impl<T, U: Encoder> Encoder for Fuse<T, U> {
-    type Item = U::Item;
+impl ops::BitOr for Readiness {
+    type Output = Readiness;
+
+    fn bitor(self, other: Readiness) -> Readiness {
+        Readiness(self.0 | other.0)
+    }
+}
+
+// ===== impl CollectFrames =====
+
+impl<T, U> Future for CollectFrames<T, U>
+    where T: AsyncRead,
+          U: Decoder,
+{
+    type Item = (Vec<U::Item>, T);
     type Error = U::Error;
 
-    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        self.1.encode(item, dst)
+    fn poll(&mut self) -> Poll<Self::Item, U::Error> {
+        {
+            let inner = self.inner.as_mut().expect("cannot poll CollectFrames twice");
+            while let Some(frame) = try_ready!(inner.poll()) {
+                self.frames.push(frame);
+            }
+        }
+
+        let frames = ::std::mem::replace(&mut self.frames, Vec::new());
+        let inner = self.inner.take().unwrap().into_inner();
+        Ok(Async::Ready((frames, inner)))
+    }
+}
+
+impl<T, U> fmt::Debug for CollectFrames<T, U>
+    where T: fmt::Debug,
+          U: Decoder + fmt::Debug,
+          U::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CollectFrames")
+         .field("inner", &self.inner)
+         .field("frames", &self.frames)
+         .finish()
+    }
+}
+
+// ===== impl MaxInFlight =====
+
+impl<T, U> MaxInFlight<T, U> {
+    /// Returns the number of frames yielded but not yet released.
+    pub fn in_flight(&self) -> usize {
+        lock(&self.state).in_flight
+    }
+
+    /// Returns a reference to the underlying `Framed`.
+    pub fn get_ref(&self) -> &Framed<T, U> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `Framed`.
+    pub fn get_mut(&mut self) -> &mut Framed<T, U> {
+        &mut self.inner
+    }
+
+    /// Consumes the `MaxInFlight`, returning the underlying `Framed`.
+    pub fn into_inner(self) -> Framed<T, U> {
+        self.inner
+    }
+}
+
+impl<T, U> Stream for MaxInFlight<T, U>
+    where T: AsyncRead,
+          U: Decoder,
+{
+    type Item = (U::Item, Release);
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        {
+            let mut state = lock(&self.state);
+
+            if state.in_flight >= self.max {
+                // Wait for a frame to be released before yielding another one.
+                state.task = Some(task::current());
+                return Ok(Async::NotReady);
+            }
+        }
+
+        match try_ready!(self.inner.poll()) {
+            Some(item) => {
+                lock(&self.state).in_flight += 1;
+                Ok(Async::Ready(Some((item, Release { state: self.state.clone() }))))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+impl<T, U> Sink for MaxInFlight<T, U>
+    where T: AsyncWrite,
+          U: Encoder,
+          U::Error: From<io::Error>,
+{
+    type SinkItem = U::Item;
+    type SinkError = U::Error;
+
+    fn start_send(&mut self,
+                  item: Self::SinkItem)
+                  -> StartSend<Self::SinkItem, Self::SinkError>
+    {
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, U> fmt::Debug for MaxInFlight<T, U>
+    where T: fmt::Debug,
+          U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MaxInFlight")
+         .field("inner", &self.inner)
+         .field("max", &self.max)
+         .field("in_flight", &self.in_flight())
+         .finish()
+    }
+}
+
+// ===== impl Router =====
+
+impl<T, U: Decoder, F, K> Router<T, U, F, K> {
+    /// Returns the number of frames decoded by `poll_key` that are waiting to
+    /// be yielded.
+    pub fn buffered_len(&self) -> usize {
+        self.buffered.len()
+    }
+
+    /// Returns a reference to the underlying `Framed`.
+    pub fn get_ref(&self) -> &Framed<T, U> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `Framed`.
+    pub fn get_mut(&mut self) -> &mut Framed<T, U> {
+        &mut self.inner
+    }
+
+    /// Consumes the `Router`, returning the underlying `Framed`.
+    ///
+    /// Any buffered frames are discarded.
+    pub fn into_inner(self) -> Framed<T, U> {
+        self.inner
+    }
+}
+
+impl<T, U, F, K> Router<T, U, F, K>
+    where T: AsyncRead,
+          U: Decoder,
+          F: FnMut(&U::Item) -> K,
+          K: PartialEq,
+{
+    /// Attempts to yield the next frame with the routing key `key`.
+    ///
+    /// Frames for other keys that are decoded in the meantime are buffered,
+    /// and are yielded by later calls to `poll_key` or `poll`. Once the
+    /// underlying stream has ended, this returns `None` if no frame for `key`
+    /// is buffered.
+    pub fn poll_key(&mut self, key: &K) -> Poll<Option<U::Item>, U::Error> {
+        if let Some(pos) = self.buffered.iter().position(|&(ref k, _)| k == key) {
+            let (_, item) = self.buffered.remove(pos).unwrap();
+            return Ok(Async::Ready(Some(item)));
+        }
+
+        loop {
+            let item = match try_ready!(self.inner.poll()) {
+                Some(item) => item,
+                None => return Ok(Async::Ready(None)),
+            };
+
+            let k = (self.key_fn)(&item);
+
+            if k == *key {
+                return Ok(Async::Ready(Some(item)));
+            }
+
+            self.buffered.push_back((k, item));
+        }
+    }
+}
+
+impl<T, U, F, K> Stream for Router<T, U, F, K>
+    where T: AsyncRead,
+          U: Decoder,
+          F: FnMut(&U::Item) -> K,
+{
+    type Item = (K, U::Item);
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Some(frame) = self.buffered.pop_front() {
+            return Ok(Async::Ready(Some(frame)));
+        }
+
+        match try_ready!(self.inner.poll()) {
+            Some(item) => {
+                let k = (self.key_fn)(&item);
+                Ok(Async::Ready(Some((k, item))))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+impl<T, U, F, K> fmt::Debug for Router<T, U, F, K>
+    where T: fmt::Debug,
+          U: Decoder + fmt::Debug,
+          U::Item: fmt::Debug,
+          K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Router")
+         .field("inner", &self.inner)
+         .field("buffered", &self.buffered)
+         .finish()
+    }
+}
+
+// ===== impl EofMarker =====
+
+impl<T, U, F> EofMarker<T, U, F> {
+    /// Returns a reference to the underlying `Framed`.
+    pub fn get_ref(&self) -> &Framed<T, U> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `Framed`.
+    pub fn get_mut(&mut self) -> &mut Framed<T, U> {
+        &mut self.inner
+    }
+
+    /// Consumes the `EofMarker`, returning the underlying `Framed`.
+    pub fn into_inner(self) -> Framed<T, U> {
+        self.inner
+    }
+}
+
+impl<T, U, F> Stream for EofMarker<T, U, F>
+    where T: AsyncRead,
+          U: Decoder,
+          F: FnOnce() -> U::Item,
+{
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<Option<U::Item>, U::Error> {
+        match try_ready!(self.inner.poll()) {
+            Some(item) => Ok(Async::Ready(Some(item))),
+            None => Ok(Async::Ready(self.make.take().map(|make| make()))),
+        }
+    }
+}
+
+impl<T, U, F> Sink for EofMarker<T, U, F>
+    where T: AsyncWrite,
+          U: Encoder,
+          U::Error: From<io::Error>,
+{
+    type SinkItem = U::Item;
+    type SinkError = U::Error;
+
+    fn start_send(&mut self,
+                  item: Self::SinkItem)
+                  -> StartSend<Self::SinkItem, Self::SinkError>
+    {
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, U, F> fmt::Debug for EofMarker<T, U, F>
+    where T: fmt::Debug,
+          U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EofMarker")
+         .field("inner", &self.inner)
+         .field("fired", &self.make.is_none())
+         .finish()
+    }
+}
+
+// ===== impl OneAtATime =====
+
+impl<T, U> OneAtATime<T, U> {
+    /// Returns whether a yielded frame has yet to be acknowledged.
+    pub fn awaiting_ack(&self) -> bool {
+        match self.pending {
+            Some(ref state) => !lock(state).acked,
+            None => false,
+        }
+    }
+
+    /// Returns a reference to the underlying `Framed`.
+    pub fn get_ref(&self) -> &Framed<T, U> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `Framed`.
+    pub fn get_mut(&mut self) -> &mut Framed<T, U> {
+        &mut self.inner
+    }
+
+    /// Consumes the `OneAtATime`, returning the underlying `Framed`.
+    pub fn into_inner(self) -> Framed<T, U> {
+        self.inner
+    }
+}
+
+// The state is never left inconsistent, so a poisoned lock is still usable
+fn lock<S>(state: &Mutex<S>) -> MutexGuard<S> {
+    match state.lock() {
+        Ok(guard) => guard,
+        Err(e) => e.into_inner(),
+    }
+}
+
+impl<T, U> Stream for OneAtATime<T, U>
+    where T: AsyncRead,
+          U: Decoder,
+{
+    type Item = (U::Item, Ack);
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, U::Error> {
+        if let Some(state) = self.pending.take() {
+            let mut guard = lock(&state);
+
+            if !guard.acked {
+                guard.task = Some(task::current());
+                drop(guard);
+                self.pending = Some(state);
+                return Ok(Async::NotReady);
+            }
+        }
+
+        match try_ready!(self.inner.poll()) {
+            Some(item) => {
+                let state = Arc::new(Mutex::new(AckState {
+                    acked: false,
+                    task: None,
+                }));
+                self.pending = Some(state.clone());
+                Ok(Async::Ready(Some((item, Ack { state: state }))))
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+impl<T, U> Sink for OneAtATime<T, U>
+    where T: AsyncWrite,
+          U: Encoder,
+          U::Error: From<io::Error>,
+{
+    type SinkItem = U::Item;
+    type SinkError = U::Error;
+
+    fn start_send(&mut self,
+                  item: Self::SinkItem)
+                  -> StartSend<Self::SinkItem, Self::SinkError>
+    {
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, U> fmt::Debug for OneAtATime<T, U>
+    where T: fmt::Debug,
+          U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OneAtATime")
+         .field("inner", &self.inner)
+         .field("awaiting_ack", &self.awaiting_ack())
+         .finish()
+    }
+}
+
+// ===== impl ChunkFrames =====
+
+impl<T, U: Decoder> ChunkFrames<T, U> {
+    /// Returns the number of frames decoded for the next batch.
+    pub fn buffered_len(&self) -> usize {
+        self.batch.len()
+    }
+
+    /// Returns a reference to the underlying `Framed`.
+    pub fn get_ref(&self) -> &Framed<T, U> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `Framed`.
+    pub fn get_mut(&mut self) -> &mut Framed<T, U> {
+        &mut self.inner
+    }
+
+    /// Consumes the `ChunkFrames`, returning the underlying `Framed`.
+    ///
+    /// Frames buffered for the next batch are dropped.
+    pub fn into_inner(self) -> Framed<T, U> {
+        self.inner
+    }
+}
+
+impl<T, U> Stream for ChunkFrames<T, U>
+    where T: AsyncRead,
+          U: Decoder,
+{
+    type Item = Vec<U::Item>;
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<Option<Vec<U::Item>>, U::Error> {
+        while !self.done {
+            match try_ready!(self.inner.poll()) {
+                Some(item) => {
+                    self.batch.push(item);
+
+                    if self.batch.len() == self.n {
+                        let batch = mem::replace(&mut self.batch, Vec::with_capacity(self.n));
+                        return Ok(Async::Ready(Some(batch)));
+                    }
+                }
+                None => self.done = true,
+            }
+        }
+
+        if self.batch.is_empty() {
+            return Ok(Async::Ready(None));
+        }
+
+        Ok(Async::Ready(Some(mem::replace(&mut self.batch, Vec::new()))))
+    }
+}
+
+impl<T, U> Sink for ChunkFrames<T, U>
+    where T: AsyncWrite,
+          U: Decoder + Encoder,
+          <U as Encoder>::Error: From<io::Error>,
+{
+    type SinkItem = <U as Encoder>::Item;
+    type SinkError = <U as Encoder>::Error;
+
+    fn start_send(&mut self,
+                  item: Self::SinkItem)
+                  -> StartSend<Self::SinkItem, Self::SinkError>
+    {
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, U> fmt::Debug for ChunkFrames<T, U>
+    where T: fmt::Debug,
+          U: Decoder + fmt::Debug,
+          U::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChunkFrames")
+         .field("inner", &self.inner)
+         .field("n", &self.n)
+         .field("batch", &self.batch)
+         .finish()
+    }
+}
+
+// ===== impl OnError =====
+
+impl<T, U, F> OnError<T, U, F>
+    where F: FnMut(&ErrorContext),
+{
+    /// Sets the maximum number of buffered bytes included in the preview of
+    /// an `ErrorContext`.
+    ///
+    /// The default is 32 bytes.
+    pub fn set_preview_len(&mut self, len: usize) {
+        self.preview_len = len;
+    }
+
+    /// Returns a reference to the underlying `Framed`.
+    pub fn get_ref(&self) -> &Framed<T, U> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `Framed`.
+    pub fn get_mut(&mut self) -> &mut Framed<T, U> {
+        &mut self.inner
+    }
+
+    /// Consumes the `OnError`, returning the underlying `Framed`.
+    pub fn into_inner(self) -> Framed<T, U> {
+        self.inner
+    }
+}
+
+fn report_error<F>(hook: &mut F, direction: ErrorDirection, buf: &[u8], preview_len: usize)
+    where F: FnMut(&ErrorContext),
+{
+    let preview = &buf[..::std::cmp::min(buf.len(), preview_len)];
+
+    hook(&ErrorContext {
+        direction: direction,
+        buffer_len: buf.len(),
+        preview: preview,
+    });
+}
+
+impl<T, U, F> Stream for OnError<T, U, F>
+    where T: AsyncRead,
+          U: Decoder,
+          F: FnMut(&ErrorContext),
+{
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<Option<U::Item>, U::Error> {
+        match try_ready!(self.inner.inner.poll_decode()) {
+            Some(Ok(item)) => Ok(Async::Ready(Some(item))),
+            Some(Err(e)) => {
+                report_error(&mut self.hook,
+                             ErrorDirection::Decode,
+                             self.inner.inner.buffer(),
+                             self.preview_len);
+                Err(e)
+            }
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+impl<T, U, F> Sink for OnError<T, U, F>
+    where T: AsyncWrite,
+          U: Encoder,
+          U::Error: From<io::Error>,
+          F: FnMut(&ErrorContext),
+{
+    type SinkItem = U::Item;
+    type SinkError = U::Error;
+
+    fn start_send(&mut self,
+                  item: Self::SinkItem)
+                  -> StartSend<Self::SinkItem, Self::SinkError>
+    {
+        let write = self.inner.inner.get_mut();
+
+        if !try!(write.poll_write_ready()).is_ready() {
+            return Ok(AsyncSink::NotReady(item));
+        }
+
+        match write.encode_item(item) {
+            Ok(_) => Ok(AsyncSink::Ready),
+            Err(e) => {
+                report_error(&mut self.hook,
+                             ErrorDirection::Encode,
+                             write.write_buffer(),
+                             self.preview_len);
+                Err(e)
+            }
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, U, F> fmt::Debug for OnError<T, U, F>
+    where T: fmt::Debug,
+          U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OnError")
+         .field("inner", &self.inner)
+         .field("preview_len", &self.preview_len)
+         .finish()
+    }
+}
+
+// ===== impl ErrorContext =====
+
+impl<'a> ErrorContext<'a> {
+    /// Returns whether the error happened while decoding or encoding.
+    pub fn direction(&self) -> ErrorDirection {
+        self.direction
+    }
+
+    /// Returns the number of bytes in the buffer.
+    pub fn buffer_len(&self) -> usize {
+        self.buffer_len
+    }
+
+    /// Returns the first bytes of the buffer, up to the preview length set
+    /// with `OnError::set_preview_len`.
+    pub fn preview(&self) -> &'a [u8] {
+        self.preview
+    }
+
+    /// Returns the preview formatted as lowercase hex, with a space between
+    /// each byte, such as `"de ad be ef"`.
+    pub fn hex_preview(&self) -> String {
+        let mut s = String::with_capacity(self.preview.len() * 3);
+
+        for (i, b) in self.preview.iter().enumerate() {
+            if i > 0 {
+                s.push(' ');
+            }
+            s.push_str(&format!("{:02x}", b));
+        }
+
+        s
+    }
+}
+
+// ===== impl Ack =====
+
+impl Ack {
+    /// Acknowledges the frame, the same as dropping the handle.
+    pub fn ack(self) {}
+}
+
+impl Drop for Ack {
+    fn drop(&mut self) {
+        let mut state = lock(&self.state);
+        state.acked = true;
+
+        if let Some(task) = state.task.take() {
+            task.notify();
+        }
+    }
+}
+
+impl fmt::Debug for Ack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Ack").finish()
+    }
+}
+
+// ===== impl Release =====
+
+impl Release {
+    /// Releases the frame, the same as dropping the handle.
+    pub fn release(self) {}
+}
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        let mut state = lock(&self.state);
+        state.in_flight -= 1;
+
+        if let Some(task) = state.task.take() {
+            task.notify();
+        }
+    }
+}
+
+impl fmt::Debug for Release {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Release").finish()
+    }
+}
+
+// ===== impl Fuse =====
+
+impl<T: Read, U> Read for Fuse<T, U> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        self.0.read(dst)
+    }
+}
+
+impl<T: AsyncRead, U> AsyncRead for Fuse<T, U> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.0.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: Write, U> Write for Fuse<T, U> {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        self.0.write(src)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<T: AsyncWrite, U> AsyncWrite for Fuse<T, U> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.0.shutdown()
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        self.0.write_bytes(bytes)
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        self.0.write_buf_vectored(bufs)
+    }
+}
+
+impl<T, U: Decoder> Decoder for Fuse<T, U> {
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn decode(&mut self, buffer: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.1.decode(buffer)
+    }
+
+    fn decode_eof(&mut self, buffer: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.1.decode_eof(buffer)
+    }
+
+    fn has_frame(&self, buffer: &BytesMut) -> bool {
+        self.1.has_frame(buffer)
+    }
+}
+
+impl<T, U: SliceDecoder> SliceDecoder for Fuse<T, U> {
+    fn decode_slice(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, Self::Error> {
+        self.1.decode_slice(src)
+    }
+
+    fn decode_slice_eof(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, Self::Error> {
+        self.1.decode_slice_eof(src)
+    }
+}
+
+impl<T, U: Encoder> Encoder for Fuse<T, U> {
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.1.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.1.encode_eof(dst)
     }
 }
 
@@ -1,9 +1,11 @@
-use std::{fmt, io};
+use std::{cmp, fmt, io};
+use std::ops::Range;
 
 use AsyncRead;
 use framed::Fuse;
 
 use futures::{Async, Poll, Stream, Sink, StartSend};
+use futures::task::{self, Task};
 use bytes::BytesMut;
 
 /// Decoding of frames via buffers.
@@ -88,6 +90,85 @@ pub trait Decoder {
             }
         }
     }
+
+    /// Returns whether `buf` holds at least one complete frame, without
+    /// decoding it or otherwise changing any state.
+    ///
+    /// This backs `Framed::has_complete_frame`, which allows deciding whether
+    /// a stream is worth polling right away. It's meant to be cheap, such as
+    /// checking a length prefix against the number of buffered bytes, so
+    /// decoders for which that isn't possible shouldn't implement it.
+    ///
+    /// This method defaults to returning `false`, which means that it's
+    /// unknown whether a frame is available, and the stream has to be polled
+    /// to find out.
+    fn has_frame(&self, _buf: &BytesMut) -> bool {
+        false
+    }
+}
+
+/// A `Decoder` which can locate frames in the read buffer without removing
+/// them from it.
+///
+/// This allows `Framed::for_each_frame` to hand out each frame as a slice of
+/// the read buffer, for parsers which don't need to own the data of a frame.
+pub trait SliceDecoder: Decoder {
+    /// Attempts to locate a frame at the start of `src`.
+    ///
+    /// If an entire frame is available, this returns the range of the frame's
+    /// contents within `src`, along with the total number of bytes taken up
+    /// by the frame, including any header or delimiter. The caller skips past
+    /// those bytes before calling this method again. If a frame isn't fully
+    /// available yet, `Ok(None)` is returned, and this method is called again
+    /// with the same bytes once more have been read.
+    ///
+    /// As with `decode`, an error indicates that the stream is corrupt.
+    fn decode_slice(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, Self::Error>;
+
+    /// Attempts to locate a frame at the start of `src` once there are no
+    /// more bytes available to be read from the underlying I/O.
+    ///
+    /// This method defaults to calling `decode_slice` and returns an error if
+    /// `Ok(None)` is returned while `src` isn't empty, mirroring
+    /// `Decoder::decode_eof`.
+    fn decode_slice_eof(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, Self::Error> {
+        match try!(self.decode_slice(src)) {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                if src.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(io::Error::new(io::ErrorKind::Other,
+                                       "bytes remaining on stream").into())
+                }
+            }
+        }
+    }
+}
+
+/// A source of read buffers for `FramedRead` and `Framed`.
+///
+/// By default the framing combinators allocate their read buffer with
+/// `BytesMut::with_capacity`. Supplying a `BufferSource` instead allows
+/// buffers to be drawn from a pool, for example, to reduce allocator pressure
+/// when many connections come and go.
+///
+/// A buffer is taken from the source when the combinator is created, and again
+/// whenever the current buffer has no room left to read into, which is
+/// typically the case once all of its contents have been split off into
+/// yielded frames. Any unconsumed data is copied over into the new buffer.
+///
+/// Sources are required to be `Send` and `Sync` when supplied, so that the
+/// combinators holding them remain so too.
+pub trait BufferSource {
+    /// Returns an empty buffer with a capacity of at least `min` bytes.
+    fn take(&mut self, min: usize) -> BytesMut;
+}
+
+impl<F: FnMut(usize) -> BytesMut> BufferSource for F {
+    fn take(&mut self, min: usize) -> BytesMut {
+        self(min)
+    }
 }
 
 /// A `Stream` of messages decoded from an `AsyncRead`.
@@ -99,7 +180,15 @@ pub struct FramedRead2<T> {
     inner: T,
     eof: bool,
     is_readable: bool,
+    paused: bool,
+    task: Option<Task>,
     buffer: BytesMut,
+    source: Option<Box<BufferSource + Send + Sync>>,
+    shrink_threshold: Option<usize>,
+    baseline_capacity: usize,
+    strict_eof: bool,
+    // Whether the last read returned zero bytes without confirming EOF
+    zero_read: bool,
 }
 
 const INITIAL_CAPACITY: usize = 8 * 1024;
@@ -116,6 +205,16 @@ impl<T, D> FramedRead<T, D>
             inner: framed_read2(Fuse(inner, decoder)),
         }
     }
+
+    /// Creates a new `FramedRead` with the given `decoder`, drawing its read
+    /// buffers from `source`.
+    pub fn with_buffer_source<S>(inner: T, decoder: D, source: S) -> FramedRead<T, D>
+        where S: BufferSource + Send + Sync + 'static,
+    {
+        FramedRead {
+            inner: framed_read2_with_source(Fuse(inner, decoder), Box::new(source)),
+        }
+    }
 }
 
 impl<T, D> FramedRead<T, D> {
@@ -148,6 +247,74 @@ impl<T, D> FramedRead<T, D> {
         self.inner.inner.0
     }
 
+    /// Pauses or resumes reading from the underlying I/O stream.
+    ///
+    /// While paused, frames that are already buffered continue to be yielded,
+    /// but once the buffer no longer contains a complete frame `poll` returns
+    /// `NotReady` instead of reading more data. The task that observed this
+    /// is notified when reading is resumed.
+    pub fn pause_reading(&mut self, paused: bool) {
+        self.inner.pause_reading(paused)
+    }
+
+    /// Sets the read buffer capacity above which the buffer is shrunk back
+    /// to the baseline capacity.
+    ///
+    /// Decoding a large frame grows the read buffer to fit it, and by default
+    /// that allocation is kept for the rest of the stream's life. With a
+    /// threshold set, whenever a frame is decoded while the buffer's capacity
+    /// exceeds `threshold` and it holds less than the baseline capacity of
+    /// data, the buffer is reallocated at the baseline capacity.
+    ///
+    /// Passing `None` disables shrinking, which is the default.
+    pub fn set_read_buffer_shrink_threshold(&mut self, threshold: Option<usize>) {
+        self.inner.set_shrink_threshold(threshold)
+    }
+
+    /// Sets the capacity the read buffer is shrunk back to.
+    ///
+    /// This defaults to the initial capacity of the read buffer and only has
+    /// an effect if a shrink threshold is set.
+    pub fn set_read_buffer_baseline_capacity(&mut self, capacity: usize) {
+        self.inner.set_baseline_capacity(capacity)
+    }
+
+    /// Sets whether EOF has to be confirmed by two zero length reads in a
+    /// row.
+    ///
+    /// A read returning zero bytes normally marks the end of the stream.
+    /// Some misbehaving readers return zero bytes spuriously though, which
+    /// would end the stream early, possibly in the middle of a frame. With
+    /// strict EOF enabled, a zero length read is followed by another read,
+    /// and only a second zero length read ends the stream. Data returned
+    /// instead is decoded as usual.
+    ///
+    /// The cost is an extra read at every EOF. This is disabled by default.
+    pub fn set_strict_eof(&mut self, strict: bool) {
+        self.inner.set_strict_eof(strict)
+    }
+
+    /// Returns the current capacity of the read buffer.
+    pub fn read_buffer_capacity(&self) -> usize {
+        self.inner.buffer_capacity()
+    }
+
+    /// Installs `buf` as the read buffer.
+    ///
+    /// This allows a buffer to be allocated ahead of time, for example from a
+    /// pool when a connection is accepted, instead of when it's first needed.
+    /// The next read from the underlying I/O stream goes into `buf`'s spare
+    /// capacity. Any data already in `buf` is treated as read from the stream
+    /// and decoded first.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the current read buffer holds data that hasn't
+    /// been decoded yet, since that data would otherwise be lost.
+    pub fn set_read_buffer(&mut self, buf: BytesMut) {
+        self.inner.set_buffer(buf)
+    }
+
     /// Returns a reference to the underlying decoder.
     pub fn decoder(&self) -> &D {
         &self.inner.inner.1
@@ -159,6 +326,18 @@ impl<T, D> FramedRead<T, D> {
     }
 }
 
+impl<T, D: Decoder> FramedRead<T, D> {
+    /// Returns whether a complete frame is buffered, so that polling the
+    /// stream would yield it without reading from the underlying I/O stream.
+    ///
+    /// Nothing is decoded or consumed by this check, which relies on
+    /// `Decoder::has_frame`, so a `false` may only mean that the decoder
+    /// can't tell.
+    pub fn has_complete_frame(&self) -> bool {
+        self.inner.has_frame()
+    }
+}
+
 impl<T, D> Stream for FramedRead<T, D>
     where T: AsyncRead,
           D: Decoder,
@@ -208,6 +387,11 @@ impl<T, D> fmt::Debug for FramedRead<T, D>
     }
 }
 
+// Unwraps a `FramedRead` into its `FramedRead2`, keeping all of its settings.
+pub fn framed_read_inner<T, D>(framed: FramedRead<T, D>) -> FramedRead2<Fuse<T, D>> {
+    framed.inner
+}
+
 // ===== impl FramedRead2 =====
 
 pub fn framed_read2<T>(inner: T) -> FramedRead2<T> {
@@ -215,7 +399,30 @@ pub fn framed_read2<T>(inner: T) -> FramedRead2<T> {
         inner: inner,
         eof: false,
         is_readable: false,
+        paused: false,
+        task: None,
         buffer: BytesMut::with_capacity(INITIAL_CAPACITY),
+        source: None,
+        shrink_threshold: None,
+        baseline_capacity: INITIAL_CAPACITY,
+        strict_eof: false,
+        zero_read: false,
+    }
+}
+
+pub fn framed_read2_with_source<T>(inner: T, mut source: Box<BufferSource + Send + Sync>) -> FramedRead2<T> {
+    FramedRead2 {
+        inner: inner,
+        eof: false,
+        is_readable: false,
+        paused: false,
+        task: None,
+        buffer: source.take(INITIAL_CAPACITY),
+        source: Some(source),
+        shrink_threshold: None,
+        baseline_capacity: INITIAL_CAPACITY,
+        strict_eof: false,
+        zero_read: false,
     }
 }
 
@@ -228,7 +435,14 @@ pub fn framed_read2_with_buffer<T>(inner: T, mut buf: BytesMut) -> FramedRead2<T
         inner: inner,
         eof: false,
         is_readable: buf.len() > 0,
+        paused: false,
+        task: None,
         buffer: buf,
+        source: None,
+        shrink_threshold: None,
+        baseline_capacity: INITIAL_CAPACITY,
+        strict_eof: false,
+        zero_read: false,
     }
 }
 
@@ -245,18 +459,99 @@ impl<T> FramedRead2<T> {
         (self.inner, self.buffer)
     }
 
+    // Replaces the inner object with the result of `f`, keeping the read
+    // buffer, the state and every setting.
+    pub fn map_inner<U, F: FnOnce(T) -> U>(self, f: F) -> FramedRead2<U> {
+        FramedRead2 {
+            inner: f(self.inner),
+            eof: self.eof,
+            is_readable: self.is_readable,
+            paused: self.paused,
+            task: self.task,
+            buffer: self.buffer,
+            source: self.source,
+            shrink_threshold: self.shrink_threshold,
+            baseline_capacity: self.baseline_capacity,
+            strict_eof: self.strict_eof,
+            zero_read: self.zero_read,
+        }
+    }
+
     pub fn get_mut(&mut self) -> &mut T {
         &mut self.inner
     }
+
+    pub fn set_shrink_threshold(&mut self, threshold: Option<usize>) {
+        self.shrink_threshold = threshold;
+    }
+
+    pub fn set_baseline_capacity(&mut self, capacity: usize) {
+        self.baseline_capacity = capacity;
+    }
+
+    pub fn set_strict_eof(&mut self, strict: bool) {
+        self.strict_eof = strict;
+    }
+
+    pub fn buffer_capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    pub fn buffer(&self) -> &BytesMut {
+        &self.buffer
+    }
+
+    pub fn set_buffer(&mut self, buf: BytesMut) {
+        assert!(self.buffer.is_empty(), "read buffer replaced while holding undecoded data");
+        self.is_readable = !buf.is_empty();
+        self.buffer = buf;
+    }
+
+    // Reallocates the read buffer down to the baseline capacity if it grew
+    // past the shrink threshold and is now mostly empty.
+    fn maybe_shrink(&mut self) {
+        let threshold = match self.shrink_threshold {
+            Some(threshold) => threshold,
+            None => return,
+        };
+
+        if self.buffer.capacity() <= threshold || self.buffer.len() >= self.baseline_capacity {
+            return;
+        }
+
+        trace!("shrinking read buffer; capacity={}", self.buffer.capacity());
+
+        let mut buffer = match self.source {
+            Some(ref mut source) => source.take(self.baseline_capacity),
+            None => BytesMut::with_capacity(self.baseline_capacity),
+        };
+        buffer.extend_from_slice(&self.buffer);
+        self.buffer = buffer;
+    }
+
+    pub fn pause_reading(&mut self, paused: bool) {
+        self.paused = paused;
+
+        if !paused {
+            if let Some(task) = self.task.take() {
+                task.notify();
+            }
+        }
+    }
 }
 
-impl<T> Stream for FramedRead2<T>
+impl<T: Decoder> FramedRead2<T> {
+    pub fn has_frame(&self) -> bool {
+        self.inner.has_frame(&self.buffer)
+    }
+}
+
+impl<T> FramedRead2<T>
     where T: AsyncRead + Decoder,
 {
-    type Item = T::Item;
-    type Error = T::Error;
-
-    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+    // Decodes the next frame, keeping errors returned by the decoder apart
+    // from errors returned by the underlying I/O.
+    pub fn poll_decode(&mut self) -> Poll<Option<Result<T::Item, T::Error>>, io::Error> {
         loop {
             // Repeatedly call `decode` or `decode_eof` as long as it is
             // "readable". Readable is defined as not having returned `None`. If
@@ -265,15 +560,22 @@ impl<T> Stream for FramedRead2<T>
             // readable again, at which point the stream is terminated.
             if self.is_readable {
                 if self.eof {
-                    let frame = try!(self.inner.decode_eof(&mut self.buffer));
-                    return Ok(Async::Ready(frame));
+                    return Ok(Async::Ready(match self.inner.decode_eof(&mut self.buffer) {
+                        Ok(frame) => frame.map(Ok),
+                        Err(e) => Some(Err(e)),
+                    }));
                 }
 
                 trace!("attempting to decode a frame");
 
-                if let Some(frame) = try!(self.inner.decode(&mut self.buffer)) {
-                    trace!("frame decoded from buffer");
-                    return Ok(Async::Ready(Some(frame)));
+                match self.inner.decode(&mut self.buffer) {
+                    Ok(Some(frame)) => {
+                        trace!("frame decoded from buffer");
+                        self.maybe_shrink();
+                        return Ok(Async::Ready(Some(Ok(frame))));
+                    }
+                    Ok(None) => {}
+                    Err(e) => return Ok(Async::Ready(Some(Err(e)))),
                 }
 
                 self.is_readable = false;
@@ -281,15 +583,137 @@ impl<T> Stream for FramedRead2<T>
 
             assert!(!self.eof);
 
-            // Otherwise, try to read more data and try again. Make sure we've
-            // got room for at least one byte to read to ensure that we don't
-            // get a spurious 0 that looks like EOF
-            self.buffer.reserve(1);
-            if 0 == try_ready!(self.inner.read_buf(&mut self.buffer)) {
+            // Don't pull any more bytes off the underlying I/O while paused,
+            // wake up the current task once reading is resumed instead.
+            if self.paused {
+                self.task = Some(task::current());
+                return Ok(Async::NotReady);
+            }
+
+            // Otherwise, try to read more data and try again.
+            try_ready!(self.fill_buffer());
+        }
+    }
+
+    // Passes each frame located by the decoder to `f` as a slice of the read
+    // buffer, reading more data as needed, until the stream ends.
+    pub fn poll_for_each_slice<F>(&mut self, f: &mut F) -> Poll<(), T::Error>
+        where T: SliceDecoder,
+              F: FnMut(&[u8]) -> Result<(), T::Error>,
+    {
+        loop {
+            if self.is_readable {
+                let mut consumed = 0;
+                let mut res = Ok(());
+
+                loop {
+                    let frame = {
+                        let src = &self.buffer[consumed..];
+                        if self.eof {
+                            self.inner.decode_slice_eof(src)
+                        } else {
+                            self.inner.decode_slice(src)
+                        }
+                    };
+
+                    match frame {
+                        Ok(Some((range, len))) => {
+                            let start = consumed;
+                            consumed += len;
+                            res = f(&self.buffer[start + range.start..start + range.end]);
+                            if res.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(e) => {
+                            res = Err(e);
+                            break;
+                        }
+                    }
+                }
+
+                // Skip past every frame at once, which doesn't allocate
+                self.buffer.advance(consumed);
+                try!(res);
+
+                if self.eof {
+                    return Ok(Async::Ready(()));
+                }
+
+                self.is_readable = false;
+            }
+
+            if self.paused {
+                self.task = Some(task::current());
+                return Ok(Async::NotReady);
+            }
+
+            try_ready!(self.fill_buffer());
+        }
+    }
+
+    // Ready if the next call to `poll` has buffered data to decode or has
+    // reached EOF, reading more data into the buffer if needed. No frames
+    // are decoded.
+    pub fn poll_read_ready(&mut self) -> Poll<(), io::Error> {
+        if self.is_readable {
+            return Ok(Async::Ready(()));
+        }
+
+        if self.paused {
+            self.task = Some(task::current());
+            return Ok(Async::NotReady);
+        }
+
+        self.fill_buffer()
+    }
+
+    fn fill_buffer(&mut self) -> Poll<(), io::Error> {
+        // Make sure we've got room for at least one byte to read to ensure
+        // that we don't get a spurious 0 that looks like EOF
+        if self.buffer.len() == self.buffer.capacity() {
+            if let Some(ref mut source) = self.source {
+                let mut buffer = source.take(cmp::max(self.buffer.len() + 1, INITIAL_CAPACITY));
+                buffer.extend_from_slice(&self.buffer);
+                self.buffer = buffer;
+            }
+        }
+        self.buffer.reserve(1);
+
+        loop {
+            let n = try_ready!(self.inner.read_buf(&mut self.buffer));
+
+            if n == 0 && self.strict_eof && !self.zero_read {
+                // Read again to tell a spurious zero length read from EOF
+                self.zero_read = true;
+                continue;
+            }
+
+            self.zero_read = false;
+
+            if n == 0 {
                 self.eof = true;
             }
+            break;
+        }
+
+        self.is_readable = true;
+        Ok(Async::Ready(()))
+    }
+}
 
-            self.is_readable = true;
+impl<T> Stream for FramedRead2<T>
+    where T: AsyncRead + Decoder,
+{
+    type Item = T::Item;
+    type Error = T::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match try_ready!(self.poll_decode()) {
+            Some(Ok(frame)) => Ok(Async::Ready(Some(frame))),
+            Some(Err(e)) => Err(e),
+            None => Ok(Async::Ready(None)),
         }
     }
 }
@@ -0,0 +1,287 @@
+use std::io::{self, Read, Write};
+use std::fmt;
+
+use {AsyncRead, AsyncWrite};
+use bytes_queue::BytesQueue;
+use framed::Fuse;
+use framed_read::{framed_read2, framed_read_inner, FramedRead, FramedRead2, Decoder};
+use framed_write::{framed_write2, framed_write_inner, FramedWrite, FramedWrite2, Encoder};
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, Sink, StartSend, Poll};
+
+/// A unified `Stream` and `Sink` interface over a separate reader and writer,
+/// using the `Encoder` and `Decoder` traits to encode and decode frames.
+///
+/// This is like `Framed`, except that frames are read from `R` and written to
+/// `W` rather than both going through a single I/O object.
+///
+/// Created by the [`framed_split`] function.
+///
+/// [`framed_split`]: fn.framed_split.html
+pub struct Framed2<R, W, U> {
+    inner: FramedRead2<FramedWrite2<Fuse<Pair<R, W>, U>>>,
+}
+
+/// A codec made up of a separate decoder and encoder.
+///
+/// Decoding goes to the `Decoder` and encoding to the `Encoder`, so the
+/// incoming and outgoing frames may be of entirely different types.
+///
+/// Created by the [`Framed2::from_read_write`] function.
+///
+/// [`Framed2::from_read_write`]: struct.Framed2.html#method.from_read_write
+#[derive(Debug)]
+pub struct CodecPair<D, E> {
+    decoder: D,
+    encoder: E,
+}
+
+struct Pair<R, W> {
+    rd: R,
+    wr: W,
+}
+
+/// Creates a `Framed2` which decodes frames read from `rd` and encodes frames
+/// to be written to `wr`, both using `codec`.
+///
+/// This is useful for framing a pair of distinct objects as a single
+/// transport, for example stdin and stdout, or two one-directional pipes.
+pub fn framed_split<R, W, U>(rd: R, wr: W, codec: U) -> Framed2<R, W, U>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          U: Decoder + Encoder,
+{
+    let pair = Pair { rd: rd, wr: wr };
+
+    Framed2 {
+        inner: framed_read2(framed_write2(Fuse(pair, codec))),
+    }
+}
+
+impl<R, W, D, E> Framed2<R, W, CodecPair<D, E>>
+    where R: AsyncRead,
+          W: AsyncWrite,
+          D: Decoder,
+          E: Encoder,
+{
+    /// Combines a `FramedRead` and a `FramedWrite` into a single `Framed2`.
+    ///
+    /// This allows each half to be created and configured on its own, for
+    /// example over the two halves of a split transport, and then be used as
+    /// one `Stream` and `Sink`. Frames are decoded from the `FramedRead`'s
+    /// reader with its decoder, and encoded with the `FramedWrite`'s encoder
+    /// to its writer. Each half is carried over intact, including any
+    /// buffered data and settings such as strict EOF handling, read buffer
+    /// shrinking, the buffer source, the max pending frames or vectored
+    /// writes.
+    ///
+    /// The decoder and encoder don't need to agree on anything: the
+    /// `Stream` yields the decoder's items and errors, and the `Sink` takes
+    /// the encoder's items and returns its errors. As with `FramedRead` and
+    /// `FramedWrite`, both error types need to be convertible from
+    /// `io::Error`.
+    pub fn from_read_write(read: FramedRead<R, D>, write: FramedWrite<W, E>)
+                           -> Framed2<R, W, CodecPair<D, E>>
+    {
+        let write = framed_write_inner(write);
+
+        Framed2 {
+            inner: framed_read_inner(read).map_inner(|Fuse(rd, decoder)| {
+                write.map_inner(|Fuse(wr, encoder)| {
+                    let pair = Pair { rd: rd, wr: wr };
+                    let codec = CodecPair { decoder: decoder, encoder: encoder };
+                    Fuse(pair, codec)
+                })
+            }),
+        }
+    }
+}
+
+impl<R, W, U> Framed2<R, W, U> {
+    /// Returns references to the underlying reader and writer.
+    ///
+    /// Note that care should be taken to not tamper with the underlying
+    /// streams of data as it may corrupt the stream of frames otherwise being
+    /// worked with.
+    pub fn get_ref(&self) -> (&R, &W) {
+        let pair = &self.inner.get_ref().get_ref().0;
+        (&pair.rd, &pair.wr)
+    }
+
+    /// Returns mutable references to the underlying reader and writer.
+    ///
+    /// Note that care should be taken to not tamper with the underlying
+    /// streams of data as it may corrupt the stream of frames otherwise being
+    /// worked with.
+    pub fn get_mut(&mut self) -> (&mut R, &mut W) {
+        let pair = &mut self.inner.get_mut().get_mut().0;
+        (&mut pair.rd, &mut pair.wr)
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn codec(&self) -> &U {
+        &self.inner.get_ref().get_ref().1
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn codec_mut(&mut self) -> &mut U {
+        &mut self.inner.get_mut().get_mut().1
+    }
+
+    /// Consumes the `Framed2`, returning the underlying reader and writer.
+    ///
+    /// Note that any buffered data which hasn't been decoded or written yet
+    /// is lost.
+    pub fn into_inner(self) -> (R, W) {
+        let pair = self.inner.into_inner().into_inner().0;
+        (pair.rd, pair.wr)
+    }
+}
+
+impl<R, W, U> Stream for Framed2<R, W, U>
+    where R: AsyncRead,
+          U: Decoder,
+{
+    type Item = U::Item;
+    type Error = U::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.inner.poll()
+    }
+}
+
+impl<R, W, U> Sink for Framed2<R, W, U>
+    where W: AsyncWrite,
+          U: Encoder,
+{
+    type SinkItem = U::Item;
+    type SinkError = U::Error;
+
+    fn start_send(&mut self,
+                  item: Self::SinkItem)
+                  -> StartSend<Self::SinkItem, Self::SinkError>
+    {
+        self.inner.get_mut().start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.get_mut().poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.get_mut().close()
+    }
+}
+
+impl<R, W, U> fmt::Debug for Framed2<R, W, U>
+    where R: fmt::Debug,
+          W: fmt::Debug,
+          U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fuse = self.inner.get_ref().get_ref();
+        f.debug_struct("Framed2")
+         .field("reader", &fuse.0.rd)
+         .field("writer", &fuse.0.wr)
+         .field("codec", &fuse.1)
+         .finish()
+    }
+}
+
+// ===== impl CodecPair =====
+
+impl<D, E> CodecPair<D, E> {
+    /// Returns a reference to the decoder.
+    pub fn decoder(&self) -> &D {
+        &self.decoder
+    }
+
+    /// Returns a mutable reference to the decoder.
+    pub fn decoder_mut(&mut self) -> &mut D {
+        &mut self.decoder
+    }
+
+    /// Returns a reference to the encoder.
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// Returns a mutable reference to the encoder.
+    pub fn encoder_mut(&mut self) -> &mut E {
+        &mut self.encoder
+    }
+
+    /// Consumes the `CodecPair`, returning the decoder and encoder.
+    pub fn into_inner(self) -> (D, E) {
+        (self.decoder, self.encoder)
+    }
+}
+
+impl<D: Decoder, E> Decoder for CodecPair<D, E> {
+    type Item = D::Item;
+    type Error = D::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<D::Item>, D::Error> {
+        self.decoder.decode(src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<D::Item>, D::Error> {
+        self.decoder.decode_eof(src)
+    }
+
+    fn has_frame(&self, src: &BytesMut) -> bool {
+        self.decoder.has_frame(src)
+    }
+}
+
+impl<D, E: Encoder> Encoder for CodecPair<D, E> {
+    type Item = E::Item;
+    type Error = E::Error;
+
+    fn encode(&mut self, item: E::Item, dst: &mut BytesMut) -> Result<(), E::Error> {
+        self.encoder.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), E::Error> {
+        self.encoder.encode_eof(dst)
+    }
+}
+
+// ===== impl Pair =====
+
+impl<R: Read, W> Read for Pair<R, W> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        self.rd.read(dst)
+    }
+}
+
+impl<R: AsyncRead, W> AsyncRead for Pair<R, W> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.rd.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<R, W: Write> Write for Pair<R, W> {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        self.wr.write(src)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.wr.flush()
+    }
+}
+
+impl<R, W: AsyncWrite> AsyncWrite for Pair<R, W> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.wr.shutdown()
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        self.wr.write_bytes(bytes)
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        self.wr.write_buf_vectored(bufs)
+    }
+}
@@ -1,12 +1,14 @@
 use std::io::{self, Read};
 use std::fmt;
+use std::ops::Range;
 
 use {AsyncRead, AsyncWrite};
-use codec::Decoder;
+use bytes_queue::BytesQueue;
+use codec::{Decoder, SliceDecoder};
 use framed::Fuse;
 
-use futures::{Async, AsyncSink, Poll, Stream, Sink, StartSend};
-use bytes::BytesMut;
+use futures::{Async, AsyncSink, Future, Poll, Stream, Sink, StartSend};
+use bytes::{Bytes, BytesMut};
 
 /// Trait of helper objects to write out messages as bytes, for use with
 /// `FramedWrite`.
@@ -27,6 +29,35 @@ pub trait Encoder {
     /// will be written out when possible.
     fn encode(&mut self, item: Self::Item, dst: &mut BytesMut)
               -> Result<(), Self::Error>;
+
+    /// Encodes a final terminator into the buffer provided.
+    ///
+    /// This method is called once by `FramedWrite` when the sink is closed,
+    /// before the buffer is flushed and the underlying I/O object is shut
+    /// down. Protocols that mark the end of a stream with a trailing sequence
+    /// that isn't a normal frame can write that sequence here.
+    ///
+    /// The default implementation does nothing.
+    fn encode_eof(&mut self, _dst: &mut BytesMut) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// An `Encoder` whose frames are a header, which only depends on the length
+/// of the payload, followed by the payload as is.
+///
+/// Length delimited framing is the typical example. This allows a large
+/// payload to be written out without encoding it as a whole, such as with
+/// `FramedWrite::chunked_send`.
+///
+/// None of the codecs in this crate implement this trait, so it's meant for
+/// user codecs. The `length_delimited` module doesn't go through `Encoder`
+/// at all; to stream a large payload with it, use
+/// `length_delimited::FramedWrite::begin_frame` and `write_frame_chunk`
+/// instead.
+pub trait HeaderEncoder: Encoder {
+    /// Encodes the header of a frame whose payload is `len` bytes long.
+    fn encode_header(&mut self, len: usize, dst: &mut BytesMut) -> Result<(), Self::Error>;
 }
 
 /// A `Sink` of frames encoded to an `AsyncWrite`.
@@ -34,9 +65,54 @@ pub struct FramedWrite<T, E> {
     inner: FramedWrite2<Fuse<T, E>>,
 }
 
+/// A `FramedWrite` which only accepts frames when a user supplied gate allows
+/// it.
+///
+/// Created by the `FramedWrite::pace` method.
+pub struct Pace<T, E, F> {
+    inner: FramedWrite<T, E>,
+    gate: F,
+}
+
+/// A `FramedWrite` which writes a heartbeat frame on each idle tick.
+///
+/// Created by the `FramedWrite::with_heartbeat` method.
+pub struct Heartbeat<T, E, F> {
+    inner: FramedWrite<T, E>,
+    make: F,
+    sent: bool,
+}
+
+/// A `FramedWrite` which drops frames equal to the frame queued just before
+/// them.
+///
+/// Created by the `FramedWrite::dedup_consecutive` method.
+pub struct DedupConsecutive<T, E: Encoder> {
+    inner: FramedWrite<T, E>,
+    last: Option<E::Item>,
+}
+
+/// A future which writes one large payload as a single frame, a chunk at a
+/// time, resolving to the `FramedWrite` once it has been written out.
+///
+/// Created by the `FramedWrite::chunked_send` method.
+pub struct ChunkedSend<T, E> {
+    inner: Option<FramedWrite<T, E>>,
+    item: Bytes,
+    chunk_size: usize,
+    header_encoded: bool,
+}
+
 pub struct FramedWrite2<T> {
     inner: T,
+    eof_encoded: bool,
     buffer: BytesMut,
+    pending_frames: usize,
+    max_pending_frames: Option<usize>,
+    // In vectored mode, encoded frames are moved out of `buffer` into here,
+    // each as its own `Bytes`, and written with `write_buf_vectored`
+    vectored: bool,
+    frames: BytesQueue,
 }
 
 const INITIAL_CAPACITY: usize = 8 * 1024;
@@ -93,6 +169,157 @@ impl<T, E> FramedWrite<T, E> {
     pub fn encoder_mut(&mut self) -> &mut E {
         &mut self.inner.inner.1
     }
+
+    /// Appends an already encoded buffer to the data waiting to be written.
+    ///
+    /// The buffer is written out by the next calls to `poll_complete` or
+    /// `close`, after any frames queued before it, and frames sent after it
+    /// are written after it, so mixing `queue_encoded` with `start_send`
+    /// preserves ordering. When nothing else is waiting to be written the
+    /// buffer is used as is rather than copied.
+    ///
+    /// Unlike `start_send` this never applies backpressure, so callers
+    /// queueing many buffers should drive `poll_complete` themselves.
+    pub fn queue_encoded(&mut self, buf: BytesMut) {
+        self.inner.queue(buf)
+    }
+
+    /// Limits the number of frames that may be encoded but not yet written.
+    ///
+    /// Once `max` encoded frames are waiting in the write buffer,
+    /// `start_send` attempts to flush, and returns
+    /// `AsyncSink::NotReady` if some of them are still unwritten. The count
+    /// resets whenever the write buffer is completely written out.
+    ///
+    /// This applies in addition to the byte based backpressure, and is useful
+    /// when frames are uniformly small. A `max` of `0` is treated as `1`.
+    pub fn set_max_pending_frames(&mut self, max: usize) {
+        let max = if max == 0 { 1 } else { max };
+        self.inner.set_max_pending_frames(Some(max))
+    }
+
+    /// Sets whether frames are written with vectored writes.
+    ///
+    /// By default encoded frames are appended to a single write buffer, which
+    /// is written with `write`. In vectored mode each encoded frame is instead
+    /// split off into its own `Bytes` and queued, and the queue is written
+    /// with `AsyncWrite::write_buf_vectored`. Transports which override that
+    /// method to submit all queued buffers at once, such as with `writev`,
+    /// then write frames without them ever being copied together. Buffers
+    /// passed to `queue_encoded` are queued the same way.
+    ///
+    /// Transports without vectored writes fall back to the default
+    /// implementation, which copies the queued frames together before
+    /// writing them, so this should only be enabled for transports which
+    /// support it.
+    pub fn set_vectored_writes(&mut self, vectored: bool) {
+        self.inner.set_vectored(vectored)
+    }
+}
+
+impl<T, E: Encoder> FramedWrite<T, E> {
+    /// Encodes `item` into a new buffer and returns it, without queueing it
+    /// for writing.
+    ///
+    /// The buffer can be inspected, modified or pooled, and then handed back
+    /// with `queue_encoded` to be written.
+    pub fn encode(&mut self, item: E::Item) -> Result<BytesMut, E::Error> {
+        let mut buf = BytesMut::new();
+        try!(self.inner.inner.1.encode(item, &mut buf));
+        Ok(buf)
+    }
+
+    /// Gates each frame sent through the returned sink on `gate`.
+    ///
+    /// Before accepting a frame, `start_send` calls `gate`, and returns
+    /// `NotReady` with the frame if it returns `false`. This allows pacing
+    /// outgoing frames without this crate depending on a timer: the gate is
+    /// typically fed by the caller's own timer, which is then also
+    /// responsible for notifying the task once sending may resume.
+    ///
+    /// The gate is only consulted once a frame could otherwise be accepted
+    /// without applying backpressure, so every `true` it returns lets exactly
+    /// one frame through. Frames that were already accepted are still written
+    /// out in full by `poll_complete` while the gate is closed.
+    pub fn pace<F>(self, gate: F) -> Pace<T, E, F>
+        where F: FnMut() -> bool,
+    {
+        Pace {
+            inner: self,
+            gate: gate,
+        }
+    }
+
+    /// Inserts heartbeat frames into the returned sink while it is idle.
+    ///
+    /// The caller drives heartbeats by calling `Heartbeat::tick` from its own
+    /// timer. If no frame was sent through the sink since the previous tick,
+    /// the tick encodes a frame built by `make`, so this crate doesn't need
+    /// to own a timer to keep connections alive.
+    pub fn with_heartbeat<F>(self, make: F) -> Heartbeat<T, E, F>
+        where F: FnMut() -> E::Item,
+    {
+        Heartbeat {
+            inner: self,
+            make: make,
+            sent: false,
+        }
+    }
+
+    /// Drops frames which are equal to the previous frame still waiting to
+    /// be written.
+    ///
+    /// Each frame passed to `start_send` on the returned sink is compared to
+    /// the last frame it accepted, and if they're equal the frame is
+    /// discarded without being encoded and `AsyncSink::Ready` is returned.
+    /// Only that single frame is remembered, so the check is O(1), and it's
+    /// forgotten once `poll_complete` or `close` has written everything out,
+    /// so a frame repeated after a flush is sent again. This suits protocols
+    /// publishing state, where sending an unchanged value twice in a row is
+    /// wasted bandwidth.
+    ///
+    /// Accepted frames are cloned to be kept for the comparison.
+    pub fn dedup_consecutive(self) -> DedupConsecutive<T, E>
+        where E::Item: PartialEq + Clone,
+    {
+        DedupConsecutive {
+            inner: self,
+            last: None,
+        }
+    }
+}
+
+impl<T, E: HeaderEncoder> FramedWrite<T, E> {
+    /// Writes `item` as the payload of a single frame, copying at most
+    /// `chunk_size` bytes of it into the write buffer at a time.
+    ///
+    /// Sending a large payload with `start_send` encodes all of it into the
+    /// write buffer at once. The returned future instead encodes the header
+    /// for the full length of `item` up front, and then appends the next
+    /// chunk of the payload only once the write buffer has been written
+    /// out, so that no more than one chunk is buffered at a time. Frames
+    /// already buffered are written before the header.
+    ///
+    /// The future resolves to this `FramedWrite` once the whole frame has
+    /// been written and flushed.
+    ///
+    /// This requires a codec implementing `HeaderEncoder`, which none of the
+    /// codecs in this crate do. Length delimited frames written with
+    /// `length_delimited::FramedWrite` are streamed with its `begin_frame`
+    /// and `write_frame_chunk` methods instead.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `chunk_size` is zero.
+    pub fn chunked_send(self, item: Bytes, chunk_size: usize) -> ChunkedSend<T, E> {
+        assert!(chunk_size > 0, "chunk size must be greater than zero");
+        ChunkedSend {
+            inner: Some(self),
+            item: item,
+            chunk_size: chunk_size,
+            header_encoded: false,
+        }
+    }
 }
 
 impl<T, E> Sink for FramedWrite<T, E>
@@ -115,6 +342,285 @@ impl<T, E> Sink for FramedWrite<T, E>
     }
 }
 
+impl<T, E, F> Pace<T, E, F> {
+    /// Returns a reference to the underlying `FramedWrite`.
+    pub fn get_ref(&self) -> &FramedWrite<T, E> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `FramedWrite`.
+    pub fn get_mut(&mut self) -> &mut FramedWrite<T, E> {
+        &mut self.inner
+    }
+
+    /// Consumes the `Pace`, returning the underlying `FramedWrite`.
+    pub fn into_inner(self) -> FramedWrite<T, E> {
+        self.inner
+    }
+}
+
+impl<T, E, F> Sink for Pace<T, E, F>
+    where T: AsyncWrite,
+          E: Encoder,
+          F: FnMut() -> bool,
+{
+    type SinkItem = E::Item;
+    type SinkError = E::Error;
+
+    fn start_send(&mut self, item: E::Item) -> StartSend<E::Item, E::Error> {
+        if !try!(self.inner.inner.poll_write_ready()).is_ready() {
+            return Ok(AsyncSink::NotReady(item));
+        }
+
+        if !(self.gate)() {
+            // Keep writing out frames that were already accepted
+            try!(self.inner.poll_complete());
+            return Ok(AsyncSink::NotReady(item));
+        }
+
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, E, F> fmt::Debug for Pace<T, E, F>
+    where T: fmt::Debug,
+          E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pace")
+         .field("inner", &self.inner)
+         .finish()
+    }
+}
+
+impl<T, E, F> Heartbeat<T, E, F> {
+    /// Returns a reference to the underlying `FramedWrite`.
+    pub fn get_ref(&self) -> &FramedWrite<T, E> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `FramedWrite`.
+    ///
+    /// Frames sent directly through the returned reference don't count as
+    /// traffic for the purpose of skipping heartbeats.
+    pub fn get_mut(&mut self) -> &mut FramedWrite<T, E> {
+        &mut self.inner
+    }
+
+    /// Consumes the `Heartbeat`, returning the underlying `FramedWrite`.
+    pub fn into_inner(self) -> FramedWrite<T, E> {
+        self.inner
+    }
+}
+
+impl<T, E, F> Heartbeat<T, E, F>
+    where E: Encoder,
+          F: FnMut() -> E::Item,
+{
+    /// Signals that an idle period has elapsed.
+    ///
+    /// If no frame was sent since the previous tick, a heartbeat frame is
+    /// encoded and queued behind any frames waiting to be written. Like
+    /// `FramedWrite::queue_encoded`, this doesn't write anything itself; the
+    /// heartbeat goes out on the next call to `poll_complete`.
+    pub fn tick(&mut self) -> Result<(), E::Error> {
+        if self.sent {
+            self.sent = false;
+            return Ok(());
+        }
+
+        let item = (self.make)();
+        try!(self.inner.inner.encode_item(item));
+        Ok(())
+    }
+}
+
+impl<T, E, F> Sink for Heartbeat<T, E, F>
+    where T: AsyncWrite,
+          E: Encoder,
+{
+    type SinkItem = E::Item;
+    type SinkError = E::Error;
+
+    fn start_send(&mut self, item: E::Item) -> StartSend<E::Item, E::Error> {
+        let res = try!(self.inner.start_send(item));
+
+        if res.is_ready() {
+            self.sent = true;
+        }
+
+        Ok(res)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<T, E, F> fmt::Debug for Heartbeat<T, E, F>
+    where T: fmt::Debug,
+          E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Heartbeat")
+         .field("inner", &self.inner)
+         .field("sent", &self.sent)
+         .finish()
+    }
+}
+
+impl<T, E: Encoder> DedupConsecutive<T, E> {
+    /// Returns a reference to the underlying `FramedWrite`.
+    pub fn get_ref(&self) -> &FramedWrite<T, E> {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying `FramedWrite`.
+    ///
+    /// Frames sent directly through the returned reference aren't compared
+    /// against, nor remembered for later comparisons.
+    pub fn get_mut(&mut self) -> &mut FramedWrite<T, E> {
+        &mut self.inner
+    }
+
+    /// Consumes the `DedupConsecutive`, returning the underlying
+    /// `FramedWrite`.
+    pub fn into_inner(self) -> FramedWrite<T, E> {
+        self.inner
+    }
+}
+
+impl<T, E> Sink for DedupConsecutive<T, E>
+    where T: AsyncWrite,
+          E: Encoder,
+          E::Item: PartialEq + Clone,
+{
+    type SinkItem = E::Item;
+    type SinkError = E::Error;
+
+    fn start_send(&mut self, item: E::Item) -> StartSend<E::Item, E::Error> {
+        if self.last.as_ref() == Some(&item) {
+            trace!("dropping duplicate frame");
+            return Ok(AsyncSink::Ready);
+        }
+
+        let res = try!(self.inner.start_send(item.clone()));
+
+        if res.is_ready() {
+            self.last = Some(item);
+        }
+
+        Ok(res)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        try_ready!(self.inner.poll_complete());
+        self.last = None;
+        Ok(Async::Ready(()))
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        try_ready!(self.inner.close());
+        self.last = None;
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<T, E> fmt::Debug for DedupConsecutive<T, E>
+    where T: fmt::Debug,
+          E: Encoder + fmt::Debug,
+          E::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DedupConsecutive")
+         .field("inner", &self.inner)
+         .field("last", &self.last)
+         .finish()
+    }
+}
+
+// ===== impl ChunkedSend =====
+
+impl<T, E> ChunkedSend<T, E> {
+    /// Returns the number of payload bytes not yet buffered for writing.
+    pub fn remaining(&self) -> usize {
+        self.item.len()
+    }
+
+    /// Returns a reference to the underlying `FramedWrite`.
+    pub fn get_ref(&self) -> &FramedWrite<T, E> {
+        self.inner.as_ref().expect("attempted to use ChunkedSend after completion")
+    }
+
+    /// Returns a mutable reference to the underlying `FramedWrite`.
+    ///
+    /// Frames sent through the returned reference would be written in the
+    /// middle of the payload, corrupting the stream.
+    pub fn get_mut(&mut self) -> &mut FramedWrite<T, E> {
+        self.inner.as_mut().expect("attempted to use ChunkedSend after completion")
+    }
+}
+
+impl<T, E> Future for ChunkedSend<T, E>
+    where T: AsyncWrite,
+          E: HeaderEncoder,
+{
+    type Item = FramedWrite<T, E>;
+    type Error = E::Error;
+
+    fn poll(&mut self) -> Poll<FramedWrite<T, E>, E::Error> {
+        {
+            let inner = &mut self.inner.as_mut().expect("cannot poll ChunkedSend twice").inner;
+
+            if !self.header_encoded {
+                try!(inner.inner.1.encode_header(self.item.len(), &mut inner.buffer));
+                inner.pending_frames += 1;
+                inner.frames_from_buffer();
+                self.header_encoded = true;
+            }
+
+            loop {
+                try_ready!(inner.poll_flush());
+
+                if self.item.is_empty() {
+                    break;
+                }
+
+                let n = ::std::cmp::min(self.chunk_size, self.item.len());
+                trace!("buffering payload chunk; len={} remaining={}", n, self.item.len());
+                inner.queue(self.item.split_to(n).into());
+            }
+        }
+
+        Ok(Async::Ready(self.inner.take().unwrap()))
+    }
+}
+
+impl<T, E> fmt::Debug for ChunkedSend<T, E>
+    where T: fmt::Debug,
+          E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChunkedSend")
+         .field("inner", &self.inner)
+         .field("remaining", &self.item.len())
+         .field("chunk_size", &self.chunk_size)
+         .finish()
+    }
+}
+
 impl<T, D> Stream for FramedWrite<T, D>
     where T: Stream,
 {
@@ -139,12 +645,22 @@ impl<T, U> fmt::Debug for FramedWrite<T, U>
     }
 }
 
+// Unwraps a `FramedWrite` into its `FramedWrite2`, keeping all of its settings.
+pub fn framed_write_inner<T, E>(framed: FramedWrite<T, E>) -> FramedWrite2<Fuse<T, E>> {
+    framed.inner
+}
+
 // ===== impl FramedWrite2 =====
 
 pub fn framed_write2<T>(inner: T) -> FramedWrite2<T> {
     FramedWrite2 {
         inner: inner,
+        eof_encoded: false,
         buffer: BytesMut::with_capacity(INITIAL_CAPACITY),
+        pending_frames: 0,
+        max_pending_frames: None,
+        vectored: false,
+        frames: BytesQueue::new(),
     }
 }
 
@@ -155,7 +671,12 @@ pub fn framed_write2_with_buffer<T>(inner: T, mut buf: BytesMut) -> FramedWrite2
     }
     FramedWrite2 {
         inner: inner,
+        eof_encoded: false,
         buffer: buf,
+        pending_frames: 0,
+        max_pending_frames: None,
+        vectored: false,
+        frames: BytesQueue::new(),
     }
 }
 
@@ -169,62 +690,204 @@ impl<T> FramedWrite2<T> {
     }
 
     pub fn into_parts(self) -> (T, BytesMut) {
-        (self.inner, self.buffer)
+        if self.frames.is_empty() {
+            return (self.inner, self.buffer);
+        }
+
+        let mut buf = BytesMut::with_capacity(self.frames.len() + self.buffer.len());
+        for frame in self.frames.chunks() {
+            buf.extend_from_slice(frame);
+        }
+        buf.extend_from_slice(&self.buffer);
+        (self.inner, buf)
+    }
+
+    // Replaces the inner object with the result of `f`, keeping the buffered
+    // frames and every setting.
+    pub fn map_inner<U, F: FnOnce(T) -> U>(self, f: F) -> FramedWrite2<U> {
+        FramedWrite2 {
+            inner: f(self.inner),
+            eof_encoded: self.eof_encoded,
+            buffer: self.buffer,
+            pending_frames: self.pending_frames,
+            max_pending_frames: self.max_pending_frames,
+            vectored: self.vectored,
+            frames: self.frames,
+        }
     }
 
     pub fn get_mut(&mut self) -> &mut T {
         &mut self.inner
     }
-}
 
-impl<T> Sink for FramedWrite2<T>
-    where T: AsyncWrite + Encoder,
-{
-    type SinkItem = T::Item;
-    type SinkError = T::Error;
+    pub fn buffer_len(&self) -> usize {
+        self.frames.len() + self.buffer.len()
+    }
 
-    fn start_send(&mut self, item: T::Item) -> StartSend<T::Item, T::Error> {
-        // If the buffer is already over 8KiB, then attempt to flush it. If after flushing it's
-        // *still* over 8KiB, then apply backpressure (reject the send).
-        if self.buffer.len() >= BACKPRESSURE_BOUNDARY {
-            try!(self.poll_complete());
+    // The bytes encoded since the last frame was moved to the frame queue,
+    // which is everything not yet written unless in vectored mode or after a
+    // partial write.
+    pub fn write_buffer(&self) -> &BytesMut {
+        &self.buffer
+    }
 
-            if self.buffer.len() >= BACKPRESSURE_BOUNDARY {
-                return Ok(AsyncSink::NotReady(item));
-            }
+    pub fn queue(&mut self, buf: BytesMut) {
+        self.buffer.unsplit(buf);
+        self.frames_from_buffer();
+    }
+
+    pub fn set_vectored(&mut self, vectored: bool) {
+        self.vectored = vectored;
+        self.frames_from_buffer();
+    }
+
+    // In vectored mode, moves whatever was encoded into the write buffer to
+    // the back of the frame queue, without copying it.
+    fn frames_from_buffer(&mut self) {
+        if self.vectored && !self.buffer.is_empty() {
+            let frame = self.buffer.take().freeze();
+            self.frames.push(frame);
         }
+    }
 
-        try!(self.inner.encode(item, &mut self.buffer));
+    pub fn set_max_pending_frames(&mut self, max: Option<usize>) {
+        self.max_pending_frames = max;
+    }
 
-        Ok(AsyncSink::Ready)
+    // Whether `start_send` should apply backpressure before encoding another
+    // frame.
+    fn is_full(&self) -> bool {
+        if self.buffer_len() >= BACKPRESSURE_BOUNDARY {
+            return true;
+        }
+
+        match self.max_pending_frames {
+            Some(max) => self.pending_frames >= max,
+            None => false,
+        }
     }
+}
 
-    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+impl<T: Encoder> FramedWrite2<T> {
+    // Encodes `item` into the write buffer regardless of backpressure,
+    // returning how many bytes were appended.
+    pub fn encode_item(&mut self, item: T::Item) -> Result<usize, T::Error> {
+        let before = self.buffer_len();
+        try!(self.inner.encode(item, &mut self.buffer));
+        self.pending_frames += 1;
+        self.frames_from_buffer();
+        Ok(self.buffer_len() - before)
+    }
+}
+
+impl<T: AsyncWrite> FramedWrite2<T> {
+    pub fn poll_flush(&mut self) -> Poll<(), io::Error> {
         trace!("flushing framed transport");
 
-        while !self.buffer.is_empty() {
+        loop {
+            while !self.frames.is_empty() {
+                trace!("writing vectored; remaining={} frames={}",
+                       self.frames.len(), self.frames.chunks().len());
+
+                let n = loop {
+                    match self.inner.write_buf_vectored(&mut self.frames) {
+                        Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                        res => break try_ready!(res),
+                    }
+                };
+
+                if n == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to
+                                          write frame to transport"));
+                }
+            }
+
+            if self.buffer.is_empty() {
+                break;
+            }
+
             trace!("writing; remaining={}", self.buffer.len());
 
-            let n = try_nb!(self.inner.write(&self.buffer));
+            // Hand the buffer over as `Bytes`, so writers which queue data
+            // can keep it without a copy
+            let mut bytes = self.buffer.take().freeze();
+            let res = loop {
+                match self.inner.write_bytes(&mut bytes) {
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    res => break res,
+                }
+            };
+
+            // Whatever wasn't written goes out before anything encoded later
+            self.frames.push(bytes);
 
-            if n == 0 {
+            if try_ready!(res) == 0 {
                 return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to
-                                          write frame to transport").into());
+                                          write frame to transport"));
             }
-
-            // TODO: Add a way to `bytes` to do this w/o returning the drained
-            // data.
-            let _ = self.buffer.split_to(n);
         }
 
+        // Every encoded frame has been written out
+        self.pending_frames = 0;
+
         // Try flushing the underlying IO
-        try_nb!(self.inner.flush());
+        try_nb!(::retry_interrupted(|| self.inner.flush()));
 
         trace!("framed transport flushed");
         return Ok(Async::Ready(()));
     }
 
+    // Ready if `start_send` would accept a frame without applying
+    // backpressure, attempting to flush if it would not.
+    pub fn poll_write_ready(&mut self) -> Poll<(), io::Error> {
+        if self.is_full() {
+            try!(self.poll_flush());
+
+            if self.is_full() {
+                return Ok(Async::NotReady);
+            }
+        }
+
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<T> Sink for FramedWrite2<T>
+    where T: AsyncWrite + Encoder,
+{
+    type SinkItem = T::Item;
+    type SinkError = T::Error;
+
+    fn start_send(&mut self, item: T::Item) -> StartSend<T::Item, T::Error> {
+        // If the buffer is already over 8KiB, or holds the maximum number of
+        // frames, then attempt to flush it. If after flushing it's *still*
+        // full, then apply backpressure (reject the send).
+        if self.is_full() {
+            try!(self.poll_complete());
+
+            if self.is_full() {
+                return Ok(AsyncSink::NotReady(item));
+            }
+        }
+
+        try!(self.inner.encode(item, &mut self.buffer));
+        self.pending_frames += 1;
+        self.frames_from_buffer();
+
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        Ok(try!(self.poll_flush()))
+    }
+
     fn close(&mut self) -> Poll<(), Self::SinkError> {
+        if !self.eof_encoded {
+            try!(self.inner.encode_eof(&mut self.buffer));
+            self.eof_encoded = true;
+            self.frames_from_buffer();
+        }
+
         try_ready!(self.poll_complete());
         Ok(try!(self.inner.shutdown()))
     }
@@ -241,6 +904,20 @@ impl<T: Decoder> Decoder for FramedWrite2<T> {
     fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<T::Item>, T::Error> {
         self.inner.decode_eof(src)
     }
+
+    fn has_frame(&self, src: &BytesMut) -> bool {
+        self.inner.has_frame(src)
+    }
+}
+
+impl<T: SliceDecoder> SliceDecoder for FramedWrite2<T> {
+    fn decode_slice(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, T::Error> {
+        self.inner.decode_slice(src)
+    }
+
+    fn decode_slice_eof(&mut self, src: &[u8]) -> Result<Option<(Range<usize>, usize)>, T::Error> {
+        self.inner.decode_slice_eof(src)
+    }
 }
 
 impl<T: Read> Read for FramedWrite2<T> {
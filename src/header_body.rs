@@ -0,0 +1,121 @@
+use std::{fmt, io, mem};
+
+use bytes::{Bytes, BytesMut};
+
+use codec::Decoder;
+
+/// A `Decoder` for frames made up of a fixed size header followed by a body
+/// whose length is computed from the header.
+///
+/// This covers protocols where the body length can't be expressed as a
+/// single length field with an adjustment, such as when it is derived from
+/// several header fields. The decoder waits for `header_len` bytes, passes
+/// them to the user supplied closure to learn how many body bytes follow,
+/// waits for those, and then yields the header and the body as separate
+/// `Bytes` values.
+///
+/// If the total frame length (header and body) exceeds the max frame length,
+/// an `InvalidData` error is returned before any of the body is buffered. The
+/// default max frame length is 8MB.
+pub struct HeaderBody<F> {
+    header_len: usize,
+    body_len: F,
+    max_frame_len: usize,
+    state: DecodeState,
+}
+
+#[derive(Debug)]
+enum DecodeState {
+    Head,
+    Body(Bytes, usize),
+}
+
+impl<F> HeaderBody<F>
+    where F: FnMut(&[u8]) -> usize,
+{
+    /// Creates a new `HeaderBody` decoder for headers of `header_len` bytes,
+    /// using `body_len` to compute the length of the body following each
+    /// header.
+    pub fn new(header_len: usize, body_len: F) -> HeaderBody<F> {
+        HeaderBody {
+            header_len: header_len,
+            body_len: body_len,
+            max_frame_len: 8 * 1_024 * 1_024,
+            state: DecodeState::Head,
+        }
+    }
+}
+
+impl<F> HeaderBody<F> {
+    /// Returns the current max frame setting
+    ///
+    /// This is the largest total size of header and body this decoder will
+    /// accept. Larger frames will be rejected.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the max frame setting.
+    ///
+    /// The change takes effect the next time a header is decoded.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+}
+
+impl<F> Decoder for HeaderBody<F>
+    where F: FnMut(&[u8]) -> usize,
+{
+    type Item = (Bytes, Bytes);
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<(Bytes, Bytes)>> {
+        if let DecodeState::Head = self.state {
+            if src.len() < self.header_len {
+                return Ok(None);
+            }
+
+            let header = src.split_to(self.header_len).freeze();
+            let n = (self.body_len)(&header);
+
+            match n.checked_add(self.header_len) {
+                Some(total) if total <= self.max_frame_len => {}
+                _ => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                              "frame length computed from header is too big"));
+                }
+            }
+
+            // Ensure that the buffer has enough space to read the body
+            src.reserve(n);
+
+            self.state = DecodeState::Body(header, n);
+        }
+
+        let n = match self.state {
+            DecodeState::Body(_, n) => n,
+            DecodeState::Head => unreachable!(),
+        };
+
+        if src.len() < n {
+            return Ok(None);
+        }
+
+        let body = src.split_to(n).freeze();
+
+        match mem::replace(&mut self.state, DecodeState::Head) {
+            DecodeState::Body(header, _) => Ok(Some((header, body))),
+            DecodeState::Head => unreachable!(),
+        }
+    }
+}
+
+impl<F> fmt::Debug for HeaderBody<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HeaderBody")
+            .field("header_len", &self.header_len)
+            .field("max_frame_len", &self.max_frame_len)
+            .field("state", &self.state)
+            .finish()
+    }
+}
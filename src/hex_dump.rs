@@ -0,0 +1,177 @@
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Read, Write};
+
+use bytes::Bytes;
+use futures::{Async, Poll};
+
+use {AsyncRead, AsyncWrite};
+use bytes_queue::{each_written, BytesQueue};
+
+const BYTES_PER_LINE: usize = 16;
+
+/// An I/O object which logs a hex dump of all data read from and written to
+/// the underlying object.
+///
+/// Each successful read or write emits a single `trace!` message made up of a
+/// header line, marked `<` for data read and `>` for data written, followed
+/// by the bytes in rows of sixteen. Every row starts with the offset of its
+/// first byte within that direction of the stream, and ends with the bytes
+/// as ASCII, showing unprintable bytes as `.`:
+///
+/// ```text
+/// < read 5 bytes at offset 0
+/// 00000000  68 65 6c 6c 6f                                   |hello|
+/// ```
+///
+/// Nothing is formatted unless the `trace` level is enabled for this crate,
+/// so the wrapper costs little more than the check otherwise. The number of
+/// bytes dumped per call can be capped with `set_max_dump_len`, in which case
+/// the rest of the call's data is only counted.
+#[derive(Debug)]
+pub struct HexDump<T> {
+    inner: T,
+    max_dump_len: Option<usize>,
+    read_offset: u64,
+    write_offset: u64,
+}
+
+impl<T> HexDump<T> {
+    /// Creates a new `HexDump` logging all traffic on `inner`.
+    pub fn new(inner: T) -> HexDump<T> {
+        HexDump {
+            inner: inner,
+            max_dump_len: None,
+            read_offset: 0,
+            write_offset: 0,
+        }
+    }
+
+    /// Returns the maximum number of bytes dumped per read or write, if any.
+    pub fn max_dump_len(&self) -> Option<usize> {
+        self.max_dump_len
+    }
+
+    /// Updates the maximum number of bytes dumped per read or write.
+    pub fn set_max_dump_len(&mut self, val: usize) {
+        self.max_dump_len = Some(val);
+    }
+
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    ///
+    /// Note that data read or written through this reference is not logged,
+    /// and the logged offsets won't account for it.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `HexDump`, returning the underlying I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn dump(&self, marker: &str, action: &str, offset: u64, data: &[u8]) {
+        if !log_enabled!(::log::Level::Trace) {
+            return;
+        }
+
+        let mut out = String::new();
+        let _ = write!(out, "{} {} {} bytes at offset {}", marker, action, data.len(), offset);
+
+        let shown = match self.max_dump_len {
+            Some(max) if max < data.len() => &data[..max],
+            _ => data,
+        };
+
+        for (i, row) in shown.chunks(BYTES_PER_LINE).enumerate() {
+            let _ = write!(out, "\n{:08x} ", offset + (i * BYTES_PER_LINE) as u64);
+
+            for b in row {
+                let _ = write!(out, " {:02x}", b);
+            }
+            for _ in row.len()..BYTES_PER_LINE {
+                out.push_str("   ");
+            }
+
+            out.push_str("  |");
+            for &b in row {
+                out.push(if b >= 0x20 && b < 0x7f { b as char } else { '.' });
+            }
+            out.push('|');
+        }
+
+        if shown.len() < data.len() {
+            let _ = write!(out, "\n... {} more bytes", data.len() - shown.len());
+        }
+
+        trace!("{}", out);
+    }
+}
+
+impl<T: Read> Read for HexDump<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.inner.read(buf));
+        self.dump("<", "read", self.read_offset, &buf[..n]);
+        self.read_offset += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for HexDump<T> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: Write> Write for HexDump<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = try!(self.inner.write(buf));
+        self.dump(">", "wrote", self.write_offset, &buf[..n]);
+        self.write_offset += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for HexDump<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        // A handle to the bytes, which doesn't copy them, to dump whatever
+        // the inner writer takes
+        let data = bytes.clone();
+        let n = try_ready!(self.inner.write_bytes(bytes));
+        self.dump(">", "wrote", self.write_offset, &data[..n]);
+        self.write_offset += n as u64;
+        Ok(Async::Ready(n))
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        // Handles to the queued buffers, which don't copy them, to dump
+        // whatever the inner writer takes
+        let queued = if log_enabled!(::log::Level::Trace) {
+            bufs.chunks().cloned().collect()
+        } else {
+            Vec::<Bytes>::new()
+        };
+
+        let n = try_ready!(self.inner.write_buf_vectored(bufs));
+
+        if !queued.is_empty() {
+            let mut data = Vec::with_capacity(n);
+            each_written(&queued, n, |buf| data.extend_from_slice(buf));
+            self.dump(">", "wrote", self.write_offset, &data);
+        }
+        self.write_offset += n as u64;
+        Ok(Async::Ready(n))
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(feature = "instrument")]
+use std::cell::RefCell;
+#[cfg(feature = "instrument")]
+use std::sync::Arc;
+#[cfg(feature = "instrument")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The combinators which count their polls and `WouldBlock` errors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Site {
+    /// The `Copy` future returned by `io::copy`.
+    Copy,
+    /// The `ReadExact` future returned by `io::read_exact`.
+    ReadExact,
+    /// The `ReadIntoWindow` future returned by `io::read_into_window`.
+    ReadIntoWindow,
+    /// The `ReadToEnd` future returned by `io::read_to_end`.
+    ReadToEnd,
+    /// The `WriteAll` future returned by `io::write_all`.
+    WriteAll,
+    /// The `WriteStream` future returned by `io::write_stream`.
+    WriteStream,
+}
+
+/// Counts of the polls of a combinator, and of the `WouldBlock` errors it
+/// hit while being polled.
+///
+/// Comparing the two shows whether a combinator which appears to stall is
+/// being polled at all, and how often polls find its I/O object not ready.
+#[cfg(feature = "instrument")]
+#[derive(Debug, Default)]
+pub struct Counters {
+    polls: AtomicUsize,
+    would_blocks: AtomicUsize,
+}
+
+#[cfg(feature = "instrument")]
+impl Counters {
+    /// Returns a new set of counters, both starting at zero.
+    pub fn new() -> Counters {
+        Counters::default()
+    }
+
+    /// Returns the number of times the combinator has been polled.
+    pub fn polls(&self) -> usize {
+        self.polls.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of `WouldBlock` errors hit by the combinator.
+    pub fn would_blocks(&self) -> usize {
+        self.would_blocks.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "instrument")]
+thread_local!(static REGISTERED: RefCell<[Option<Arc<Counters>>; 6]> = RefCell::new(Default::default()));
+
+/// Registers `counters` to be updated whenever a combinator of the kind
+/// `site` is polled on the current thread, replacing any counters
+/// previously registered for it.
+#[cfg(feature = "instrument")]
+pub fn register(site: Site, counters: Arc<Counters>) {
+    REGISTERED.with(|r| r.borrow_mut()[site as usize] = Some(counters));
+}
+
+/// Removes the counters registered for `site` on the current thread,
+/// returning them.
+#[cfg(feature = "instrument")]
+pub fn unregister(site: Site) -> Option<Arc<Counters>> {
+    REGISTERED.with(|r| r.borrow_mut()[site as usize].take())
+}
+
+#[cfg(feature = "instrument")]
+fn count<F: FnOnce(&Counters)>(site: Site, f: F) {
+    REGISTERED.with(|r| {
+        if let Some(ref counters) = r.borrow()[site as usize] {
+            f(counters);
+        }
+    })
+}
+
+// Records a poll of a combinator of the kind `site`.
+#[cfg(feature = "instrument")]
+pub fn poll(site: Site) {
+    count(site, |c| { c.polls.fetch_add(1, Ordering::Relaxed); });
+}
+
+// Records a `WouldBlock` error hit by a combinator of the kind `site`.
+#[cfg(feature = "instrument")]
+pub fn would_block(site: Site) {
+    count(site, |c| { c.would_blocks.fetch_add(1, Ordering::Relaxed); });
+}
+
+#[cfg(not(feature = "instrument"))]
+#[inline]
+pub fn poll(_site: Site) {}
+
+#[cfg(not(feature = "instrument"))]
+#[inline]
+pub fn would_block(_site: Site) {}
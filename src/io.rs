@@ -10,14 +10,56 @@
 //! [low level details]: https://tokio.rs/docs/going-deeper-tokio/core-low-level/
 
 pub use allow_std::AllowStdIo;
+pub use block_align::BlockAlign;
+pub use blocking::{BlockingRead, BlockingWrite};
+pub use bom_strip::{Bom, BomStrip};
+pub use bytes_queue::BytesQueue;
+pub use checksum::{Checksum, ChecksumTrailer, ChecksumVerify, Crc32};
+pub use concat_readers::{concat_readers, ConcatReaders};
 pub use copy::{copy, Copy};
+pub use either::Either;
 pub use flush::{flush, Flush};
+pub use hex_dump::HexDump;
 pub use lines::{lines, Lines};
+pub use lines_bytes::{lines_bytes, LinesBytes};
+pub use permitted::{Permitted, ReadPermit};
+pub use phased::{Phase, PhasePolicy, Phased};
 pub use read::{read, Read};
-pub use read_exact::{read_exact, ReadExact};
+pub use read_budget::ReadBudget;
+pub use read_exact::{read_exact, read_exact_at, ReadExact};
+pub use read_header_then_frame::{read_header_then_frame, ReadHeaderThenFrame};
+pub use read_into_window::{read_into_window, ReadIntoWindow};
 pub use read_to_end::{read_to_end, ReadToEnd};
 pub use read_until::{read_until, ReadUntil};
+pub use select_read::{select_read, SelectRead};
+pub use shared::Shared;
 pub use shutdown::{shutdown, Shutdown};
+pub use slurp::{slurp, Slurp};
 pub use split::{ReadHalf, WriteHalf};
+pub use transactional::Transactional;
 pub use window::Window;
 pub use write_all::{write_all, WriteAll};
+pub use write_all_buf::{write_all_buf, WriteAllBuf, WriteAllBufError};
+pub use write_stream::{write_stream, WriteStream};
+pub use xor_crypt::XorCrypt;
+
+#[cfg(feature = "instrument")]
+pub mod instrument {
+    //! Poll and would-block counters for the core combinators
+    //!
+    //! When a combinator like [`read_exact`] or [`copy`] appears to stall,
+    //! it's hard to tell whether it's being polled at all. Registering
+    //! [`Counters`] for a [`Site`] makes every combinator of that kind polled
+    //! on the current thread count its polls, and the `WouldBlock` errors
+    //! which made it return `NotReady`. Each such `NotReady` is also logged
+    //! through `trace!`, whether or not this feature is enabled.
+    //!
+    //! This module is only available with the `instrument` feature enabled.
+    //!
+    //! [`read_exact`]: ../fn.read_exact.html
+    //! [`copy`]: ../fn.copy.html
+    //! [`Counters`]: struct.Counters.html
+    //! [`Site`]: enum.Site.html
+
+    pub use ::instrument::{register, unregister, Counters, Site};
+}
@@ -0,0 +1,185 @@
+use std::{io, mem, str};
+
+use bytes::{BufMut, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+/// A codec for blocks of `KEY: VALUE` lines terminated by an empty line.
+///
+/// Each line is split at its first colon, and whitespace around the value is
+/// trimmed. Lines may end in either `\n` or `\r\n`. The pairs of a block are
+/// collected in order and yielded as a single item once the empty line ending
+/// the block is decoded. Encoding writes each pair as `KEY: VALUE\r\n`
+/// followed by an empty line.
+///
+/// Folded lines, continuing the previous value on a line starting with
+/// whitespace, are not supported and are rejected.
+///
+/// Decoding fails with an `InvalidData` error if a line isn't valid UTF-8,
+/// lacks a colon, is longer than the max line length, or if a block has more
+/// pairs than the max header count. The pairs already decoded for that
+/// block are discarded when this happens, but the lines following the
+/// offending one are not skipped: decoding picks up with them as the start of
+/// a new block, so the rest of a broken block is yielded as a block of its
+/// own once the empty line ending it is decoded. The default limits are 8KB
+/// per line and 100 headers per block.
+///
+/// Encoding fails with an `InvalidInput` error, leaving the destination
+/// buffer untouched, if any key or value can't be written as a single line.
+#[derive(Debug)]
+pub struct KeyValueHeaders {
+    max_headers: usize,
+    max_line_len: usize,
+    headers: Vec<(String, String)>,
+    // Index of the next byte to scan for a newline, so a partial line isn't
+    // scanned again on every call
+    next_index: usize,
+}
+
+impl KeyValueHeaders {
+    /// Creates a new `KeyValueHeaders` codec with the default limits.
+    pub fn new() -> KeyValueHeaders {
+        KeyValueHeaders {
+            max_headers: 100,
+            max_line_len: 8 * 1_024,
+            headers: Vec::new(),
+            next_index: 0,
+        }
+    }
+
+    /// Returns the maximum number of headers in a block.
+    pub fn max_headers(&self) -> usize {
+        self.max_headers
+    }
+
+    /// Updates the maximum number of headers in a block.
+    pub fn set_max_headers(&mut self, val: usize) {
+        self.max_headers = val;
+    }
+
+    /// Returns the maximum length of a line, not including its line ending.
+    pub fn max_line_length(&self) -> usize {
+        self.max_line_len
+    }
+
+    /// Updates the maximum length of a line, not including its line ending.
+    pub fn set_max_line_length(&mut self, val: usize) {
+        self.max_line_len = val;
+    }
+
+    fn fail(&mut self, msg: &'static str) -> io::Error {
+        self.headers.clear();
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    fn parse_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if line[0] == b' ' || line[0] == b'\t' {
+            return Err(self.fail("folded header lines are not supported"));
+        }
+
+        if self.headers.len() >= self.max_headers {
+            return Err(self.fail("too many headers"));
+        }
+
+        let line = match str::from_utf8(line) {
+            Ok(line) => line,
+            Err(_) => return Err(self.fail("header line is not valid UTF-8")),
+        };
+
+        let colon = match line.find(':') {
+            Some(colon) => colon,
+            None => return Err(self.fail("header line has no colon")),
+        };
+
+        let key = line[..colon].to_string();
+        let value = line[colon + 1..].trim_matches(|c| c == ' ' || c == '\t').to_string();
+        self.headers.push((key, value));
+        Ok(())
+    }
+}
+
+impl Decoder for KeyValueHeaders {
+    type Item = Vec<(String, String)>;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Vec<(String, String)>>> {
+        loop {
+            let newline = buf[self.next_index..].iter().position(|b| *b == b'\n');
+
+            let newline = match newline {
+                Some(offset) => offset + self.next_index,
+                None => {
+                    // Leave room for the `\r` of a `\r\n` line ending
+                    if buf.len() > self.max_line_len + 1 {
+                        self.next_index = 0;
+                        return Err(self.fail("header line too long"));
+                    }
+                    self.next_index = buf.len();
+                    return Ok(None);
+                }
+            };
+
+            self.next_index = 0;
+            let line = buf.split_to(newline + 1);
+            let mut line = &line[..line.len() - 1];
+            if let Some(&b'\r') = line.last() {
+                line = &line[..line.len() - 1];
+            }
+
+            if line.len() > self.max_line_len {
+                return Err(self.fail("header line too long"));
+            }
+
+            if line.is_empty() {
+                return Ok(Some(mem::replace(&mut self.headers, Vec::new())));
+            }
+
+            try!(self.parse_line(line));
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<Vec<(String, String)>>> {
+        match try!(self.decode(buf)) {
+            Some(headers) => Ok(Some(headers)),
+            None => {
+                if buf.is_empty() && self.headers.is_empty() {
+                    Ok(None)
+                } else {
+                    self.headers.clear();
+                    Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                       "header block not terminated by an empty line"))
+                }
+            }
+        }
+    }
+}
+
+impl Encoder for KeyValueHeaders {
+    type Item = Vec<(String, String)>;
+    type Error = io::Error;
+
+    fn encode(&mut self, headers: Vec<(String, String)>, buf: &mut BytesMut) -> io::Result<()> {
+        for &(ref key, ref value) in &headers {
+            let bad_key = key.is_empty() ||
+                key.starts_with(|c| c == ' ' || c == '\t') ||
+                key.contains(|c| c == ':' || c == '\r' || c == '\n');
+            if bad_key || value.contains(|c| c == '\r' || c == '\n') {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "header can't be encoded as a single line"));
+            }
+        }
+
+        let len = headers.iter().map(|&(ref k, ref v)| k.len() + v.len() + 4).sum::<usize>();
+        buf.reserve(len + 2);
+
+        for (key, value) in headers {
+            buf.put(key);
+            buf.put(": ");
+            buf.put(value);
+            buf.put("\r\n");
+        }
+
+        buf.put("\r\n");
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use {codec, AsyncRead, AsyncWrite};
+use bytes_queue::BytesQueue;
 
-use bytes::{Buf, BufMut, BytesMut, IntoBuf, BigEndian, LittleEndian};
+use bytes::{Buf, BufMut, Bytes, BytesMut, IntoBuf, BigEndian, LittleEndian};
 use bytes::buf::Chain;
 
 use futures::{Async, AsyncSink, Stream, Sink, StartSend, Poll};
@@ -8,6 +9,7 @@ use futures::{Async, AsyncSink, Stream, Sink, StartSend, Poll};
 use std::{cmp, fmt};
 use std::error::Error as StdError;
 use std::io::{self, Cursor};
+use std::ops::Range;
 
 /// Configure length delimited `FramedRead`, `FramedWrite`, and `Framed` values.
 ///
@@ -28,12 +30,41 @@ pub struct Builder {
     // Adjust the length specified in the header field by this amount
     length_adjustment: isize,
 
+    // Multiply the length specified in the header field by this factor,
+    // before adjusting it
+    length_scale: usize,
+
     // Total number of bytes to skip before reading the payload, if not set,
     // `length_field_len + length_field_offset`
     num_skip: Option<usize>,
 
+    // Number of bytes following the payload that are not counted by the
+    // length field and are discarded
+    frame_trailer_len: usize,
+
     // Length field byte order (little or big endian)
     length_field_is_big_endian: bool,
+
+    // Length field is encoded as zero-padded ASCII decimal digits
+    length_field_is_ascii: bool,
+
+    // Length field is followed by a 1 byte XOR checksum of its bytes
+    length_field_has_checksum: bool,
+
+    // Read buffer capacity above which the buffer is shrunk after a decode
+    read_buffer_shrink_threshold: Option<usize>,
+
+    // Capacity the read buffer is shrunk back to
+    read_buffer_baseline_capacity: Option<usize>,
+
+    // Range of header bytes yielded alongside the payload, as `(start, end)`
+    captured_header: Option<(usize, usize)>,
+
+    // Each read from the upstream delivers exactly one whole frame
+    datagram_mode: bool,
+
+    // EOF is only accepted after two zero length reads in a row
+    strict_eof: bool,
 }
 
 /// Adapts a byte stream into a unified `Stream` and `Sink` that works over
@@ -56,6 +87,47 @@ pub struct FramedRead<T> {
     inner: codec::FramedRead<T, Decoder>,
 }
 
+/// Adapts a byte stream to a `Stream` yielding entire frame values along with
+/// a captured range of each frame's header.
+///
+/// Created by `Builder::new_read_with_header`. See `Builder::capture_header`
+/// for details.
+#[derive(Debug)]
+pub struct FramedReadWithHeader<T> {
+    inner: codec::FramedRead<T, HeaderDecoder>,
+}
+
+/// Adapts a byte stream to a `Stream` yielding each frame as a header,
+/// followed by chunks of its payload as they arrive, followed by an end
+/// marker.
+///
+/// Created by `Builder::new_read_streaming`. Unlike `FramedRead`, a frame's
+/// payload is never buffered in full, which allows handling frames too large
+/// to hold in memory, for example by streaming them to disk.
+#[derive(Debug)]
+pub struct FramedReadStreaming<T> {
+    inner: codec::FramedRead<T, StreamingDecoder>,
+}
+
+/// A piece of a frame yielded by `FramedReadStreaming`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FramePart {
+    /// The start of a frame.
+    Header(FrameHeader),
+    /// A chunk of the current frame's payload.
+    Chunk(BytesMut),
+    /// The end of the current frame, once all of its payload has been
+    /// yielded.
+    End,
+}
+
+/// The start of a frame yielded by `FramedReadStreaming`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    /// The length of the frame's payload, after any length adjustment.
+    pub len: usize,
+}
+
 /// An error when the number of bytes read is more than max frame length.
 pub struct FrameTooBig {
     _priv: (),
@@ -68,8 +140,36 @@ struct Decoder {
 
     // Read state
     state: DecodeState,
+
+    // Header bytes captured for the frame currently being decoded
+    header: Option<Bytes>,
+
+    // Called with the progress of an incomplete frame
+    progress: Option<Progress>,
+}
+
+struct Progress(Box<FnMut(usize, usize) + Send + Sync>);
+
+#[derive(Debug)]
+struct HeaderDecoder {
+    inner: Decoder,
+}
+
+#[derive(Debug)]
+struct StreamingDecoder {
+    inner: Decoder,
+
+    // Payload bytes of the current frame not yet yielded, if a header has
+    // been yielded
+    remaining: Option<usize>,
 }
 
+// Read buffer space reserved for the payload when streaming a frame
+const STREAMING_CHUNK_CAPACITY: usize = 8 * 1_024;
+
+// Upper bound on the read buffer space reserved for a datagram
+const MAX_DATAGRAM_CAPACITY: usize = 64 * 1_024;
+
 #[derive(Debug, Clone, Copy)]
 enum DecodeState {
     Head,
@@ -90,6 +190,12 @@ pub struct FramedWrite<T, B: IntoBuf = BytesMut> {
 
     // Current frame being written
     frame: Option<Chain<Cursor<BytesMut>, B::Buf>>,
+
+    // Head or payload chunk of a streamed frame being written
+    chunk: Option<Cursor<Bytes>>,
+
+    // Payload bytes of the streamed frame not yet passed in, if one was begun
+    stream_remaining: Option<usize>,
 }
 
 // ===== impl Framed =====
@@ -102,6 +208,14 @@ impl<T: AsyncRead + AsyncWrite, B: IntoBuf> Framed<T, B> {
 }
 
 impl<T, B: IntoBuf> Framed<T, B> {
+    /// Returns whether a complete frame is buffered, so that polling the
+    /// stream would yield it without reading from the underlying I/O stream.
+    ///
+    /// See `FramedRead::has_complete_frame` for details.
+    pub fn has_complete_frame(&self) -> bool {
+        self.inner.has_complete_frame()
+    }
+
     /// Returns a reference to the underlying I/O stream wrapped by `Framed`.
     ///
     /// Note that care should be taken to not tamper with the underlying stream
@@ -196,6 +310,36 @@ impl<T> FramedRead<T> {
         self.inner.decoder_mut().builder.max_frame_length(val);
     }
 
+    /// Returns the current capacity of the read buffer.
+    pub fn read_buffer_capacity(&self) -> usize {
+        self.inner.read_buffer_capacity()
+    }
+
+    /// Returns whether a complete frame is buffered, so that polling the
+    /// stream would yield it without reading from the underlying I/O stream.
+    ///
+    /// This only compares the length declared by the buffered header against
+    /// the number of buffered bytes, without decoding anything.
+    pub fn has_complete_frame(&self) -> bool {
+        self.inner.has_complete_frame()
+    }
+
+    /// Sets a callback reporting the progress of frames which haven't been
+    /// fully received yet.
+    ///
+    /// Once a frame's header has been decoded, `f` is called after each read
+    /// from the underlying I/O stream that leaves the frame incomplete, with
+    /// the number of payload bytes received so far and the payload length
+    /// declared by the header. This is useful for showing the progress of
+    /// individual large frames.
+    ///
+    /// No callback is set by default, in which case there is no overhead.
+    pub fn on_progress<F>(&mut self, f: F)
+        where F: FnMut(usize, usize) + Send + Sync + 'static,
+    {
+        self.inner.decoder_mut().progress = Some(Progress(Box::new(f)));
+    }
+
     /// Returns a reference to the underlying I/O stream wrapped by `FramedRead`.
     ///
     /// Note that care should be taken to not tamper with the underlying stream
@@ -269,12 +413,127 @@ impl<T: AsyncWrite> AsyncWrite for FramedRead<T> {
     fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
         self.inner.get_mut().write_buf(buf)
     }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        self.inner.get_mut().write_bytes(bytes)
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        self.inner.get_mut().write_buf_vectored(bufs)
+    }
+}
+
+// ===== impl FramedReadWithHeader =====
+
+impl<T> FramedReadWithHeader<T> {
+    /// Returns a reference to the underlying I/O stream wrapped by
+    /// `FramedReadWithHeader`.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise
+    /// being worked with.
+    pub fn get_ref(&self) -> &T {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying I/O stream wrapped by
+    /// `FramedReadWithHeader`.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise being
+    /// worked with.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    /// Consumes the `FramedReadWithHeader`, returning its underlying I/O
+    /// stream.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise being
+    /// worked with.
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: AsyncRead> Stream for FramedReadWithHeader<T> {
+    type Item = (Bytes, BytesMut);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<(Bytes, BytesMut)>, io::Error> {
+        self.inner.poll()
+    }
+}
+
+// ===== impl FramedReadStreaming =====
+
+impl<T> FramedReadStreaming<T> {
+    /// Returns a reference to the underlying I/O stream wrapped by
+    /// `FramedReadStreaming`.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise
+    /// being worked with.
+    pub fn get_ref(&self) -> &T {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying I/O stream wrapped by
+    /// `FramedReadStreaming`.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise being
+    /// worked with.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    /// Consumes the `FramedReadStreaming`, returning its underlying I/O
+    /// stream.
+    ///
+    /// Note that care should be taken to not tamper with the underlying stream
+    /// of data coming in as it may corrupt the stream of frames otherwise being
+    /// worked with.
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: AsyncRead> Stream for FramedReadStreaming<T> {
+    type Item = FramePart;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<FramePart>, io::Error> {
+        self.inner.poll()
+    }
 }
 
 // ===== impl Decoder ======
 
 impl Decoder {
     fn decode_head(&mut self, src: &mut BytesMut) -> io::Result<Option<usize>> {
+        let n = match try!(self.peek_head(src)) {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        if let Some((start, end)) = self.builder.captured_header {
+            self.header = Some(Bytes::from(&src[start..end]));
+        }
+
+        let num_skip = self.builder.get_num_skip();
+
+        if num_skip > 0 {
+            let _ = src.split_to(num_skip);
+        }
+
+        return Ok(Some(n));
+    }
+
+    // Parses the payload length from the header at the start of `src`,
+    // without consuming it
+    fn peek_head(&self, src: &[u8]) -> io::Result<Option<usize>> {
         let head_len = self.builder.num_head_bytes();
         let field_len = self.builder.length_field_len;
 
@@ -283,24 +542,39 @@ impl Decoder {
             return Ok(None);
         }
 
+        // Validate the checksum before trusting the length field
+        if self.builder.length_field_has_checksum {
+            let start = self.builder.length_field_offset;
+            let field = &src[start..start + field_len];
+
+            if checksum(field) != src[start + field_len] {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "length field checksum mismatch"));
+            }
+        }
+
         let n = {
-            let mut src = Cursor::new(&mut *src);
+            let mut src = Cursor::new(src);
 
             // Skip the required bytes
             src.advance(self.builder.length_field_offset);
 
-            // match endianess
-            let n = if self.builder.length_field_is_big_endian {
+            // match encoding and endianess
+            let n = if self.builder.length_field_is_ascii {
+                try!(parse_ascii_length(&src.bytes()[..field_len]))
+            } else if self.builder.length_field_is_big_endian {
                 src.get_uint::<BigEndian>(field_len)
             } else {
                 src.get_uint::<LittleEndian>(field_len)
             };
 
-            if n > self.builder.max_frame_len as u64 {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, FrameTooBig {
-                    _priv: (),
-                }));
-            }
+            let n = match n.checked_mul(self.builder.length_scale as u64) {
+                Some(n) if n <= self.builder.max_frame_len as u64 => n,
+                _ => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, FrameTooBig {
+                        _priv: (),
+                    }));
+                }
+            };
 
             // The check above ensures there is no overflow
             let n = n as usize;
@@ -319,27 +593,46 @@ impl Decoder {
             }
         };
 
-        let num_skip = self.builder.get_num_skip();
+        Ok(Some(n))
+    }
 
-        if num_skip > 0 {
-            let _ = src.split_to(num_skip);
+    fn decode_data(&self, n: usize, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        let trailer_len = self.builder.frame_trailer_len;
+
+        // At this point, the buffer has already had the required capacity
+        // reserved. All there is to do is read.
+        if src.len() < n + trailer_len {
+            return Ok(None);
         }
 
-        // Ensure that the buffer has enough space to read the incoming
-        // payload
-        src.reserve(n);
+        let data = src.split_to(n);
 
-        return Ok(Some(n));
+        if trailer_len > 0 {
+            let _ = src.split_to(trailer_len);
+        }
+
+        Ok(Some(data))
     }
 
-    fn decode_data(&self, n: usize, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
-        // At this point, the buffer has already had the required capacity
-        // reserved. All there is to do is read.
-        if src.len() < n {
+    // Decodes the whole buffer, which holds a single datagram, as one frame
+    fn decode_datagram(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        if src.is_empty() {
             return Ok(None);
         }
 
-        Ok(Some(src.split_to(n)))
+        let mut datagram = src.take();
+        src.reserve(self.builder.datagram_capacity());
+
+        let n = match try!(self.decode_head(&mut datagram)) {
+            Some(n) => n,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "datagram shorter than frame header")),
+        };
+
+        if datagram.len() != n + self.builder.frame_trailer_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "datagram length doesn't match frame length"));
+        }
+
+        self.decode_data(n, &mut datagram)
     }
 }
 
@@ -348,11 +641,20 @@ impl codec::Decoder for Decoder {
     type Error = io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        if self.builder.datagram_mode {
+            return self.decode_datagram(src);
+        }
+
         let n = match self.state {
             DecodeState::Head => {
                 match try!(self.decode_head(src)) {
                     Some(n) => {
                         self.state = DecodeState::Data(n);
+
+                        // Ensure that the buffer has enough space to read the
+                        // incoming payload and its trailer
+                        src.reserve(n + self.builder.frame_trailer_len);
+
                         n
                     }
                     None => return Ok(None),
@@ -371,9 +673,120 @@ impl codec::Decoder for Decoder {
 
                 Ok(Some(data))
             }
+            None => {
+                if let Some(ref mut progress) = self.progress {
+                    (progress.0)(cmp::min(src.len(), n), n);
+                }
+
+                Ok(None)
+            }
+        }
+    }
+
+    fn has_frame(&self, src: &BytesMut) -> bool {
+        if self.builder.datagram_mode {
+            return !src.is_empty();
+        }
+
+        let trailer_len = self.builder.frame_trailer_len;
+
+        match self.state {
+            DecodeState::Head => {
+                match self.peek_head(src) {
+                    Ok(Some(n)) => src.len() >= self.builder.get_num_skip() + n + trailer_len,
+                    Ok(None) => false,
+                    // Polling yields the error right away
+                    Err(_) => true,
+                }
+            }
+            DecodeState::Data(n) => src.len() >= n + trailer_len,
+        }
+    }
+}
+
+impl codec::Decoder for HeaderDecoder {
+    type Item = (Bytes, BytesMut);
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<(Bytes, BytesMut)>> {
+        match try!(self.inner.decode(src)) {
+            Some(data) => {
+                let header = self.inner.header.take().unwrap_or_else(Bytes::new);
+                Ok(Some((header, data)))
+            }
             None => Ok(None),
         }
     }
+
+    fn has_frame(&self, src: &BytesMut) -> bool {
+        self.inner.has_frame(src)
+    }
+}
+
+impl codec::Decoder for StreamingDecoder {
+    type Item = FramePart;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<FramePart>> {
+        match self.remaining {
+            None => {
+                match try!(self.inner.decode_head(src)) {
+                    Some(n) => {
+                        self.remaining = Some(n);
+
+                        // Only make room for a bounded chunk of the payload,
+                        // rather than all of it
+                        src.reserve(cmp::min(n, STREAMING_CHUNK_CAPACITY));
+
+                        Ok(Some(FramePart::Header(FrameHeader { len: n })))
+                    }
+                    None => Ok(None),
+                }
+            }
+            Some(0) => {
+                let trailer_len = self.inner.builder.frame_trailer_len;
+
+                if src.len() < trailer_len {
+                    src.reserve(trailer_len);
+                    return Ok(None);
+                }
+
+                if trailer_len > 0 {
+                    let _ = src.split_to(trailer_len);
+                }
+
+                self.remaining = None;
+                src.reserve(self.inner.builder.num_head_bytes());
+                Ok(Some(FramePart::End))
+            }
+            Some(n) => {
+                if src.is_empty() {
+                    return Ok(None);
+                }
+
+                let chunk = src.split_to(cmp::min(n, src.len()));
+                let n = n - chunk.len();
+                self.remaining = Some(n);
+                src.reserve(cmp::min(n, STREAMING_CHUNK_CAPACITY));
+                Ok(Some(FramePart::Chunk(chunk)))
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<FramePart>> {
+        match try!(self.decode(src)) {
+            Some(part) => Ok(Some(part)),
+            None => {
+                if self.remaining.is_some() {
+                    Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stream ended in the middle of a frame"))
+                } else if !src.is_empty() {
+                    Err(io::Error::new(io::ErrorKind::Other, "bytes remaining on stream"))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
 }
 
 // ===== impl FramedWrite =====
@@ -435,29 +848,109 @@ impl<T, B: IntoBuf> FramedWrite<T, B> {
 }
 
 impl<T: AsyncWrite, B: IntoBuf> FramedWrite<T, B> {
-    // If there is a buffered frame, try to write it to `T`
-    fn do_write(&mut self) -> Poll<(), io::Error> {
-        if self.frame.is_none() {
-            return Ok(Async::Ready(()));
+    /// Starts writing a frame whose payload is passed in over several calls
+    /// to `write_frame_chunk`.
+    ///
+    /// The frame head, announcing a payload of `total_len` bytes, is written
+    /// right away, so the payload doesn't have to be buffered in memory as a
+    /// whole. Returns `NotReady` if a previous frame is still being written.
+    ///
+    /// It is an error to begin a frame while another streamed frame hasn't
+    /// been ended, or to send frames with `start_send` until it has been.
+    pub fn begin_frame(&mut self, total_len: usize) -> Poll<(), io::Error> {
+        if self.stream_remaining.is_some() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "a streamed frame is already in progress"));
+        }
+
+        try_ready!(self.do_write());
+
+        let head = try!(self.encode_head(total_len));
+        self.chunk = Some(Cursor::new(head.freeze()));
+        self.stream_remaining = Some(total_len);
+
+        Ok(Async::Ready(()))
+    }
+
+    /// Appends `chunk` to the payload of the streamed frame begun with
+    /// `begin_frame`.
+    ///
+    /// Like `start_send`, this returns `AsyncSink::NotReady` with the chunk
+    /// if the previous chunk is still being written. Chunks are written out
+    /// by `poll_complete`.
+    ///
+    /// It is an error to pass in more bytes than the `total_len` given to
+    /// `begin_frame`, or to call this without a streamed frame in progress.
+    pub fn write_frame_chunk(&mut self, chunk: Bytes) -> StartSend<Bytes, io::Error> {
+        let remaining = match self.stream_remaining {
+            Some(remaining) => remaining,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "no streamed frame in progress")),
+        };
+
+        if chunk.len() > remaining {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "chunk exceeds the streamed frame's length"));
+        }
+
+        if !try!(self.do_write()).is_ready() {
+            return Ok(AsyncSink::NotReady(chunk));
         }
 
-        loop {
-            let frame = self.frame.as_mut().unwrap();
-            try_ready!(self.inner.write_buf(frame));
+        self.stream_remaining = Some(remaining - chunk.len());
+        self.chunk = Some(Cursor::new(chunk));
+
+        Ok(AsyncSink::Ready)
+    }
+
+    /// Ends the streamed frame begun with `begin_frame`.
+    ///
+    /// Returns an error if the chunks passed in don't add up to the
+    /// `total_len` given to `begin_frame`, in which case the frame remains in
+    /// progress. The last chunk may still be waiting to be written out by
+    /// `poll_complete`.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        match self.stream_remaining {
+            Some(0) => {
+                self.stream_remaining = None;
+                Ok(())
+            }
+            Some(_) => Err(io::Error::new(io::ErrorKind::InvalidInput, "streamed frame is shorter than its declared length")),
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "no streamed frame in progress")),
+        }
+    }
 
-            if !frame.has_remaining() {
-                break;
+    // If there is a buffered frame or chunk, try to write it to `T`
+    fn do_write(&mut self) -> Poll<(), io::Error> {
+        if let Some(ref mut frame) = self.frame {
+            while frame.has_remaining() {
+                try_ready!(self.inner.write_buf(frame));
             }
         }
 
         self.frame = None;
 
+        if let Some(ref mut chunk) = self.chunk {
+            while chunk.has_remaining() {
+                try_ready!(self.inner.write_buf(chunk));
+            }
+        }
+
+        self.chunk = None;
+
         Ok(Async::Ready(()))
     }
 
     fn set_frame(&mut self, buf: B::Buf) -> io::Result<()> {
-        let mut head = BytesMut::with_capacity(8);
-        let n = buf.remaining();
+        let head = try!(self.encode_head(buf.remaining()));
+
+        debug_assert!(self.frame.is_none());
+
+        self.frame = Some(head.into_buf().chain(buf));
+
+        Ok(())
+    }
+
+    // Encodes the head of a frame with a payload of `n` bytes
+    fn encode_head(&self, n: usize) -> io::Result<BytesMut> {
+        let mut head = BytesMut::with_capacity(self.builder.length_field_len + 1);
 
         if n > self.builder.max_frame_len {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, FrameTooBig {
@@ -478,17 +971,36 @@ impl<T: AsyncWrite, B: IntoBuf> FramedWrite<T, B> {
             None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "provided length would overflow after adjustment")),
         };
 
-        if self.builder.length_field_is_big_endian {
-            head.put_uint::<BigEndian>(n as u64, self.builder.length_field_len);
-        } else {
-            head.put_uint::<LittleEndian>(n as u64, self.builder.length_field_len);
+        if n % self.builder.length_scale != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame length is not a multiple of the length scale"));
         }
 
-        debug_assert!(self.frame.is_none());
+        let n = n / self.builder.length_scale;
 
-        self.frame = Some(head.into_buf().chain(buf));
+        if self.builder.length_field_is_ascii {
+            let digits = n.to_string();
 
-        Ok(())
+            if digits.len() > self.builder.length_field_len {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame length does not fit in the ASCII length field"));
+            }
+
+            for _ in digits.len()..self.builder.length_field_len {
+                head.put_u8(b'0');
+            }
+
+            head.put_slice(digits.as_bytes());
+        } else if self.builder.length_field_is_big_endian {
+            head.put_uint::<BigEndian>(n as u64, self.builder.length_field_len);
+        } else {
+            head.put_uint::<LittleEndian>(n as u64, self.builder.length_field_len);
+        }
+
+        if self.builder.length_field_has_checksum {
+            let sum = checksum(&head);
+            head.put_u8(sum);
+        }
+
+        Ok(head)
     }
 }
 
@@ -497,6 +1009,10 @@ impl<T: AsyncWrite, B: IntoBuf> Sink for FramedWrite<T, B> {
     type SinkError = io::Error;
 
     fn start_send(&mut self, item: B) -> StartSend<B, io::Error> {
+        if self.stream_remaining.is_some() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "a streamed frame is in progress"));
+        }
+
         if !try!(self.do_write()).is_ready() {
             return Ok(AsyncSink::NotReady(item));
         }
@@ -556,6 +1072,8 @@ impl<T, B: IntoBuf> fmt::Debug for FramedWrite<T, B>
             .field("inner", &self.inner)
             .field("builder", &self.builder)
             .field("frame", &self.frame)
+            .field("chunk", &self.chunk)
+            .field("stream_remaining", &self.stream_remaining)
             .finish()
     }
 }
@@ -594,12 +1112,39 @@ impl Builder {
 
             length_adjustment: 0,
 
+            // Default to the length field counting single bytes.
+            length_scale: 1,
+
             // Total number of bytes to skip before reading the payload, if not set,
             // `length_field_len + length_field_offset`
             num_skip: None,
 
+            // Default to no trailer after the payload.
+            frame_trailer_len: 0,
+
             // Default to reading the length field in network (big) endian.
             length_field_is_big_endian: true,
+
+            // Default to a binary length field.
+            length_field_is_ascii: false,
+
+            // Default to no checksum after the length field.
+            length_field_has_checksum: false,
+
+            // Default to never shrinking the read buffer.
+            read_buffer_shrink_threshold: None,
+
+            // Default to the read buffer's initial capacity.
+            read_buffer_baseline_capacity: None,
+
+            // Default to not capturing any header bytes.
+            captured_header: None,
+
+            // Default to frames spanning any number of reads.
+            datagram_mode: false,
+
+            // Default to trusting a single zero length read as EOF.
+            strict_eof: false,
         }
     }
 
@@ -700,6 +1245,69 @@ impl Builder {
     pub fn length_field_length(&mut self, val: usize) -> &mut Self {
         assert!(val > 0 && val <= 8, "invalid length field length");
         self.length_field_len = val;
+        self.length_field_is_ascii = false;
+        self
+    }
+
+    /// Read the length field as `width` ASCII decimal digits
+    ///
+    /// Instead of a binary integer, the length field is made up of `width`
+    /// characters in the range `0-9`, most significant digit first. Leading
+    /// zeros are accepted when decoding and are used to pad the length to
+    /// `width` characters when encoding. When decoding, a non-digit byte in
+    /// the length field results in an `InvalidData` error. When encoding, a
+    /// length with more than `width` digits results in an `InvalidInput`
+    /// error.
+    ///
+    /// The endianness setting is ignored in this mode and a later call to
+    /// `length_field_length` switches back to a binary length field. The max
+    /// value of `width` is `20`.
+    ///
+    /// This configuration option applies to both encoding and decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .ascii_length_field(4)
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn ascii_length_field(&mut self, width: usize) -> &mut Self {
+        assert!(width > 0 && width <= 20, "invalid ASCII length field width");
+        self.length_field_len = width;
+        self.length_field_is_ascii = true;
+        self
+    }
+
+    /// Follow the length field with a 1 byte checksum of its bytes
+    ///
+    /// The checksum is the XOR of all the bytes in the length field and is
+    /// placed directly after it. When decoding, the checksum is validated
+    /// before the length is used, so a corrupted length field results in an
+    /// `InvalidData` error rather than a huge read or allocation. The checksum
+    /// byte is skipped along with the length field unless `num_skip` is set.
+    ///
+    /// This configuration option applies to both encoding and decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .header_checksum()
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn header_checksum(&mut self) -> &mut Self {
+        self.length_field_has_checksum = true;
         self
     }
 
@@ -744,6 +1352,43 @@ impl Builder {
         self
     }
 
+    /// Sets the factor the length field is multiplied by to get the payload
+    /// length, for protocols which count the payload in units larger than a
+    /// byte.
+    ///
+    /// When decoding, the length field is scaled before `length_adjustment`
+    /// is applied, and the scaled length is checked against
+    /// `max_frame_length`. When encoding, the length is divided by the
+    /// factor, and frames whose length isn't a multiple of it are rejected
+    /// with an `InvalidInput` error.
+    ///
+    /// Default value is 1.
+    ///
+    /// This configuration option applies to both encoding and decoding.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `factor` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .length_field_length(2)
+    ///     .length_scale(4)
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn length_scale(&mut self, factor: usize) -> &mut Self {
+        assert!(factor > 0, "length scale must be greater than zero");
+        self.length_scale = factor;
+        self
+    }
+
     /// Sets the number of bytes to skip before reading the payload
     ///
     /// Default value is `length_field_len + length_field_offset`
@@ -767,6 +1412,190 @@ impl Builder {
         self
     }
 
+    /// Sets the number of bytes that follow each payload but are not counted
+    /// by the length field
+    ///
+    /// The trailer, for example a CRC over the payload, is read after the
+    /// payload and then discarded, so it is not part of the yielded frame.
+    ///
+    /// Default value is `0`
+    ///
+    /// This configuration option only applies to decoding
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .frame_trailer_len(4)
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn frame_trailer_len(&mut self, val: usize) -> &mut Self {
+        self.frame_trailer_len = val;
+        self
+    }
+
+    /// Sets a range of header bytes to yield alongside each frame's payload
+    ///
+    /// The range is relative to the start of the frame's header and may
+    /// cover bytes that are otherwise skipped, such as a flags field between
+    /// the length field and the payload. The header is read until at least
+    /// `range.end` bytes are available, even if that is more than the length
+    /// field and `num_skip` require.
+    ///
+    /// The captured bytes are only surfaced by `new_read_with_header`.
+    ///
+    /// This configuration option only applies to decoding.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `range.start` is greater than `range.end`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .length_field_length(2)
+    ///     .num_skip(4)
+    ///     .capture_header(2..4)
+    ///     .new_read_with_header(io);
+    /// # }
+    /// ```
+    pub fn capture_header(&mut self, range: Range<usize>) -> &mut Self {
+        assert!(range.start <= range.end, "invalid captured header range");
+        self.captured_header = Some((range.start, range.end));
+        self
+    }
+
+    /// Treats each read from the upstream as exactly one whole frame
+    ///
+    /// This suits message oriented transports, where every read returns a
+    /// single datagram. Rather than buffering until a frame is complete, each
+    /// datagram is decoded on its own: the header is parsed from it, and the
+    /// rest of the datagram must be exactly the payload, followed by the
+    /// trailer if one is configured. Any other length is reported as an
+    /// `InvalidData` error and the datagram is discarded, so decoding resumes
+    /// with the next one.
+    ///
+    /// The read buffer is kept large enough for a header and a payload of the
+    /// max frame length, up to 64KB, so that a datagram isn't split across
+    /// reads.
+    ///
+    /// This configuration option only applies to decoding, and has no effect
+    /// on `new_read_streaming`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .length_field_length(2)
+    ///     .datagram_mode()
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn datagram_mode(&mut self) -> &mut Self {
+        self.datagram_mode = true;
+        self
+    }
+
+    /// Requires EOF to be confirmed by a second zero length read
+    ///
+    /// By default, the first read returning zero bytes ends the stream, as
+    /// is the contract of `Read`. Some misbehaving upstreams return zero
+    /// bytes spuriously though, and treating that as EOF would cut the stream
+    /// short, reporting a partial frame as an error. With `strict_eof(true)`
+    /// a zero length read is followed by another read, and only if that one
+    /// also returns zero bytes does the stream end.
+    ///
+    /// This guards against flaky readers at the cost of an extra read at
+    /// every EOF. It doesn't help with a reader that returns zero bytes twice
+    /// in a row before more data, and with a well behaved reader it's never
+    /// needed, so it's disabled by default.
+    ///
+    /// This configuration option only applies to decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .strict_eof(true)
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn strict_eof(&mut self, val: bool) -> &mut Self {
+        self.strict_eof = val;
+        self
+    }
+
+    /// Sets the read buffer capacity above which the buffer is shrunk after a
+    /// frame is decoded
+    ///
+    /// Reading a large frame grows the read buffer to fit it. Without a
+    /// threshold that allocation is kept for as long as the `FramedRead`
+    /// lives. With one, once a frame is decoded and the buffer's capacity
+    /// exceeds `val` while it holds less than the baseline capacity of data,
+    /// the buffer is reallocated down to the baseline capacity.
+    ///
+    /// This configuration option only applies to decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .read_buffer_shrink_threshold(64 * 1024)
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn read_buffer_shrink_threshold(&mut self, val: usize) -> &mut Self {
+        self.read_buffer_shrink_threshold = Some(val);
+        self
+    }
+
+    /// Sets the capacity the read buffer is shrunk back to
+    ///
+    /// Defaults to the initial capacity of the read buffer. This only has an
+    /// effect if `read_buffer_shrink_threshold` is set.
+    ///
+    /// This configuration option only applies to decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .read_buffer_shrink_threshold(64 * 1024)
+    ///     .baseline_capacity(4 * 1024)
+    ///     .new_read(io);
+    /// # }
+    /// ```
+    pub fn baseline_capacity(&mut self, val: usize) -> &mut Self {
+        self.read_buffer_baseline_capacity = Some(val);
+        self
+    }
+
     /// Create a configured length delimited `FramedRead`
     ///
     /// # Examples
@@ -788,13 +1617,98 @@ impl Builder {
         where T: AsyncRead,
     {
         FramedRead {
-            inner: codec::FramedRead::new(upstream, Decoder {
-                builder: *self,
-                state: DecodeState::Head,
-            }),
+            inner: self.framed_read(upstream, self.decoder()),
         }
     }
 
+    /// Create a configured length delimited `FramedReadWithHeader`
+    ///
+    /// Each frame is yielded along with the header bytes selected by
+    /// `capture_header`. If no range was set, the header bytes are empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .length_field_length(2)
+    ///     .num_skip(4)
+    ///     .capture_header(2..4)
+    ///     .new_read_with_header(io);
+    /// # }
+    /// ```
+    pub fn new_read_with_header<T>(&self, upstream: T) -> FramedReadWithHeader<T>
+        where T: AsyncRead,
+    {
+        FramedReadWithHeader {
+            inner: self.framed_read(upstream, HeaderDecoder { inner: self.decoder() }),
+        }
+    }
+
+    /// Create a configured length delimited `FramedReadStreaming`
+    ///
+    /// Each frame is yielded as a `FramePart::Header` with the payload
+    /// length, then as many `FramePart::Chunk`s as it takes to deliver the
+    /// payload as it's read, then a `FramePart::End`. The max frame length
+    /// still applies to the declared length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tokio_io::AsyncRead;
+    /// use tokio_io::codec::length_delimited::Builder;
+    ///
+    /// # fn bind_read<T: AsyncRead>(io: T) {
+    /// Builder::new()
+    ///     .max_frame_length(1024 * 1024 * 1024)
+    ///     .new_read_streaming(io);
+    /// # }
+    /// ```
+    pub fn new_read_streaming<T>(&self, upstream: T) -> FramedReadStreaming<T>
+        where T: AsyncRead,
+    {
+        let decoder = StreamingDecoder {
+            inner: self.decoder(),
+            remaining: None,
+        };
+
+        FramedReadStreaming {
+            inner: self.framed_read(upstream, decoder),
+        }
+    }
+
+    fn decoder(&self) -> Decoder {
+        Decoder {
+            builder: *self,
+            state: DecodeState::Head,
+            header: None,
+            progress: None,
+        }
+    }
+
+    fn framed_read<T, D>(&self, upstream: T, decoder: D) -> codec::FramedRead<T, D>
+        where T: AsyncRead,
+              D: codec::Decoder,
+    {
+        let mut inner = codec::FramedRead::new(upstream, decoder);
+
+        if self.datagram_mode {
+            inner.set_read_buffer(BytesMut::with_capacity(self.datagram_capacity()));
+        }
+
+        inner.set_read_buffer_shrink_threshold(self.read_buffer_shrink_threshold);
+        inner.set_strict_eof(self.strict_eof);
+
+        if let Some(capacity) = self.read_buffer_baseline_capacity {
+            inner.set_read_buffer_baseline_capacity(capacity);
+        }
+
+        inner
+    }
+
     /// Create a configured length delimited `FramedWrite`
     ///
     /// # Examples
@@ -821,6 +1735,8 @@ impl Builder {
             inner: inner,
             builder: *self,
             frame: None,
+            chunk: None,
+            stream_remaining: None,
         }
     }
 
@@ -851,15 +1767,56 @@ impl Builder {
     }
 
     fn num_head_bytes(&self) -> usize {
-        let num = self.length_field_offset + self.length_field_len;
+        let num = self.length_field_offset + self.length_field_len + self.checksum_len();
+        let num = cmp::max(num, self.captured_header.map(|(_, end)| end).unwrap_or(0));
         cmp::max(num, self.num_skip.unwrap_or(0))
     }
 
+    fn datagram_capacity(&self) -> usize {
+        let len = self.num_head_bytes()
+            .saturating_add(self.max_frame_len)
+            .saturating_add(self.frame_trailer_len);
+        cmp::min(len, MAX_DATAGRAM_CAPACITY)
+    }
+
     fn get_num_skip(&self) -> usize {
-        self.num_skip.unwrap_or(self.length_field_offset + self.length_field_len)
+        self.num_skip.unwrap_or(self.length_field_offset + self.length_field_len + self.checksum_len())
+    }
+
+    fn checksum_len(&self) -> usize {
+        if self.length_field_has_checksum { 1 } else { 0 }
     }
 }
 
+fn checksum(field: &[u8]) -> u8 {
+    field.iter().fold(0, |sum, &b| sum ^ b)
+}
+
+fn parse_ascii_length(digits: &[u8]) -> io::Result<u64> {
+    let mut n: u64 = 0;
+
+    for &b in digits {
+        if b < b'0' || b > b'9' {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "length field contains a non-digit byte"));
+        }
+
+        n = match n.checked_mul(10).and_then(|n| n.checked_add((b - b'0') as u64)) {
+            Some(n) => n,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "length field overflows a u64")),
+        };
+    }
+
+    Ok(n)
+}
+
+// ===== impl Progress =====
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Progress")
+            .finish()
+    }
+}
 
 // ===== impl FrameTooBig =====
 
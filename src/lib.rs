@@ -20,7 +20,9 @@ use std::io as std_io;
 use std::io::Write;
 
 use futures::{Async, Future, Poll, Stream};
-use bytes::{Buf, BufMut};
+use bytes::{Buf, BufMut, Bytes};
+
+use bytes_queue::BytesQueue;
 
 /// A convenience typedef around a `Future` whose error component is `io::Error`
 pub type IoFuture<T> = Box<Future<Item = T, Error = std_io::Error> + Send>;
@@ -28,6 +30,58 @@ pub type IoFuture<T> = Box<Future<Item = T, Error = std_io::Error> + Send>;
 /// A convenience typedef around a `Stream` whose error component is `io::Error`
 pub type IoStream<T> = Box<Stream<Item = T, Error = std_io::Error> + Send>;
 
+/// An extension trait for converting any `Future` whose error component is
+/// `io::Error` into an `IoFuture`.
+///
+/// Boxing erases the concrete type of a future, which is useful when it needs
+/// to be stored in a struct or returned from a trait method. It does, however,
+/// cost an allocation and a virtual call on each `poll`, so on hot paths where
+/// the concrete type can be named (or returned generically) it's typically
+/// better to avoid boxing altogether.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate futures;
+/// # extern crate tokio_io;
+/// use std::io;
+///
+/// use futures::future;
+/// use tokio_io::{IoFuture, IntoIoFuture};
+///
+/// # fn main() {
+/// let f: IoFuture<u32> = future::ok::<u32, io::Error>(1).into_io_future();
+/// # drop(f);
+/// # }
+/// ```
+pub trait IntoIoFuture: Future<Error = std_io::Error> {
+    /// Boxes this future into an `IoFuture`.
+    fn into_io_future(self) -> IoFuture<Self::Item>
+        where Self: Sized + Send + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<F: Future<Error = std_io::Error>> IntoIoFuture for F {}
+
+/// An extension trait for converting any `Stream` whose error component is
+/// `io::Error` into an `IoStream`.
+///
+/// See [`IntoIoFuture`] for a discussion of when boxing is appropriate.
+///
+/// [`IntoIoFuture`]: trait.IntoIoFuture.html
+pub trait IntoIoStream: Stream<Error = std_io::Error> {
+    /// Boxes this stream into an `IoStream`.
+    fn into_io_stream(self) -> IoStream<Self::Item>
+        where Self: Sized + Send + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+impl<S: Stream<Error = std_io::Error>> IntoIoStream for S {}
+
 /// A convenience macro for working with `io::Result<T>` from the `Read` and
 /// `Write` traits.
 ///
@@ -45,26 +99,104 @@ macro_rules! try_nb {
     })
 }
 
+// Same as `try_nb!`, but counts a `WouldBlock` error against the instrumented
+// combinator `$site` and emits a `trace!` message before returning
+// `NotReady`, so stalled futures can be diagnosed.
+macro_rules! try_nb_traced {
+    ($site:expr, $e:expr, $($arg:tt)+) => (match $e {
+        Ok(t) => t,
+        Err(ref e) if e.kind() == ::std::io::ErrorKind::WouldBlock => {
+            $crate::instrument::would_block($site);
+            $crate::log::trace!($($arg)+);
+            return Ok($crate::futures::Async::NotReady)
+        }
+        Err(e) => return Err(e.into()),
+    });
+}
+
+// Calls `f` again for as long as it fails with an `Interrupted` error,
+// following the convention of the std I/O helpers.
+fn retry_interrupted<T, F>(mut f: F) -> std_io::Result<T>
+    where F: FnMut() -> std_io::Result<T>,
+{
+    loop {
+        match f() {
+            Err(ref e) if e.kind() == std_io::ErrorKind::Interrupted => {}
+            ret => return ret,
+        }
+    }
+}
+
 pub mod io;
 pub mod codec;
 
 mod allow_std;
+mod alternating;
+mod and_then;
+mod asn1;
+mod batched;
+#[cfg(feature = "bencode")]
+mod bencode;
+mod bit_packed;
+mod block_align;
+mod blocking;
+mod bom_strip;
+mod byte_quota;
+mod bytes_queue;
+mod checksum;
 mod codecs;
+mod concat_readers;
 mod copy;
+mod count_errors;
+mod csv;
+mod decode_all;
+mod either;
+mod encode_all;
+mod filter_frames;
 mod flush;
+mod fragment;
 mod framed;
 mod framed_read;
+mod framed_split;
 mod framed_write;
+mod header_body;
+mod hex_dump;
+mod instrument;
+mod key_value;
 mod length_delimited;
 mod lines;
+mod lines_bytes;
+mod min_frame_len;
+mod permitted;
+mod phased;
+mod profile_decode;
 mod read;
+mod read_budget;
 mod read_exact;
+mod read_header_then_frame;
+mod read_into_window;
 mod read_to_end;
 mod read_until;
+mod rle;
+mod select_read;
+mod sequenced;
+mod shared;
 mod shutdown;
+mod slip;
+mod slurp;
 mod split;
+mod stale_frame_guard;
+mod sync_framed;
+mod text_command;
+mod transactional;
+mod transform;
+mod validated;
 mod window;
+mod with_raw;
 mod write_all;
+mod write_all_buf;
+mod write_stream;
+mod xor_crypt;
 
 use codec::{Decoder, Encoder, Framed};
 use split::{ReadHalf, WriteHalf};
@@ -147,7 +279,7 @@ pub trait AsyncRead: std_io::Read {
 
                 self.prepare_uninitialized_buffer(b);
 
-                try_nb!(self.read(b))
+                try_nb!(::retry_interrupted(|| self.read(b)))
             };
 
             buf.advance_mut(n);
@@ -155,6 +287,33 @@ pub trait AsyncRead: std_io::Read {
         }
     }
 
+    /// Pull bytes from this source into the specified `Buf` until it's full,
+    /// returning how many bytes were read.
+    ///
+    /// Unlike `read_buf`, which performs a single read, this keeps reading
+    /// until `buf` has no remaining capacity, EOF is reached, or the read
+    /// would block. The total number of bytes read is returned, and
+    /// `NotReady` is only returned if the very first read would block.
+    ///
+    /// If an error occurs after some bytes have been read, the error is
+    /// returned and the bytes read so far are left in `buf`.
+    fn read_buf_full<B: BufMut>(&mut self, buf: &mut B) -> Poll<usize, std_io::Error>
+        where Self: Sized,
+    {
+        let mut total = 0;
+
+        while buf.has_remaining_mut() {
+            match try!(self.read_buf(buf)) {
+                Async::Ready(0) => break,
+                Async::Ready(n) => total += n,
+                Async::NotReady if total == 0 => return Ok(Async::NotReady),
+                Async::NotReady => break,
+            }
+        }
+
+        Ok(Async::Ready(total))
+    }
+
     /// Provides a `Stream` and `Sink` interface for reading and writing to this
     /// `Io` object, using `Decode` and `Encode` to read and write the raw data.
     ///
@@ -181,9 +340,10 @@ pub trait AsyncRead: std_io::Read {
     /// Helper method for splitting this read/write object into two halves.
     ///
     /// The two halves returned implement the `Read` and `Write` traits,
-    /// respectively.
+    /// respectively. The write half additionally implements `AsyncWrite` if
+    /// this object does, but only `Write` is required to split it.
     fn split(self) -> (ReadHalf<Self>, WriteHalf<Self>)
-        where Self: AsyncWrite + Sized,
+        where Self: Write + Sized,
     {
         split::split(self)
     }
@@ -310,17 +470,104 @@ pub trait AsyncWrite: std_io::Write {
         buf.advance(n);
         Ok(Async::Ready(n))
     }
+
+    /// Write an owned `Bytes` into this value, returning how many bytes were
+    /// written.
+    ///
+    /// Like `write_buf`, this advances `bytes` by the number of bytes
+    /// written, leaving only the unwritten remainder. The difference is that
+    /// the data is handed over as a reference counted `Bytes`, so writers
+    /// which queue data instead of copying it somewhere, such as buffering or
+    /// queueing sinks, can override this to keep a handle to the written
+    /// bytes, for example with `bytes.split_to(n)`, without a copy. Since
+    /// `Bytes` is immutable, the caller can't observe whether this happened.
+    ///
+    /// The default implementation writes the bytes with `write`, copying
+    /// them as usual.
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, std_io::Error> {
+        if bytes.is_empty() {
+            return Ok(Async::Ready(0));
+        }
+
+        let n = try_nb!(self.write(&bytes[..]));
+        bytes.advance(n);
+        Ok(Async::Ready(n))
+    }
+
+    /// Write a queue of `Bytes` buffers into this value, returning how many
+    /// bytes were written.
+    ///
+    /// Like `write_buf`, this advances `bufs` by the number of bytes written.
+    /// Writers supporting vectored I/O, such as `writev`, should override
+    /// this to submit the queued buffers in a single call, each one as it
+    /// is, which is what `FramedWrite` relies on in its vectored mode to
+    /// avoid copying frames together.
+    ///
+    /// The default implementation writes the front buffer with `write` if
+    /// it's the only one. Otherwise, as vectored writes aren't available, it
+    /// concatenates buffers from the front of the queue, up to 64KB, and
+    /// writes those.
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, std_io::Error> {
+        if bufs.is_empty() {
+            return Ok(Async::Ready(0));
+        }
+
+        let n = if bufs.chunks().len() == 1 {
+            try_nb!(self.write(bufs.bytes()))
+        } else {
+            let mut data = Vec::with_capacity(::std::cmp::min(bufs.len(), MAX_CONCAT_LEN));
+
+            for buf in bufs.chunks() {
+                let n = ::std::cmp::min(buf.len(), MAX_CONCAT_LEN - data.len());
+                data.extend_from_slice(&buf[..n]);
+
+                if data.len() == MAX_CONCAT_LEN {
+                    break;
+                }
+            }
+
+            try_nb!(self.write(&data))
+        };
+
+        bufs.advance(n);
+        Ok(Async::Ready(n))
+    }
 }
 
+// Most bytes copied together by the default `AsyncWrite::write_buf_vectored`
+const MAX_CONCAT_LEN: usize = 64 * 1024;
+
 impl<T: ?Sized + AsyncWrite> AsyncWrite for Box<T> {
     fn shutdown(&mut self) -> Poll<(), std_io::Error> {
         (**self).shutdown()
     }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, std_io::Error> {
+        (**self).write_bytes(bytes)
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, std_io::Error> {
+        (**self).write_buf_vectored(bufs)
+    }
 }
 impl<'a, T: ?Sized + AsyncWrite> AsyncWrite for &'a mut T {
     fn shutdown(&mut self) -> Poll<(), std_io::Error> {
         (**self).shutdown()
     }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, std_io::Error> {
+        (**self).write_bytes(bytes)
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, std_io::Error> {
+        (**self).write_buf_vectored(bufs)
+    }
+}
+
+impl<'a> AsyncWrite for &'a mut [u8] {
+    fn shutdown(&mut self) -> Poll<(), std_io::Error> {
+        Ok(().into())
+    }
 }
 
 impl AsyncRead for std_io::Repeat {
@@ -329,6 +576,12 @@ impl AsyncRead for std_io::Repeat {
     }
 }
 
+impl AsyncRead for std_io::Empty {
+    unsafe fn prepare_uninitialized_buffer(&self, _: &mut [u8]) -> bool {
+        false
+    }
+}
+
 impl AsyncWrite for std_io::Sink {
     fn shutdown(&mut self) -> Poll<(), std_io::Error> {
         Ok(().into())
@@ -0,0 +1,73 @@
+use std::io::{self, BufRead};
+use std::mem;
+
+use bytes::Bytes;
+use futures::{Poll, Stream};
+
+use AsyncRead;
+
+/// Combinator created by the top-level `lines_bytes` method which is a stream
+/// over the raw lines of an I/O object.
+#[derive(Debug)]
+pub struct LinesBytes<A> {
+    io: A,
+    line: Vec<u8>,
+    strip_carriage_return: bool,
+}
+
+/// Creates a new stream from the I/O object given representing the lines of
+/// input that are found on `A`, as raw bytes.
+///
+/// This is like `lines`, except that the yielded lines are not validated to
+/// be UTF-8. Each line is yielded as `Bytes` with the trailing `\n` removed,
+/// as well as a `\r` preceding it unless this is disabled with
+/// `set_strip_carriage_return`. A final line without a terminator is yielded
+/// once `a` reaches EOF.
+pub fn lines_bytes<A>(a: A) -> LinesBytes<A>
+    where A: AsyncRead + BufRead,
+{
+    LinesBytes {
+        io: a,
+        line: Vec::new(),
+        strip_carriage_return: true,
+    }
+}
+
+impl<A> LinesBytes<A> {
+    /// Sets whether a `\r` preceding the `\n` terminator is removed from
+    /// yielded lines.
+    ///
+    /// This is enabled by default.
+    pub fn set_strip_carriage_return(&mut self, val: bool) {
+        self.strip_carriage_return = val;
+    }
+
+    /// Returns the underlying I/O object.
+    ///
+    /// Note that this may lose data already read into internal buffers. It's
+    /// recommended to only call this once the stream has reached its end.
+    pub fn into_inner(self) -> A {
+        self.io
+    }
+}
+
+impl<A> Stream for LinesBytes<A>
+    where A: AsyncRead + BufRead,
+{
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Bytes>, io::Error> {
+        let n = try_nb!(self.io.read_until(b'\n', &mut self.line));
+        if n == 0 && self.line.len() == 0 {
+            return Ok(None.into())
+        }
+        if self.line.ends_with(b"\n") {
+            self.line.pop();
+            if self.strip_carriage_return && self.line.ends_with(b"\r") {
+                self.line.pop();
+            }
+        }
+        Ok(Some(mem::replace(&mut self.line, Vec::new()).into()).into())
+    }
+}
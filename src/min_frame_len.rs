@@ -0,0 +1,99 @@
+use std::{fmt, io};
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which rejects frames shorter than a minimum length.
+///
+/// Each frame decoded by the inner codec is checked against the minimum
+/// before it's returned, and an error of kind `InvalidData` is returned in
+/// its place if it's too short. This is useful for protocols where every
+/// frame carries at least a fixed size header, so truncated or garbage
+/// frames are caught early.
+///
+/// Encoding is passed through to the inner codec unchanged.
+pub struct MinFrameLen<C> {
+    inner: C,
+    min: usize,
+}
+
+impl<C> MinFrameLen<C> {
+    /// Creates a new `MinFrameLen` rejecting frames shorter than `min` bytes.
+    pub fn new(inner: C, min: usize) -> MinFrameLen<C> {
+        MinFrameLen {
+            inner: inner,
+            min: min,
+        }
+    }
+
+    /// Returns the minimum frame length.
+    pub fn min_frame_length(&self) -> usize {
+        self.min
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `MinFrameLen`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn check<T: AsRef<[u8]>>(&self, frame: Option<T>) -> io::Result<Option<T>> {
+        match frame {
+            Some(ref frame) if frame.as_ref().len() < self.min => {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "frame too small"))
+            }
+            frame => Ok(frame),
+        }
+    }
+}
+
+impl<C> Decoder for MinFrameLen<C>
+    where C: Decoder,
+          C::Item: AsRef<[u8]>,
+          C::Error: From<io::Error>,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let frame = try!(self.inner.decode(src));
+        Ok(try!(self.check(frame)))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let frame = try!(self.inner.decode_eof(src));
+        Ok(try!(self.check(frame)))
+    }
+}
+
+impl<C: Encoder> Encoder for MinFrameLen<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for MinFrameLen<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MinFrameLen")
+            .field("inner", &self.inner)
+            .field("min", &self.min)
+            .finish()
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Read};
+
+use AsyncRead;
+
+/// A grant to read a number of bytes, as acquired by `Permitted`.
+///
+/// A permit is released by dropping it, so implementations backed by a
+/// shared pool, such as a semaphore counting the bytes buffered across all
+/// connections, return their capacity to the pool in `Drop`.
+pub trait ReadPermit {
+    /// Returns the number of bytes this permit allows to be read.
+    fn len(&self) -> usize;
+
+    /// Shrinks the permit to `len` bytes, releasing the rest.
+    ///
+    /// This is called when a read returns fewer bytes than the permit
+    /// allows, and when only part of the bytes read under the permit have
+    /// been consumed. `len` is always less than the current length.
+    fn shrink(&mut self, len: usize);
+}
+
+/// An `AsyncRead` adapter which only reads from the underlying object while
+/// permits can be acquired.
+///
+/// Before each read, a permit is acquired by calling the `acquire` closure.
+/// If it returns `None`, the read returns a "would block" error, which is
+/// `NotReady` for futures-aware callers. Unlike most I/O objects, this
+/// doesn't arrange for the current task to be notified, so the caller is
+/// responsible for polling again once permits are available.
+///
+/// Otherwise, at most `permit.len()` bytes are read, and the permit is
+/// shrunk to the number of bytes actually read. It's then held until those
+/// bytes are reported as processed through `consume`, which lets a permit
+/// pool bound the memory used by bytes which have been read but not yet
+/// handled downstream, across any number of connections.
+pub struct Permitted<R, F, P> {
+    inner: R,
+    acquire: F,
+    held: VecDeque<P>,
+    held_len: usize,
+}
+
+impl<R, F, P> Permitted<R, F, P>
+    where F: FnMut() -> Option<P>,
+          P: ReadPermit,
+{
+    /// Creates a new `Permitted` reading from `inner` under the permits
+    /// returned by `acquire`.
+    pub fn new(inner: R, acquire: F) -> Permitted<R, F, P> {
+        Permitted {
+            inner: inner,
+            acquire: acquire,
+            held: VecDeque::new(),
+            held_len: 0,
+        }
+    }
+
+    /// Returns the number of bytes which have been read but not yet
+    /// consumed, and so are still covered by held permits.
+    pub fn held_len(&self) -> usize {
+        self.held_len
+    }
+
+    /// Reports that `n` of the bytes read have been processed, releasing
+    /// the permits covering them.
+    ///
+    /// Bytes are consumed in the order in which they were read.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `n` is greater than `held_len()`.
+    pub fn consume(&mut self, mut n: usize) {
+        assert!(n <= self.held_len, "consumed more bytes than were read");
+        self.held_len -= n;
+
+        while n > 0 {
+            let len = self.held[0].len();
+
+            if n < len {
+                self.held[0].shrink(len - n);
+                return;
+            }
+
+            self.held.pop_front();
+            n -= len;
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Note that bytes read through this reference are not covered by any
+    /// permit.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `Permitted`, returning the underlying reader.
+    ///
+    /// All held permits are released.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, F, P> Read for Permitted<R, F, P>
+    where R: Read,
+          F: FnMut() -> Option<P>,
+          P: ReadPermit,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut permit = match (self.acquire)() {
+            Some(permit) if permit.len() > 0 => permit,
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "no read permit available"));
+            }
+        };
+
+        let len = ::std::cmp::min(buf.len(), permit.len());
+
+        // The permit is released when dropped, if nothing was read under it
+        let n = try!(self.inner.read(&mut buf[..len]));
+
+        if n > 0 {
+            if n < permit.len() {
+                permit.shrink(n);
+            }
+
+            self.held.push_back(permit);
+            self.held_len += n;
+        }
+
+        Ok(n)
+    }
+}
+
+impl<R, F, P> AsyncRead for Permitted<R, F, P>
+    where R: AsyncRead,
+          F: FnMut() -> Option<P>,
+          P: ReadPermit,
+{
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<R: fmt::Debug, F, P> fmt::Debug for Permitted<R, F, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Permitted")
+            .field("inner", &self.inner)
+            .field("held_len", &self.held_len)
+            .finish()
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use bytes::Bytes;
+use futures::Poll;
+
+use {AsyncRead, AsyncWrite};
+use bytes_queue::BytesQueue;
+
+/// The operations allowed on a `Phased` I/O object in some protocol phase.
+///
+/// Protocols with their own phases, such as an enum with a variant for each
+/// step of a handshake, implement this for that type and pass it to
+/// `Phased::set_phase` directly. `Phase` covers the simple cases.
+pub trait PhasePolicy {
+    /// Returns whether reading is allowed in this phase.
+    fn can_read(&self) -> bool;
+
+    /// Returns whether writing, including shutting down the write side, is
+    /// allowed in this phase.
+    fn can_write(&self) -> bool;
+}
+
+/// A general purpose set of protocol phases.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Phase {
+    /// Both reading and writing are allowed.
+    ReadWrite,
+    /// Only reading is allowed.
+    ReadOnly,
+    /// Only writing is allowed.
+    WriteOnly,
+    /// Neither reading nor writing is allowed.
+    Closed,
+}
+
+impl PhasePolicy for Phase {
+    fn can_read(&self) -> bool {
+        match *self {
+            Phase::ReadWrite | Phase::ReadOnly => true,
+            Phase::WriteOnly | Phase::Closed => false,
+        }
+    }
+
+    fn can_write(&self) -> bool {
+        match *self {
+            Phase::ReadWrite | Phase::WriteOnly => true,
+            Phase::ReadOnly | Phase::Closed => false,
+        }
+    }
+}
+
+/// An I/O object which checks that every read and write happens in a
+/// protocol phase which allows it.
+///
+/// The current phase is set with `set_phase`, and its `PhasePolicy` decides
+/// whether reads and writes are allowed. An operation in the wrong phase is
+/// a bug in the protocol implementation, so with debug assertions enabled it
+/// panics, pointing straight at the offending call. Otherwise it fails with
+/// an error of kind `Other`, without touching the underlying object.
+///
+/// Flushing is always allowed, since it only pushes out data written in an
+/// earlier phase.
+#[derive(Debug)]
+pub struct Phased<T, P = Phase> {
+    inner: T,
+    phase: P,
+}
+
+impl<T, P> Phased<T, P>
+    where P: PhasePolicy + fmt::Debug,
+{
+    /// Creates a new `Phased` wrapping `inner`, starting in `phase`.
+    pub fn new(inner: T, phase: P) -> Phased<T, P> {
+        Phased {
+            inner: inner,
+            phase: phase,
+        }
+    }
+
+    /// Returns the current phase.
+    pub fn phase(&self) -> &P {
+        &self.phase
+    }
+
+    /// Transitions to `phase`, returning the previous phase.
+    pub fn set_phase(&mut self, phase: P) -> P {
+        ::std::mem::replace(&mut self.phase, phase)
+    }
+
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    ///
+    /// Note that operations through this reference aren't checked against
+    /// the current phase.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `Phased`, returning the underlying I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn violation(&self, op: &str) -> io::Error {
+        let msg = format!("{} during {:?} phase", op, self.phase);
+
+        if cfg!(debug_assertions) {
+            panic!("{}", msg);
+        }
+
+        io::Error::new(io::ErrorKind::Other, msg)
+    }
+}
+
+impl<T, P> Read for Phased<T, P>
+    where T: Read,
+          P: PhasePolicy + fmt::Debug,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.phase.can_read() {
+            return Err(self.violation("read"));
+        }
+
+        self.inner.read(buf)
+    }
+}
+
+impl<T, P> AsyncRead for Phased<T, P>
+    where T: AsyncRead,
+          P: PhasePolicy + fmt::Debug,
+{
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T, P> Write for Phased<T, P>
+    where T: Write,
+          P: PhasePolicy + fmt::Debug,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.phase.can_write() {
+            return Err(self.violation("write"));
+        }
+
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T, P> AsyncWrite for Phased<T, P>
+    where T: AsyncWrite,
+          P: PhasePolicy + fmt::Debug,
+{
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        if !self.phase.can_write() {
+            return Err(self.violation("shutdown"));
+        }
+
+        self.inner.shutdown()
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        if !self.phase.can_write() {
+            return Err(self.violation("write"));
+        }
+
+        self.inner.write_bytes(bytes)
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        if !self.phase.can_write() {
+            return Err(self.violation("write"));
+        }
+
+        self.inner.write_buf_vectored(bufs)
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which measures how long each call to the inner
+/// decoder takes.
+///
+/// The time is read from a user-supplied clock, a function returning a
+/// timestamp in nanoseconds, so that no particular timer is imposed. Around
+/// each call to `decode` or `decode_eof` the clock is read twice, and the
+/// difference is passed to the sink. Every call is measured, including those
+/// which return no frame or an error, since those also cost time. If the
+/// clock goes backwards the elapsed time is reported as zero.
+///
+/// This is typically created through `Framed::profile_decode`. Encoding is
+/// passed through to the inner codec unchanged.
+pub struct ProfileDecode<C, K, S> {
+    inner: C,
+    clock: K,
+    sink: S,
+}
+
+impl<C, K, S> ProfileDecode<C, K, S>
+    where C: Decoder,
+          K: Fn() -> u64,
+          S: FnMut(u64),
+{
+    /// Creates a new `ProfileDecode` timing `inner` with `clock` and passing
+    /// the elapsed nanoseconds of each decode to `sink`.
+    pub fn new(inner: C, clock: K, sink: S) -> ProfileDecode<C, K, S> {
+        ProfileDecode {
+            inner: inner,
+            clock: clock,
+            sink: sink,
+        }
+    }
+}
+
+impl<C, K, S> ProfileDecode<C, K, S> {
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `ProfileDecode`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, K, S> Decoder for ProfileDecode<C, K, S>
+    where C: Decoder,
+          K: Fn() -> u64,
+          S: FnMut(u64),
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let start = (self.clock)();
+        let res = self.inner.decode(src);
+        (self.sink)((self.clock)().saturating_sub(start));
+        res
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let start = (self.clock)();
+        let res = self.inner.decode_eof(src);
+        (self.sink)((self.clock)().saturating_sub(start));
+        res
+    }
+}
+
+impl<C: Encoder, K, S> Encoder for ProfileDecode<C, K, S> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug, K, S> fmt::Debug for ProfileDecode<C, K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProfileDecode")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
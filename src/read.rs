@@ -44,7 +44,7 @@ impl<R, T> Future for Read<R, T>
 
     fn poll(&mut self) -> Poll<(R, T, usize), io::Error> {
         let nread = match self.state {
-            State::Pending { ref mut rd, ref mut buf } => try_nb!(rd.read(&mut buf.as_mut()[..])),
+            State::Pending { ref mut rd, ref mut buf } => try_nb!(::retry_interrupted(|| rd.read(&mut buf.as_mut()[..]))),
             State::Empty => panic!("poll a Read after it's done"),
         };
 
@@ -0,0 +1,87 @@
+use std::io::{self, Read};
+
+use futures::task;
+
+use AsyncRead;
+
+/// An `AsyncRead` adapter which limits the number of reads performed on the
+/// underlying object before yielding back to the event loop.
+///
+/// Once `budget` reads have been made on the underlying object, the next read
+/// returns a "would block" error instead and the current task is notified, so
+/// that it's polled again promptly. The budget is then replenished. When used
+/// beneath a combinator such as `FramedRead`, this caps the number of reads
+/// per `poll`, which keeps a reader that repeatedly returns small chunks of
+/// data from monopolizing the event loop.
+///
+/// # Panics
+///
+/// Reading from a `ReadBudget` whose budget is exhausted will panic if not
+/// called within the context of a future's task.
+#[derive(Debug)]
+pub struct ReadBudget<R> {
+    inner: R,
+    budget: usize,
+    remaining: usize,
+}
+
+impl<R> ReadBudget<R> {
+    /// Creates a new `ReadBudget` allowing `budget` reads on `inner` before
+    /// yielding.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `budget` is zero.
+    pub fn new(inner: R, budget: usize) -> ReadBudget<R> {
+        assert!(budget > 0, "read budget must be at least one");
+
+        ReadBudget {
+            inner: inner,
+            budget: budget,
+            remaining: budget,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `ReadBudget`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ReadBudget<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            self.remaining = self.budget;
+            task::current().notify();
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "read budget exhausted"));
+        }
+
+        let res = self.inner.read(buf);
+
+        match res {
+            // The underlying reader yields on its own, so start afresh
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.remaining = self.budget;
+            }
+            _ => self.remaining -= 1,
+        }
+
+        res
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for ReadBudget<R> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
@@ -4,13 +4,15 @@ use std::mem;
 use futures::{Poll, Future};
 
 use AsyncRead;
+use instrument::{self, Site};
 
 /// A future which can be used to easily read exactly enough bytes to fill
 /// a buffer.
 ///
-/// Created by the [`read_exact`] function.
+/// Created by the [`read_exact`] and [`read_exact_at`] functions.
 ///
 /// [`read_exact`]: fn.read_exact.html
+/// [`read_exact_at`]: fn.read_exact_at.html
 #[derive(Debug)]
 pub struct ReadExact<A, T> {
     state: State<A, T>,
@@ -49,6 +51,30 @@ pub fn read_exact<A, T>(a: A, buf: T) -> ReadExact<A, T>
     }
 }
 
+/// Creates a future which will read exactly enough bytes to fill `buf` from
+/// `offset` to its end, returning an error if EOF is hit sooner.
+///
+/// The bytes before `offset` are left untouched, which makes this useful for
+/// filling in the rest of a buffer whose front has already been read. If
+/// `offset` is at or past the end of `buf` the future completes without
+/// reading anything.
+///
+/// This otherwise behaves the same as [`read_exact`].
+///
+/// [`read_exact`]: fn.read_exact.html
+pub fn read_exact_at<A, T>(a: A, buf: T, offset: usize) -> ReadExact<A, T>
+    where A: AsyncRead,
+          T: AsMut<[u8]>,
+{
+    ReadExact {
+        state: State::Reading {
+            a: a,
+            buf: buf,
+            pos: offset,
+        },
+    }
+}
+
 fn eof() -> io::Error {
     io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
 }
@@ -61,11 +87,16 @@ impl<A, T> Future for ReadExact<A, T>
     type Error = io::Error;
 
     fn poll(&mut self) -> Poll<(A, T), io::Error> {
+        instrument::poll(Site::ReadExact);
+
         match self.state {
             State::Reading { ref mut a, ref mut buf, ref mut pos } => {
                 let buf = buf.as_mut();
                 while *pos < buf.len() {
-                    let n = try_nb!(a.read(&mut buf[*pos..]));
+                    let n = try_nb_traced!(Site::ReadExact,
+                                           ::retry_interrupted(|| a.read(&mut buf[*pos..])),
+                                           "read_exact: would block; pos={} len={}",
+                                           *pos, buf.len());
                     *pos += n;
                     if n == 0 {
                         return Err(eof())
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::io;
+use std::mem;
+
+use bytes::BytesMut;
+use futures::{Async, Future, Poll};
+
+use AsyncRead;
+use codec::Decoder;
+use framed::{Framed, FramedParts};
+
+const INITIAL_CAPACITY: usize = 8 * 1024;
+
+/// A future which reads a fixed size header and then frames the rest of the
+/// stream.
+///
+/// Created by the [`read_header_then_frame`] function.
+///
+/// [`read_header_then_frame`]: fn.read_header_then_frame.html
+pub struct ReadHeaderThenFrame<R, F, U> {
+    state: State<R, F, U>,
+}
+
+enum State<R, F, U> {
+    Reading {
+        reader: R,
+        header_len: usize,
+        decode_header: F,
+        codec: U,
+        buf: BytesMut,
+    },
+    Empty,
+}
+
+/// Creates a future which reads a `header_len` byte header from `reader`,
+/// decodes it with `decode_header`, and then frames the rest of the stream
+/// with `codec`.
+///
+/// Reads from `reader` aren't limited to the header, so bytes belonging to
+/// the body may be read along with it. Those bytes are handed to the returned
+/// `Framed` as its read buffer, so no data is lost when switching from the
+/// header to framed decoding.
+///
+/// The returned future resolves to the decoded header and the `Framed`. If
+/// EOF is hit before the whole header is read, the future resolves to an
+/// `UnexpectedEof` error. Errors returned by `decode_header` are passed
+/// through.
+pub fn read_header_then_frame<R, H, F, U>(reader: R,
+                                          header_len: usize,
+                                          decode_header: F,
+                                          codec: U)
+                                          -> ReadHeaderThenFrame<R, F, U>
+    where R: AsyncRead,
+          F: FnMut(&[u8]) -> Result<H, io::Error>,
+          U: Decoder,
+{
+    let capacity = if header_len > INITIAL_CAPACITY { header_len } else { INITIAL_CAPACITY };
+
+    ReadHeaderThenFrame {
+        state: State::Reading {
+            reader: reader,
+            header_len: header_len,
+            decode_header: decode_header,
+            codec: codec,
+            buf: BytesMut::with_capacity(capacity),
+        },
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
+}
+
+impl<R, H, F, U> Future for ReadHeaderThenFrame<R, F, U>
+    where R: AsyncRead,
+          F: FnMut(&[u8]) -> Result<H, io::Error>,
+          U: Decoder,
+{
+    type Item = (H, Framed<R, U>);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(H, Framed<R, U>), io::Error> {
+        match self.state {
+            State::Reading { ref mut reader, header_len, ref mut buf, .. } => {
+                while buf.len() < header_len {
+                    if buf.capacity() == buf.len() {
+                        buf.reserve(INITIAL_CAPACITY);
+                    }
+
+                    let n = try_ready!(reader.read_buf(buf));
+
+                    if n == 0 {
+                        return Err(eof());
+                    }
+                }
+            }
+            State::Empty => panic!("poll a ReadHeaderThenFrame after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { reader, header_len, mut decode_header, codec, mut buf } => {
+                let header = buf.split_to(header_len);
+                let header = try!(decode_header(&header));
+
+                let parts = FramedParts {
+                    inner: reader,
+                    readbuf: buf,
+                    writebuf: BytesMut::new(),
+                };
+
+                Ok(Async::Ready((header, Framed::from_parts(parts, codec))))
+            }
+            State::Empty => panic!(),
+        }
+    }
+}
+
+impl<R, F, U> fmt::Debug for ReadHeaderThenFrame<R, F, U>
+    where R: fmt::Debug,
+          U: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.state {
+            State::Reading { ref reader, header_len, ref codec, ref buf, .. } => {
+                f.debug_struct("ReadHeaderThenFrame")
+                 .field("reader", reader)
+                 .field("header_len", &header_len)
+                 .field("codec", codec)
+                 .field("buf", buf)
+                 .finish()
+            }
+            State::Empty => {
+                f.debug_struct("ReadHeaderThenFrame")
+                 .finish()
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::io;
+use std::mem;
+
+use futures::{Poll, Future};
+
+use AsyncRead;
+use instrument::{self, Site};
+use window::Window;
+
+/// A future which fills the region of a buffer covered by a `Window`.
+///
+/// Created by the [`read_into_window`] function.
+///
+/// [`read_into_window`]: fn.read_into_window.html
+#[derive(Debug)]
+pub struct ReadIntoWindow<A, T> {
+    state: State<A, T>,
+}
+
+#[derive(Debug)]
+enum State<A, T> {
+    Reading {
+        a: A,
+        window: Window<T>,
+    },
+    Empty,
+}
+
+/// Creates a future which will read exactly enough bytes to fill the region
+/// of the underlying buffer covered by `window`, returning an error if EOF is
+/// hit sooner.
+///
+/// Data is read directly into the underlying buffer, and the window's start
+/// is advanced past the bytes as they arrive, so the window always covers the
+/// part which is still to be filled. This allows scattering reads to precise
+/// offsets of a larger buffer, such as when reassembling fragments, without
+/// slicing it up first. The bytes outside of the window are left untouched.
+///
+/// The returned future will resolve to both the I/O stream and the window
+/// once the read operation is completed, at which point the window is empty,
+/// starting at its original end. The underlying buffer can be recovered with
+/// `Window::into_inner`.
+///
+/// In the case of an error the window and the object will be discarded, with
+/// the error yielded.
+pub fn read_into_window<A, T>(a: A, window: Window<T>) -> ReadIntoWindow<A, T>
+    where A: AsyncRead,
+          T: AsRef<[u8]> + AsMut<[u8]>,
+{
+    ReadIntoWindow {
+        state: State::Reading {
+            a: a,
+            window: window,
+        },
+    }
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")
+}
+
+impl<A, T> Future for ReadIntoWindow<A, T>
+    where A: AsyncRead,
+          T: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Item = (A, Window<T>);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, Window<T>), io::Error> {
+        instrument::poll(Site::ReadIntoWindow);
+
+        match self.state {
+            State::Reading { ref mut a, ref mut window } => {
+                while window.start() < window.end() {
+                    let n = try_nb_traced!(Site::ReadIntoWindow,
+                                           ::retry_interrupted(|| a.read(window.as_mut())),
+                                           "read_into_window: would block; start={} end={}",
+                                           window.start(), window.end());
+                    if n == 0 {
+                        return Err(eof())
+                    }
+                    let start = window.start() + n;
+                    window.set_start(start);
+                }
+            }
+            State::Empty => panic!("poll a ReadIntoWindow after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, window } => Ok((a, window).into()),
+            State::Empty => panic!(),
+        }
+    }
+}
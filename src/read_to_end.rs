@@ -4,6 +4,7 @@ use std::mem;
 use futures::{Poll, Future};
 
 use AsyncRead;
+use instrument::{self, Site};
 
 /// A future which can be used to easily read the entire contents of a stream
 /// into a vector.
@@ -50,12 +51,16 @@ impl<A> Future for ReadToEnd<A>
     type Error = io::Error;
 
     fn poll(&mut self) -> Poll<(A, Vec<u8>), io::Error> {
+        instrument::poll(Site::ReadToEnd);
+
         match self.state {
             State::Reading { ref mut a, ref mut buf } => {
                 // If we get `Ok`, then we know the stream hit EOF and we're done. If we
                 // hit "would block" then all the read data so far is in our buffer, and
                 // otherwise we propagate errors
-                try_nb!(a.read_to_end(buf));
+                try_nb_traced!(Site::ReadToEnd,
+                               a.read_to_end(buf),
+                               "read_to_end: would block; read={}", buf.len());
             },
             State::Empty => panic!("poll ReadToEnd after it's done"),
         }
@@ -0,0 +1,149 @@
+use std::{fmt, io};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+/// A codec layer which run-length encodes the contents of each frame of an
+/// inner codec.
+///
+/// Each frame's contents are a sequence of `[count, byte]` pairs, each
+/// standing for `count` repetitions of `byte`. Decoding expands the frames
+/// yielded by the inner codec, and encoding compresses each item into runs
+/// of at most 255 bytes before passing it to the inner codec, which is
+/// responsible for delimiting the frames on the wire.
+///
+/// A frame with an odd length or a run with a count of zero is rejected with
+/// an `InvalidData` error. So is a frame which would expand to more than the
+/// max expanded length, which is checked before any of it is expanded to
+/// guard against decompression bombs. The default max expanded length is
+/// 8MB.
+pub struct Rle<C> {
+    inner: C,
+    max_expanded_len: usize,
+}
+
+impl<C> Rle<C> {
+    /// Creates a new `Rle` layer over the frames of `inner`.
+    pub fn new(inner: C) -> Rle<C> {
+        Rle {
+            inner: inner,
+            max_expanded_len: 8 * 1_024 * 1_024,
+        }
+    }
+
+    /// Returns the current max expanded length setting.
+    pub fn max_expanded_length(&self) -> usize {
+        self.max_expanded_len
+    }
+
+    /// Updates the max expanded length setting.
+    pub fn set_max_expanded_length(&mut self, val: usize) {
+        self.max_expanded_len = val;
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `Rle`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn expand(&self, frame: &[u8]) -> io::Result<Bytes> {
+        if frame.len() % 2 != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "run-length encoded frame has an odd length"));
+        }
+
+        let mut len = 0usize;
+        for pair in frame.chunks(2) {
+            if pair[0] == 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "run-length encoded frame has an empty run"));
+            }
+            len += pair[0] as usize;
+        }
+
+        if len > self.max_expanded_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "expanded frame too large"));
+        }
+
+        let mut expanded = BytesMut::with_capacity(len);
+        for pair in frame.chunks(2) {
+            for _ in 0..pair[0] {
+                expanded.put_u8(pair[1]);
+            }
+        }
+
+        Ok(expanded.freeze())
+    }
+}
+
+impl<C> Decoder for Rle<C>
+    where C: Decoder,
+          C::Item: AsRef<[u8]>,
+{
+    type Item = Bytes;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, C::Error> {
+        match try!(self.inner.decode(src)) {
+            Some(frame) => Ok(Some(try!(self.expand(frame.as_ref())))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, C::Error> {
+        match try!(self.inner.decode_eof(src)) {
+            Some(frame) => Ok(Some(try!(self.expand(frame.as_ref())))),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<C> Encoder for Rle<C>
+    where C: Encoder<Item = Bytes>,
+{
+    type Item = Bytes;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), C::Error> {
+        let mut compressed = BytesMut::new();
+        let mut rest = &item[..];
+
+        while let Some(&byte) = rest.first() {
+            let run = rest.iter()
+                .take(255)
+                .take_while(|b| **b == byte)
+                .count();
+            compressed.reserve(2);
+            compressed.put_u8(run as u8);
+            compressed.put_u8(byte);
+            rest = &rest[run..];
+        }
+
+        self.inner.encode(compressed.freeze(), dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Rle<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Rle")
+            .field("inner", &self.inner)
+            .field("max_expanded_len", &self.max_expanded_len)
+            .finish()
+    }
+}
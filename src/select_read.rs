@@ -0,0 +1,161 @@
+use std::io::{self, Read};
+
+use either::Either;
+use AsyncRead;
+
+/// A reader which races two `AsyncRead`s, reading from whichever one has data
+/// available.
+///
+/// Created by the [`select_read`] function.
+///
+/// [`select_read`]: fn.select_read.html
+#[derive(Debug)]
+pub struct SelectRead<A, B> {
+    a: A,
+    b: B,
+    a_done: bool,
+    b_done: bool,
+    a_first: bool,
+    sticky: bool,
+    winner: Option<Side>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Side {
+    A,
+    B,
+}
+
+/// Creates a reader which reads from whichever of `a` and `b` has data
+/// available first.
+///
+/// Each read tries both readers, alternating which one is tried first, and
+/// returns the data from the first one which doesn't return a "would block"
+/// error. If both would block, so does the returned reader, with the current
+/// task registered with both of them. A reader which hits EOF is skipped from
+/// then on, and EOF is only reported once both have hit it.
+///
+/// With [`set_sticky`] enabled, the first reader to yield data wins and all
+/// later reads go to it alone, including its EOF. This suits a failover
+/// setup where a primary and a backup link carry the same data.
+///
+/// [`set_sticky`]: struct.SelectRead.html#method.set_sticky
+pub fn select_read<A, B>(a: A, b: B) -> SelectRead<A, B>
+    where A: AsyncRead,
+          B: AsyncRead,
+{
+    SelectRead {
+        a: a,
+        b: b,
+        a_done: false,
+        b_done: false,
+        a_first: true,
+        sticky: false,
+        winner: None,
+    }
+}
+
+impl<A, B> SelectRead<A, B> {
+    /// Sets whether the first reader to yield data is committed to for all
+    /// later reads.
+    ///
+    /// This is disabled by default. Enabling it after a reader has already
+    /// yielded data commits to the reader that yields data next.
+    pub fn set_sticky(&mut self, sticky: bool) {
+        self.sticky = sticky;
+
+        if !sticky {
+            self.winner = None;
+        }
+    }
+
+    /// Returns the reader that was committed to, if sticky mode is enabled
+    /// and one of them has yielded data.
+    pub fn winner(&self) -> Option<Either<&A, &B>> {
+        match self.winner {
+            Some(Side::A) => Some(Either::Left(&self.a)),
+            Some(Side::B) => Some(Either::Right(&self.b)),
+            None => None,
+        }
+    }
+
+    /// Consumes the `SelectRead`, returning both underlying readers.
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: Read, B: Read> SelectRead<A, B> {
+    fn read_side(&mut self, side: Side, buf: &mut [u8]) -> io::Result<usize> {
+        let ret = match side {
+            Side::A => self.a.read(buf),
+            Side::B => self.b.read(buf),
+        };
+
+        match ret {
+            Ok(0) if !buf.is_empty() => {
+                match side {
+                    Side::A => self.a_done = true,
+                    Side::B => self.b_done = true,
+                }
+                Ok(0)
+            }
+            Ok(n) => {
+                if self.sticky {
+                    self.winner = Some(side);
+                }
+                Ok(n)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<A: Read, B: Read> Read for SelectRead<A, B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(side) = self.winner {
+            return self.read_side(side, buf);
+        }
+
+        let order = if self.a_first {
+            [Side::A, Side::B]
+        } else {
+            [Side::B, Side::A]
+        };
+        self.a_first = !self.a_first;
+
+        let mut blocked = None;
+
+        for &side in order.iter() {
+            let done = match side {
+                Side::A => self.a_done,
+                Side::B => self.b_done,
+            };
+            if done {
+                continue;
+            }
+
+            match self.read_side(side, buf) {
+                Ok(0) if !buf.is_empty() => {}
+                Ok(n) => return Ok(n),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    blocked = Some(io::Error::new(io::ErrorKind::WouldBlock, "both readers would block"));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        match blocked {
+            Some(e) => Err(e),
+            None => Ok(0),
+        }
+    }
+}
+
+impl<A: AsyncRead, B: AsyncRead> AsyncRead for SelectRead<A, B> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        let a = self.a.prepare_uninitialized_buffer(buf);
+        let b = self.b.prepare_uninitialized_buffer(buf);
+        a || b
+    }
+}
@@ -0,0 +1,166 @@
+use std::error::Error as StdError;
+use std::{fmt, io};
+
+use bytes::{Buf, BufMut, BigEndian, Bytes, BytesMut, IntoBuf};
+
+use codec::{Decoder, Encoder};
+
+/// A codec wrapper which prefixes every frame with a `u32` sequence number
+/// and verifies that incoming frames arrive in order.
+///
+/// When decoding, the first four bytes of each frame produced by the inner
+/// decoder are read as a big endian sequence number and stripped off. If the
+/// sequence number isn't exactly one more than that of the previous frame
+/// (or the initial sequence number, for the first frame), an `io::Error` of
+/// kind `InvalidData` holding a `SequenceGap` is returned. Decoding then
+/// continues from the sequence number that was received, so a stream that
+/// yields decode errors as items, such as `Framed::with_errors`, resumes
+/// normally after reporting the gap.
+///
+/// When encoding, the next sequence number is prepended to each frame before
+/// passing it to the inner encoder.
+///
+/// Sequence numbers wrap around from `u32::MAX` to `0`.
+#[derive(Debug)]
+pub struct Sequenced<C> {
+    inner: C,
+    initial: u32,
+    read_seq: u32,
+    write_seq: u32,
+}
+
+/// An error when a frame's sequence number doesn't follow the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceGap {
+    expected: u32,
+    got: u32,
+}
+
+impl<C> Sequenced<C> {
+    /// Creates a new `Sequenced` wrapping `inner`, with sequence numbers
+    /// starting at `0`.
+    pub fn new(inner: C) -> Sequenced<C> {
+        Sequenced::with_initial_sequence(inner, 0)
+    }
+
+    /// Creates a new `Sequenced` wrapping `inner`, with sequence numbers
+    /// starting at `seq`.
+    pub fn with_initial_sequence(inner: C, seq: u32) -> Sequenced<C> {
+        Sequenced {
+            inner: inner,
+            initial: seq,
+            read_seq: seq,
+            write_seq: seq,
+        }
+    }
+
+    /// Resets both the expected and the next outgoing sequence number to the
+    /// initial sequence number.
+    pub fn reset_sequence(&mut self) {
+        self.read_seq = self.initial;
+        self.write_seq = self.initial;
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `Sequenced`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn check(&mut self, frame: Option<BytesMut>) -> io::Result<Option<BytesMut>> {
+        let mut frame = match frame {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        if frame.len() < 4 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "frame too short to hold a sequence number"));
+        }
+
+        let got = frame.split_to(4).into_buf().get_u32::<BigEndian>();
+        let expected = self.read_seq;
+        self.read_seq = got.wrapping_add(1);
+
+        if got != expected {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, SequenceGap {
+                expected: expected,
+                got: got,
+            }));
+        }
+
+        Ok(Some(frame))
+    }
+}
+
+impl<C> Decoder for Sequenced<C>
+    where C: Decoder<Item = BytesMut>,
+{
+    type Item = BytesMut;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>, C::Error> {
+        let frame = try!(self.inner.decode(src));
+        Ok(try!(self.check(frame)))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>, C::Error> {
+        let frame = try!(self.inner.decode_eof(src));
+        Ok(try!(self.check(frame)))
+    }
+}
+
+impl<C> Encoder for Sequenced<C>
+    where C: Encoder<Item = Bytes>,
+{
+    type Item = Bytes;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), C::Error> {
+        let mut frame = BytesMut::with_capacity(4 + item.len());
+        frame.put_u32::<BigEndian>(self.write_seq);
+        frame.put(item);
+
+        self.write_seq = self.write_seq.wrapping_add(1);
+        self.inner.encode(frame.freeze(), dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+// ===== impl SequenceGap =====
+
+impl SequenceGap {
+    /// Returns the sequence number that was expected.
+    pub fn expected(&self) -> u32 {
+        self.expected
+    }
+
+    /// Returns the sequence number that was received.
+    pub fn got(&self) -> u32 {
+        self.got
+    }
+}
+
+impl fmt::Display for SequenceGap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "sequence gap: expected {}, got {}", self.expected, self.got)
+    }
+}
+
+impl StdError for SequenceGap {
+    fn description(&self) -> &str {
+        "sequence gap"
+    }
+}
@@ -0,0 +1,104 @@
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures::Poll;
+use bytes::{Buf, BufMut, Bytes};
+
+use {AsyncRead, AsyncWrite};
+use bytes_queue::BytesQueue;
+
+/// A cloneable handle to an I/O object shared between tasks.
+///
+/// Unlike the halves returned by `AsyncRead::split`, every handle can both
+/// read and write. Each operation locks the shared object for just the
+/// duration of that call, so operations from different handles are
+/// serialized but may interleave freely. This is useful for protocols where
+/// any of several tasks may need to send on the same connection.
+///
+/// The lock is a `std::sync::Mutex`, which blocks the thread rather than the
+/// task while contended. It is only held for a single non-blocking I/O call,
+/// so this is normally brief, but a guard obtained from `lock` must not be
+/// held across a point where the task yields: any other handle used on the
+/// same thread in the meantime would deadlock.
+#[derive(Debug)]
+pub struct Shared<T> {
+    inner: Arc<Mutex<T>>,
+}
+
+impl<T> Shared<T> {
+    /// Creates a new `Shared` handle wrapping `inner`.
+    pub fn new(inner: T) -> Shared<T> {
+        Shared { inner: Arc::new(Mutex::new(inner)) }
+    }
+
+    /// Locks the shared object, blocking the current thread until it's
+    /// available.
+    ///
+    /// See the type level documentation for the deadlock risk of holding the
+    /// returned guard.
+    pub fn lock(&self) -> io::Result<MutexGuard<T>> {
+        self.inner.lock().map_err(|_| {
+            io::Error::new(io::ErrorKind::Other, "shared I/O object lock poisoned")
+        })
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Shared<T> {
+        Shared { inner: self.inner.clone() }
+    }
+}
+
+impl<T: Read> Read for Shared<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        try!(self.lock()).read(buf)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for Shared<T> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        match self.lock() {
+            Ok(l) => l.prepare_uninitialized_buffer(buf),
+            Err(_) => {
+                for x in buf.iter_mut() {
+                    *x = 0;
+                }
+                true
+            }
+        }
+    }
+
+    fn read_buf<B: BufMut>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        try!(self.lock()).read_buf(buf)
+    }
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        try!(self.lock()).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        try!(self.lock()).flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for Shared<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        try!(self.lock()).shutdown()
+    }
+
+    fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error>
+        where Self: Sized,
+    {
+        try!(self.lock()).write_buf(buf)
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        try!(self.lock()).write_bytes(bytes)
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        try!(self.lock()).write_buf_vectored(bufs)
+    }
+}
@@ -0,0 +1,115 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use codec::{Decoder, Encoder};
+
+use std::io;
+
+/// Frame delimiter
+pub const END: u8 = 0xC0;
+
+/// Escape byte
+pub const ESC: u8 = 0xDB;
+
+/// Escaped `END`, following an `ESC`
+pub const ESC_END: u8 = 0xDC;
+
+/// Escaped `ESC`, following an `ESC`
+pub const ESC_ESC: u8 = 0xDD;
+
+/// A `Codec` implementation for SLIP framing, as described in RFC 1055.
+///
+/// See [module level] documentation for more detail.
+///
+/// [module level]: index.html
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct SlipCodec {
+    // Index of the next byte to examine for an `END` delimiter.
+    next_index: usize,
+}
+
+impl SlipCodec {
+    /// Returns a `SlipCodec` for framing SLIP encoded data.
+    pub fn new() -> SlipCodec {
+        SlipCodec { next_index: 0 }
+    }
+}
+
+fn unescape(src: &[u8]) -> io::Result<BytesMut> {
+    let mut dst = BytesMut::with_capacity(src.len());
+    let mut bytes = src.iter();
+
+    while let Some(&b) = bytes.next() {
+        if b != ESC {
+            dst.put_u8(b);
+            continue;
+        }
+
+        match bytes.next() {
+            Some(&ESC_END) => dst.put_u8(END),
+            Some(&ESC_ESC) => dst.put_u8(ESC),
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          "invalid SLIP escape sequence"));
+            }
+        }
+    }
+
+    Ok(dst)
+}
+
+impl Decoder for SlipCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<BytesMut>, io::Error> {
+        loop {
+            let end = match buf[self.next_index..].iter().position(|b| *b == END) {
+                Some(offset) => offset + self.next_index,
+                None => {
+                    self.next_index = buf.len();
+                    return Ok(None);
+                }
+            };
+
+            // Consume the frame along with its delimiter before unescaping,
+            // so that a malformed frame is skipped and decoding resumes at
+            // the next one.
+            let frame = buf.split_to(end + 1);
+            self.next_index = 0;
+
+            // Back to back delimiters are used to flush out line noise and
+            // don't carry a frame.
+            if frame.len() == 1 {
+                continue;
+            }
+
+            return unescape(&frame[..end]).map(Some);
+        }
+    }
+}
+
+impl Encoder for SlipCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, data: Bytes, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.reserve(data.len() + 2);
+        buf.put_u8(END);
+
+        for &b in data.iter() {
+            match b {
+                END => {
+                    buf.reserve(1);
+                    buf.put_slice(&[ESC, ESC_END]);
+                }
+                ESC => {
+                    buf.reserve(1);
+                    buf.put_slice(&[ESC, ESC_ESC]);
+                }
+                b => buf.put_u8(b),
+            }
+        }
+
+        buf.put_u8(END);
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::cmp;
+use std::io;
+use std::mem;
+
+use bytes::{Bytes, BytesMut};
+use futures::{Async, Future, Poll};
+
+use AsyncRead;
+
+const INITIAL_CAPACITY: usize = 8 * 1024;
+
+/// A future which reads the entire contents of a stream into a `Bytes`.
+///
+/// Created by the [`slurp`] function.
+///
+/// [`slurp`]: fn.slurp.html
+#[derive(Debug)]
+pub struct Slurp<A> {
+    state: State<A>,
+    max_size: Option<usize>,
+}
+
+#[derive(Debug)]
+enum State<A> {
+    Reading {
+        a: A,
+        buf: BytesMut,
+        size_hint: Option<usize>,
+    },
+    Empty,
+}
+
+/// Creates a future which will read all the bytes of the I/O object `A`
+/// into memory, resolving to both the I/O object and the bytes read.
+///
+/// If `size_hint` is given, that many bytes are reserved up front, so a
+/// resource whose size is known ahead of time is read without reallocating.
+/// The buffer grows as needed either way. A hard limit on the number of bytes
+/// read can be set with `Slurp::max_size`.
+///
+/// Data is read with `AsyncRead::read_buf`, so readers which don't need their
+/// buffers zeroed avoid doing so. Interrupted reads are retried.
+///
+/// In the case of an error the bytes read so far and the object will be
+/// discarded, with the error yielded.
+pub fn slurp<A>(a: A, size_hint: Option<usize>) -> Slurp<A>
+    where A: AsyncRead,
+{
+    Slurp {
+        state: State::Reading {
+            a: a,
+            buf: BytesMut::new(),
+            size_hint: size_hint,
+        },
+        max_size: None,
+    }
+}
+
+impl<A> Slurp<A> {
+    /// Limits the number of bytes read to `max`.
+    ///
+    /// If the stream holds more than `max` bytes, the future fails with an
+    /// error of kind `InvalidData` once the excess is read. The buffer never
+    /// grows much beyond `max`, however large the size hint or the stream.
+    pub fn max_size(mut self, max: usize) -> Slurp<A> {
+        self.max_size = Some(max);
+        self
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "stream exceeds max size")
+}
+
+impl<A> Future for Slurp<A>
+    where A: AsyncRead,
+{
+    type Item = (A, Bytes);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(A, Bytes), io::Error> {
+        match self.state {
+            State::Reading { ref mut a, ref mut buf, ref mut size_hint } => {
+                // Room for one more byte than the limit allows, to tell a
+                // stream of exactly `max_size` bytes from a longer one
+                let limit = self.max_size.map(|max| max.saturating_add(1));
+
+                if let Some(hint) = size_hint.take() {
+                    // One extra byte lets the read which hits EOF go through
+                    // without growing the buffer
+                    let hint = hint.saturating_add(1);
+                    buf.reserve(limit.map_or(hint, |limit| cmp::min(hint, limit)));
+                }
+
+                loop {
+                    if buf.len() == buf.capacity() {
+                        let grow = cmp::max(buf.capacity(), INITIAL_CAPACITY);
+                        let grow = limit.map_or(grow, |limit| cmp::min(grow, limit - buf.len()));
+                        buf.reserve(grow);
+                    }
+
+                    let n = match a.read_buf(buf) {
+                        Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Ok(Async::NotReady) => {
+                            trace!("slurp: would block; read={}", buf.len());
+                            return Ok(Async::NotReady);
+                        }
+                        res => try_ready!(res),
+                    };
+
+                    if self.max_size.map_or(false, |max| buf.len() > max) {
+                        return Err(too_large());
+                    }
+
+                    if n == 0 {
+                        break;
+                    }
+                }
+            }
+            State::Empty => panic!("poll Slurp after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Reading { a, buf, .. } => Ok((a, buf.freeze()).into()),
+            State::Empty => unreachable!(),
+        }
+    }
+}
@@ -2,9 +2,10 @@ use std::io::{self, Read, Write};
 
 use futures::{Async, Poll};
 use futures::sync::BiLock;
-use bytes::{Buf, BufMut};
+use bytes::{Buf, BufMut, Bytes};
 
 use {AsyncRead, AsyncWrite};
+use bytes_queue::BytesQueue;
 
 /// The readable half of an object returned from `AsyncRead::split`.
 #[derive(Debug)]
@@ -18,7 +19,7 @@ pub struct WriteHalf<T> {
     handle: BiLock<T>,
 }
 
-pub fn split<T: AsyncRead + AsyncWrite>(t: T) -> (ReadHalf<T>, WriteHalf<T>) {
+pub fn split<T: AsyncRead + Write>(t: T) -> (ReadHalf<T>, WriteHalf<T>) {
     let (a, b) = BiLock::new(t);
     (ReadHalf { handle: a }, WriteHalf { handle: b })
 }
@@ -45,7 +46,7 @@ impl<T: AsyncRead> AsyncRead for ReadHalf<T> {
     }
 }
 
-impl<T: AsyncWrite> Write for WriteHalf<T> {
+impl<T: Write> Write for WriteHalf<T> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         match self.handle.poll_lock() {
             Async::Ready(mut l) => l.write(buf),
@@ -77,4 +78,18 @@ impl<T: AsyncWrite> AsyncWrite for WriteHalf<T> {
             Async::NotReady => Err(would_block()),
         }
     }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        match self.handle.poll_lock() {
+            Async::Ready(mut l) => l.write_bytes(bytes),
+            Async::NotReady => Err(would_block()),
+        }
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        match self.handle.poll_lock() {
+            Async::Ready(mut l) => l.write_buf_vectored(bufs),
+            Async::NotReady => Err(would_block()),
+        }
+    }
 }
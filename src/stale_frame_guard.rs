@@ -0,0 +1,128 @@
+use std::{fmt, io};
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which times out partial frames based on a generation
+/// counter driven by the caller.
+///
+/// The guard doesn't depend on any timer. Instead the caller calls
+/// `advance_generation` from their own timer, at whatever granularity they
+/// like. When the inner decoder leaves a partial frame in the buffer, the
+/// current generation is recorded. If that frame still hasn't completed once
+/// more than `max_generations` generations have passed, the next call to
+/// `decode` returns an error of kind `TimedOut`.
+///
+/// The recorded generation is cleared whenever a full frame is decoded or the
+/// buffer is empty.
+///
+/// Encoding is passed through to the inner codec unchanged.
+pub struct StaleFrameGuard<C> {
+    inner: C,
+    max_generations: u64,
+    generation: u64,
+    pending_since: Option<u64>,
+}
+
+impl<C> StaleFrameGuard<C> {
+    /// Creates a new `StaleFrameGuard` which fails a partial frame once it has
+    /// been pending for more than `max_generations` generations.
+    pub fn new(inner: C, max_generations: u64) -> StaleFrameGuard<C> {
+        StaleFrameGuard {
+            inner: inner,
+            max_generations: max_generations,
+            generation: 0,
+            pending_since: None,
+        }
+    }
+
+    /// Advances the current generation by one.
+    ///
+    /// This is expected to be called periodically by the owner of the codec,
+    /// for example from a timer.
+    pub fn advance_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Returns the current generation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `StaleFrameGuard`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn check(&mut self, decoded: bool, src: &BytesMut) -> io::Result<()> {
+        if decoded || src.is_empty() {
+            self.pending_since = None;
+            return Ok(());
+        }
+
+        let since = *self.pending_since.get_or_insert(self.generation);
+
+        if self.generation.wrapping_sub(since) > self.max_generations {
+            self.pending_since = None;
+            return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                      "partial frame pending for too many generations"));
+        }
+
+        Ok(())
+    }
+}
+
+impl<C> Decoder for StaleFrameGuard<C>
+    where C: Decoder,
+          C::Error: From<io::Error>,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let ret = try!(self.inner.decode(src));
+        try!(self.check(ret.is_some(), src));
+        Ok(ret)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let ret = try!(self.inner.decode_eof(src));
+        try!(self.check(ret.is_some(), src));
+        Ok(ret)
+    }
+}
+
+impl<C: Encoder> Encoder for StaleFrameGuard<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for StaleFrameGuard<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StaleFrameGuard")
+            .field("inner", &self.inner)
+            .field("max_generations", &self.max_generations)
+            .field("generation", &self.generation)
+            .field("pending_since", &self.pending_since)
+            .finish()
+    }
+}
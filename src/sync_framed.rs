@@ -0,0 +1,251 @@
+use std::{io, mem};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+// Escaped bytes are sent XORed with this value after the escape byte
+const ESCAPE_XOR: u8 = 0x20;
+
+/// A codec for frames that start with a sync word and carry a byte-stuffed
+/// length and payload.
+///
+/// Each frame on the wire is the sync word followed by a big-endian length
+/// field and then the payload, where the length field and payload are
+/// byte-stuffed: every occurrence of the escape byte or of the first byte of
+/// the sync word is replaced by the escape byte followed by the original byte
+/// XORed with `0x20`. The first byte of the sync word therefore never appears
+/// inside a frame, so the sync word marks frame boundaries unambiguously and
+/// the decoder can always find the start of the next frame.
+///
+/// Bytes before a sync word are discarded as line noise. If a frame is
+/// malformed, because of an invalid escape sequence, a length exceeding the
+/// max frame length, or a sync word starting before the frame is complete,
+/// an `InvalidData` error is returned and the bad frame is discarded, so the
+/// next call to `decode` resynchronizes on the following sync word.
+///
+/// The length field defaults to 2 bytes, and the max frame length to 8MB.
+#[derive(Debug)]
+pub struct SyncFramed {
+    sync: Vec<u8>,
+    escape: u8,
+    length_field_len: usize,
+    max_frame_len: usize,
+    // The frame at the start of the buffer, as far as it has been scanned
+    partial: Option<Partial>,
+}
+
+// Progress through a frame, kept between calls so that a frame arriving in
+// pieces is only scanned and unstuffed once.
+#[derive(Debug)]
+struct Partial {
+    // Position of the next byte to scan, counted from the sync word
+    pos: usize,
+    head: u64,
+    head_read: usize,
+    len: Option<usize>,
+    payload: BytesMut,
+}
+
+enum Scan {
+    // The frame isn't complete yet
+    Incomplete,
+    // A frame was decoded from the first `usize` bytes of the buffer
+    Frame(usize, BytesMut),
+    // The first `usize` bytes of the buffer hold a malformed frame
+    Invalid(usize, &'static str),
+}
+
+impl SyncFramed {
+    /// Creates a new `SyncFramed` codec using the given sync word and escape
+    /// byte.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `sync` is empty, or if the escape byte or the
+    /// escaped form of the escape byte is the first byte of the sync word.
+    pub fn new(sync: &[u8], escape: u8) -> SyncFramed {
+        assert!(!sync.is_empty(), "sync word must not be empty");
+        assert!(escape != sync[0] && escape ^ ESCAPE_XOR != sync[0],
+                "escape byte conflicts with the sync word");
+
+        SyncFramed {
+            sync: sync.to_vec(),
+            escape: escape,
+            length_field_len: 2,
+            max_frame_len: 8 * 1_024 * 1_024,
+            partial: None,
+        }
+    }
+
+    /// Returns the number of bytes in the length field.
+    pub fn length_field_length(&self) -> usize {
+        self.length_field_len
+    }
+
+    /// Sets the number of bytes in the length field.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `val` is not between 1 and 8.
+    pub fn set_length_field_length(&mut self, val: usize) {
+        assert!(val > 0 && val <= 8, "invalid length field length");
+        self.length_field_len = val;
+    }
+
+    /// Returns the current max frame setting.
+    ///
+    /// This is the largest payload this codec will decode or encode.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the max frame setting.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    // Discards any bytes before the first possible sync word, returning
+    // whether a complete sync word is now at the start of the buffer.
+    fn find_sync(&self, buf: &mut BytesMut) -> bool {
+        let sync = &self.sync[..];
+        let start = (0..buf.len())
+            .find(|&i| {
+                let n = ::std::cmp::min(sync.len(), buf.len() - i);
+                buf[i..i + n] == sync[..n]
+            })
+            .unwrap_or(buf.len());
+
+        if start > 0 {
+            trace!("discarding {} bytes before sync word", start);
+            let _ = buf.split_to(start);
+        }
+
+        buf.len() >= sync.len()
+    }
+
+    // Unstuffs the frame starting with the sync word at the start of `buf`,
+    // resuming where the last call left off.
+    fn scan(&mut self, buf: &[u8]) -> Scan {
+        let sync_len = self.sync.len();
+        let p = self.partial.get_or_insert_with(|| {
+            Partial {
+                pos: sync_len,
+                head: 0,
+                head_read: 0,
+                len: None,
+                payload: BytesMut::new(),
+            }
+        });
+
+        loop {
+            if p.len == Some(p.payload.len()) {
+                return Scan::Frame(p.pos, mem::replace(&mut p.payload, BytesMut::new()));
+            }
+
+            let b = match buf.get(p.pos) {
+                Some(&b) => b,
+                None => return Scan::Incomplete,
+            };
+
+            let b = if b == self.sync[0] {
+                // Leave the new sync word in place to resynchronize on it
+                return Scan::Invalid(p.pos, "sync word inside a frame");
+            } else if b == self.escape {
+                match buf.get(p.pos + 1) {
+                    Some(&e) => {
+                        let b = e ^ ESCAPE_XOR;
+                        if b != self.escape && b != self.sync[0] {
+                            return Scan::Invalid(p.pos + 2, "invalid escape sequence");
+                        }
+                        p.pos += 2;
+                        b
+                    }
+                    None => return Scan::Incomplete,
+                }
+            } else {
+                p.pos += 1;
+                b
+            };
+
+            if p.head_read < self.length_field_len {
+                p.head = (p.head << 8) | b as u64;
+                p.head_read += 1;
+
+                if p.head_read == self.length_field_len {
+                    if p.head > self.max_frame_len as u64 {
+                        return Scan::Invalid(p.pos, "frame too large");
+                    }
+                    p.len = Some(p.head as usize);
+                    p.payload.reserve(p.head as usize);
+                }
+            } else {
+                p.payload.put_u8(b);
+            }
+        }
+    }
+
+    fn stuff(&self, b: u8, dst: &mut BytesMut) {
+        if b == self.escape || b == self.sync[0] {
+            dst.reserve(2);
+            dst.put_u8(self.escape);
+            dst.put_u8(b ^ ESCAPE_XOR);
+        } else {
+            dst.reserve(1);
+            dst.put_u8(b);
+        }
+    }
+}
+
+impl Decoder for SyncFramed {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        if self.partial.is_none() && !self.find_sync(buf) {
+            return Ok(None);
+        }
+
+        match self.scan(buf) {
+            Scan::Incomplete => Ok(None),
+            Scan::Frame(n, payload) => {
+                self.partial = None;
+                let _ = buf.split_to(n);
+                Ok(Some(payload))
+            }
+            Scan::Invalid(n, msg) => {
+                self.partial = None;
+                let _ = buf.split_to(n);
+                Err(io::Error::new(io::ErrorKind::InvalidData, msg))
+            }
+        }
+    }
+}
+
+impl Encoder for SyncFramed {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, data: Bytes, dst: &mut BytesMut) -> io::Result<()> {
+        let too_long = self.length_field_len < 8 &&
+            data.len() as u64 >= 1 << (8 * self.length_field_len);
+
+        if data.len() > self.max_frame_len || too_long {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame too large"));
+        }
+
+        dst.reserve(self.sync.len() + self.length_field_len + data.len());
+        dst.put_slice(&self.sync);
+
+        for i in (0..self.length_field_len).rev() {
+            let b = (data.len() as u64 >> (8 * i)) as u8;
+            self.stuff(b, dst);
+        }
+
+        for &b in data.iter() {
+            self.stuff(b, dst);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use codec::{Decoder, Encoder};
+
+use std::{io, str};
+
+/// A frame of a text command protocol.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Frame {
+    /// The command, taken from the first line of the frame.
+    pub command: String,
+    /// The headers of the frame, in the order they appear.
+    pub headers: Vec<(String, String)>,
+    /// The body of the frame, following the empty line after the headers.
+    pub body: Bytes,
+}
+
+/// A `Codec` implementation for STOMP-like text command frames.
+///
+/// See [module level] documentation for more detail.
+///
+/// [module level]: index.html
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TextCommandCodec {
+    max_frame_len: usize,
+    max_headers: usize,
+    // Index of the next byte to examine for a `\0` terminator.
+    next_index: usize,
+    // Whether the rest of an oversized frame is being skipped.
+    discarding: bool,
+}
+
+impl TextCommandCodec {
+    /// Returns a `TextCommandCodec` with the default limits.
+    pub fn new() -> TextCommandCodec {
+        TextCommandCodec {
+            max_frame_len: 8 * 1_024 * 1_024,
+            max_headers: 100,
+            next_index: 0,
+            discarding: false,
+        }
+    }
+
+    /// Returns the maximum size of a frame, including its terminator.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// Updates the maximum size of a frame, including its terminator.
+    pub fn set_max_frame_length(&mut self, val: usize) {
+        self.max_frame_len = val;
+    }
+
+    /// Returns the maximum number of headers in a frame.
+    pub fn max_headers(&self) -> usize {
+        self.max_headers
+    }
+
+    /// Updates the maximum number of headers in a frame.
+    pub fn set_max_headers(&mut self, val: usize) {
+        self.max_headers = val;
+    }
+
+    fn parse(&self, mut frame: BytesMut) -> io::Result<Frame> {
+        let mut headers = Vec::new();
+        let mut command = None;
+        let mut pos = 0;
+
+        loop {
+            let newline = match frame[pos..].iter().position(|b| *b == b'\n') {
+                Some(offset) => offset + pos,
+                None => return Err(invalid("frame headers not terminated by an empty line")),
+            };
+
+            let mut line = &frame[pos..newline];
+            if let Some(&b'\r') = line.last() {
+                line = &line[..line.len() - 1];
+            }
+            pos = newline + 1;
+
+            if line.is_empty() {
+                break;
+            }
+
+            let line = match str::from_utf8(line) {
+                Ok(line) => line,
+                Err(_) => return Err(invalid("frame line is not valid UTF-8")),
+            };
+
+            if command.is_none() {
+                command = Some(line.to_string());
+                continue;
+            }
+
+            if headers.len() >= self.max_headers {
+                return Err(invalid("too many headers"));
+            }
+
+            let colon = match line.find(':') {
+                Some(colon) => colon,
+                None => return Err(invalid("header line has no colon")),
+            };
+
+            headers.push((line[..colon].to_string(), line[colon + 1..].to_string()));
+        }
+
+        let command = match command {
+            Some(command) => command,
+            None => return Err(invalid("frame has no command")),
+        };
+
+        Ok(Frame {
+            command: command,
+            headers: headers,
+            body: frame.split_off(pos).freeze(),
+        })
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// Number of end of line bytes before the next frame. These are sent between
+// frames as heartbeats.
+fn leading_eols(buf: &[u8]) -> usize {
+    buf.iter().take_while(|b| **b == b'\n' || **b == b'\r').count()
+}
+
+impl Decoder for TextCommandCodec {
+    type Item = Frame;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Frame>> {
+        loop {
+            if !self.discarding && self.next_index == 0 {
+                let n = leading_eols(buf);
+                let _ = buf.split_to(n);
+            }
+
+            let end = match buf[self.next_index..].iter().position(|b| *b == 0) {
+                Some(offset) => offset + self.next_index,
+                None => {
+                    if self.discarding {
+                        buf.clear();
+                        self.next_index = 0;
+                    } else if buf.len() >= self.max_frame_len {
+                        // Skip the rest of the frame, up to its terminator
+                        buf.clear();
+                        self.next_index = 0;
+                        self.discarding = true;
+                        return Err(invalid("frame too big"));
+                    } else {
+                        self.next_index = buf.len();
+                    }
+                    return Ok(None);
+                }
+            };
+
+            // Consume the frame along with its terminator before parsing it,
+            // so that a malformed frame is skipped and decoding resumes at the
+            // next one.
+            let mut frame = buf.split_to(end + 1);
+            self.next_index = 0;
+
+            if self.discarding {
+                self.discarding = false;
+                continue;
+            }
+
+            if frame.len() > self.max_frame_len {
+                return Err(invalid("frame too big"));
+            }
+
+            frame.truncate(end);
+            return self.parse(frame).map(Some);
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<Frame>> {
+        match try!(self.decode(buf)) {
+            Some(frame) => Ok(Some(frame)),
+            None => {
+                if buf.len() == leading_eols(buf) {
+                    buf.clear();
+                    Ok(None)
+                } else {
+                    Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                       "frame not terminated by a NUL byte"))
+                }
+            }
+        }
+    }
+}
+
+impl Encoder for TextCommandCodec {
+    type Item = Frame;
+    type Error = io::Error;
+
+    fn encode(&mut self, frame: Frame, buf: &mut BytesMut) -> io::Result<()> {
+        let breaks_line = |s: &str| s.contains(|c| c == '\r' || c == '\n' || c == '\0');
+
+        if frame.command.is_empty() || breaks_line(&frame.command) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "command can't be encoded as a single line"));
+        }
+
+        for &(ref key, ref value) in &frame.headers {
+            if key.is_empty() || key.contains(':') || breaks_line(key) || breaks_line(value) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "header can't be encoded as a single line"));
+            }
+        }
+
+        if frame.body.contains(&0) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "frame body contains a NUL byte"));
+        }
+
+        let len = frame.command.len() + 1 +
+            frame.headers.iter().map(|&(ref k, ref v)| k.len() + v.len() + 2).sum::<usize>() +
+            1 + frame.body.len() + 1;
+        buf.reserve(len);
+
+        buf.put(&frame.command[..]);
+        buf.put_u8(b'\n');
+
+        for (key, value) in frame.headers {
+            buf.put(key);
+            buf.put_u8(b':');
+            buf.put(value);
+            buf.put_u8(b'\n');
+        }
+
+        buf.put_u8(b'\n');
+        buf.put(frame.body);
+        buf.put_u8(0);
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::cmp;
+use std::io::{self, Read};
+
+use AsyncRead;
+
+const DEFAULT_MAX_ROLLBACK_LEN: usize = 64 * 1_024;
+
+/// An `AsyncRead` adapter which can roll back reads to a marked position.
+///
+/// After `begin` is called, every byte read is also saved, up to a limit.
+/// `rollback` then makes the saved bytes available to be read again, before
+/// any further data from the underlying reader, while `commit` discards them.
+/// Either one ends the transaction. This allows a parser to backtrack after
+/// a failed speculative parse.
+///
+/// Reads outside of a transaction aren't saved. Once a transaction has saved
+/// the maximum rollback length, which is 64KB by default, further reads
+/// within it fail with an error of kind `Other` rather than losing the
+/// ability to roll back.
+#[derive(Debug)]
+pub struct Transactional<R> {
+    inner: R,
+    // Bytes read since `begin`, if a transaction is active
+    saved: Vec<u8>,
+    active: bool,
+    max_rollback_len: usize,
+    // Rolled back bytes which are read before the underlying reader
+    replay: Vec<u8>,
+    replay_pos: usize,
+}
+
+impl<R> Transactional<R> {
+    /// Creates a new `Transactional` reading from `inner`.
+    pub fn new(inner: R) -> Transactional<R> {
+        Transactional {
+            inner: inner,
+            saved: Vec::new(),
+            active: false,
+            max_rollback_len: DEFAULT_MAX_ROLLBACK_LEN,
+            replay: Vec::new(),
+            replay_pos: 0,
+        }
+    }
+
+    /// Starts a transaction at the current position.
+    ///
+    /// If a transaction is already active, it is committed first.
+    pub fn begin(&mut self) {
+        self.saved.clear();
+        self.active = true;
+    }
+
+    /// Ends the current transaction, keeping everything read since `begin`
+    /// as read.
+    pub fn commit(&mut self) {
+        self.saved.clear();
+        self.active = false;
+    }
+
+    /// Ends the current transaction, so that everything read since `begin`
+    /// is read again by the following reads.
+    ///
+    /// This does nothing if no transaction is active.
+    pub fn rollback(&mut self) {
+        if !self.active {
+            return;
+        }
+
+        self.saved.extend_from_slice(&self.replay[self.replay_pos..]);
+        self.replay = ::std::mem::replace(&mut self.saved, Vec::new());
+        self.replay_pos = 0;
+        self.active = false;
+    }
+
+    /// Returns whether a transaction is active.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the maximum number of bytes a transaction may read.
+    pub fn max_rollback_len(&self) -> usize {
+        self.max_rollback_len
+    }
+
+    /// Updates the maximum number of bytes a transaction may read.
+    pub fn set_max_rollback_len(&mut self, val: usize) {
+        self.max_rollback_len = val;
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Note that bytes read through this reference bypass the transaction,
+    /// and any rolled back bytes.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `Transactional`, returning the underlying reader.
+    ///
+    /// Any rolled back bytes which haven't been read again are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Transactional<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut len = buf.len();
+
+        if self.active && len > 0 {
+            len = cmp::min(len, self.max_rollback_len.saturating_sub(self.saved.len()));
+
+            if len == 0 {
+                return Err(io::Error::new(io::ErrorKind::Other,
+                                          "transaction read more than the max rollback length"));
+            }
+        }
+
+        let buf = &mut buf[..len];
+
+        let n = if self.replay_pos < self.replay.len() {
+            let n = cmp::min(len, self.replay.len() - self.replay_pos);
+            buf[..n].copy_from_slice(&self.replay[self.replay_pos..self.replay_pos + n]);
+            self.replay_pos += n;
+
+            if self.replay_pos == self.replay.len() {
+                self.replay.clear();
+                self.replay_pos = 0;
+            }
+
+            n
+        } else {
+            try!(self.inner.read(buf))
+        };
+
+        if self.active {
+            self.saved.extend_from_slice(&buf[..n]);
+        }
+
+        Ok(n)
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for Transactional<R> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A codec wrapper which converts the items decoded by the inner codec, and
+/// the items given to it for encoding.
+///
+/// Each decoded frame is passed through `decode_map`, and each item to encode
+/// is passed through `encode_map` before being handed to the inner encoder.
+/// Errors are passed through unchanged.
+///
+/// This is typically created through `Framed::transform`, which adapts a
+/// byte level codec to domain types in both directions at once.
+pub struct Transform<C, FD, FE, O> {
+    inner: C,
+    decode_map: FD,
+    encode_map: FE,
+    _encode_item: PhantomData<fn(O)>,
+}
+
+impl<C, FD, FE, O> Transform<C, FD, FE, O> {
+    /// Creates a new `Transform` converting the items of `inner` with
+    /// `decode_map` and `encode_map`.
+    pub fn new(inner: C, decode_map: FD, encode_map: FE) -> Transform<C, FD, FE, O> {
+        Transform {
+            inner: inner,
+            decode_map: decode_map,
+            encode_map: encode_map,
+            _encode_item: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `Transform`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, FD, FE, O, I> Decoder for Transform<C, FD, FE, O>
+    where C: Decoder,
+          FD: FnMut(C::Item) -> I,
+{
+    type Item = I;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<I>, C::Error> {
+        Ok(try!(self.inner.decode(src)).map(&mut self.decode_map))
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<I>, C::Error> {
+        Ok(try!(self.inner.decode_eof(src)).map(&mut self.decode_map))
+    }
+
+    fn has_frame(&self, src: &BytesMut) -> bool {
+        self.inner.has_frame(src)
+    }
+}
+
+impl<C, FD, FE, O> Encoder for Transform<C, FD, FE, O>
+    where C: Encoder,
+          FE: FnMut(O) -> C::Item,
+{
+    type Item = O;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: O, dst: &mut BytesMut) -> Result<(), C::Error> {
+        let item = (self.encode_map)(item);
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug, FD, FE, O> fmt::Debug for Transform<C, FD, FE, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Transform")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
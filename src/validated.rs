@@ -0,0 +1,107 @@
+use std::fmt;
+
+use bytes::BytesMut;
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which runs a validator on every decoded frame.
+///
+/// The validator is given each frame decoded by the inner codec before it's
+/// returned, and decides whether it's acceptable, such as by checking size
+/// constraints or invariants between fields. If it returns an error, the
+/// frame is dropped and the error, converted into the codec's error type,
+/// is returned in its place. The frame has already been consumed from the
+/// buffer by then, so decoding resumes with the following frame.
+///
+/// Encoding is passed through to the inner codec unchanged.
+pub struct Validated<C, F> {
+    inner: C,
+    validator: F,
+}
+
+impl<C, F> Validated<C, F> {
+    /// Creates a new `Validated` checking each frame decoded by `inner` with
+    /// `validator`.
+    pub fn new(inner: C, validator: F) -> Validated<C, F> {
+        Validated {
+            inner: inner,
+            validator: validator,
+        }
+    }
+
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `Validated`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C, F, E> Validated<C, F>
+    where C: Decoder,
+          F: FnMut(&C::Item) -> Result<(), E>,
+          E: Into<C::Error>,
+{
+    fn check(&mut self, frame: Option<C::Item>) -> Result<Option<C::Item>, C::Error> {
+        if let Some(ref frame) = frame {
+            if let Err(e) = (self.validator)(frame) {
+                trace!("frame failed validation");
+                return Err(e.into());
+            }
+        }
+
+        Ok(frame)
+    }
+}
+
+impl<C, F, E> Decoder for Validated<C, F>
+    where C: Decoder,
+          F: FnMut(&C::Item) -> Result<(), E>,
+          E: Into<C::Error>,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let frame = try!(self.inner.decode(src));
+        self.check(frame)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<C::Item>, C::Error> {
+        let frame = try!(self.inner.decode_eof(src));
+        self.check(frame)
+    }
+
+    fn has_frame(&self, src: &BytesMut) -> bool {
+        self.inner.has_frame(src)
+    }
+}
+
+impl<C: Encoder, F> Encoder for Validated<C, F> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug, F> fmt::Debug for Validated<C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Validated")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
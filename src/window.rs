@@ -1,5 +1,7 @@
 use std::ops;
 
+use bytes::Bytes;
+
 /// A owned window around an underlying buffer.
 ///
 /// Normally slices work great for considering sub-portions of a buffer, but
@@ -104,6 +106,27 @@ impl<T: AsRef<[u8]>> Window<T> {
     // etc.
 }
 
+impl<T: AsRef<[u8]> + Into<Bytes>> Window<T> {
+    /// Consumes this `Window`, returning the windowed range of the underlying
+    /// buffer as `Bytes`.
+    ///
+    /// This only avoids copying if converting `T` into `Bytes` does, as is
+    /// the case for `Bytes` and `BytesMut`.
+    pub fn into_bytes(self) -> Bytes {
+        self.inner.into().slice(self.range.start, self.range.end)
+    }
+}
+
+impl Window<Bytes> {
+    /// Returns the windowed range of the underlying buffer as `Bytes`.
+    ///
+    /// This is a cheap reference counted slice, so it's only available for
+    /// windows around `Bytes`; use `into_bytes` for other buffers.
+    pub fn as_bytes(&self) -> Bytes {
+        self.inner.slice(self.range.start, self.range.end)
+    }
+}
+
 impl<T: AsRef<[u8]>> AsRef<[u8]> for Window<T> {
     fn as_ref(&self) -> &[u8] {
         &self.inner.as_ref()[self.range.start..self.range.end]
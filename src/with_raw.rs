@@ -0,0 +1,132 @@
+use std::fmt;
+
+use bytes::{Bytes, BytesMut};
+
+use codec::{Decoder, Encoder};
+
+/// A `Decoder` wrapper which yields each decoded frame along with the raw
+/// bytes it was decoded from.
+///
+/// The raw bytes are the prefix of the read buffer consumed by the inner
+/// decoder while producing the frame, which assumes that the inner decoder
+/// consumes frames from the front of the buffer, as decoders normally do.
+/// The decoder can't hand back bytes it has already consumed, so `WithRaw`
+/// keeps its own copy of the bytes of the frame being decoded, appending newly
+/// buffered bytes to it before each call to the inner decoder. Bytes consumed
+/// by a call which doesn't yield a frame, such as a header stripped while
+/// waiting for the payload, are kept as the start of that frame's raw bytes.
+/// Each byte is copied once, and raw frames are then split off the copy
+/// without copying again, at the cost of holding the undecoded data twice.
+///
+/// This is typically created through `Framed::with_raw`. Encoding is passed
+/// through to the inner codec unchanged.
+pub struct WithRaw<C> {
+    inner: C,
+    // A copy of the bytes of the frame being decoded: the `consumed` bytes
+    // already taken out of the read buffer, followed by the undecoded bytes
+    // at its front
+    raw: BytesMut,
+    consumed: usize,
+}
+
+impl<C: Decoder> WithRaw<C> {
+    /// Creates a new `WithRaw` wrapping `inner`.
+    pub fn new(inner: C) -> WithRaw<C> {
+        WithRaw {
+            inner: inner,
+            raw: BytesMut::new(),
+            consumed: 0,
+        }
+    }
+}
+
+impl<C> WithRaw<C> {
+    /// Returns a reference to the underlying codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying codec.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Consumes the `WithRaw`, returning the underlying codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Decoder> Decoder for WithRaw<C> {
+    type Item = (C::Item, Bytes);
+    type Error = C::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, C::Error> {
+        self.sync(src);
+        let res = self.inner.decode(src);
+        self.split(res, src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, C::Error> {
+        self.sync(src);
+        let res = self.inner.decode_eof(src);
+        self.split(res, src)
+    }
+
+    fn has_frame(&self, src: &BytesMut) -> bool {
+        self.inner.has_frame(src)
+    }
+}
+
+impl<C> WithRaw<C> {
+    // Appends the bytes buffered since the last call to the copy.
+    fn sync(&mut self, src: &BytesMut) {
+        if self.raw.len() - self.consumed > src.len() {
+            // Only possible if the buffer was consumed or replaced behind our
+            // back, so start over
+            self.raw.clear();
+            self.consumed = 0;
+        }
+
+        let n = self.raw.len() - self.consumed;
+        self.raw.extend_from_slice(&src[n..]);
+    }
+
+    // Records the bytes consumed by a call to the inner decoder, whatever its
+    // result, and splits them off as the raw bytes of a decoded frame.
+    fn split<T, E>(&mut self, res: Result<Option<T>, E>, src: &BytesMut)
+                   -> Result<Option<(T, Bytes)>, E>
+    {
+        self.consumed = self.raw.len().saturating_sub(src.len());
+
+        match try!(res) {
+            Some(item) => {
+                let raw = self.raw.split_to(self.consumed).freeze();
+                self.consumed = 0;
+                Ok(Some((item, raw)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<C: Encoder> Encoder for WithRaw<C> {
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn encode(&mut self, item: C::Item, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode(item, dst)
+    }
+
+    fn encode_eof(&mut self, dst: &mut BytesMut) -> Result<(), C::Error> {
+        self.inner.encode_eof(dst)
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for WithRaw<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WithRaw")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
@@ -4,6 +4,7 @@ use std::mem;
 use futures::{Poll, Future};
 
 use AsyncWrite;
+use instrument::{self, Site};
 
 /// A future used to write the entire contents of some data to a stream.
 ///
@@ -64,11 +65,16 @@ impl<A, T> Future for WriteAll<A, T>
     type Error = io::Error;
 
     fn poll(&mut self) -> Poll<(A, T), io::Error> {
+        instrument::poll(Site::WriteAll);
+
         match self.state {
             State::Writing { ref mut a, ref buf, ref mut pos } => {
                 let buf = buf.as_ref();
                 while *pos < buf.len() {
-                    let n = try_nb!(a.write(&buf[*pos..]));
+                    let n = try_nb_traced!(Site::WriteAll,
+                                           ::retry_interrupted(|| a.write(&buf[*pos..])),
+                                           "write_all: would block; pos={} len={}",
+                                           *pos, buf.len());
                     *pos += n;
                     if n == 0 {
                         return Err(zero_write())
@@ -0,0 +1,149 @@
+use std::error::Error as StdError;
+use std::{fmt, io};
+use std::mem;
+
+use bytes::Buf;
+use futures::{Async, Future, Poll};
+
+use AsyncWrite;
+
+/// A future used to write the entire contents of a `Buf` to a stream.
+///
+/// This is created by the [`write_all_buf`] top-level method.
+///
+/// [`write_all_buf`]: fn.write_all_buf.html
+#[derive(Debug)]
+pub struct WriteAllBuf<A, B> {
+    state: State<A, B>,
+}
+
+#[derive(Debug)]
+enum State<A, B> {
+    Writing {
+        a: A,
+        buf: B,
+        written: usize,
+    },
+    Empty,
+}
+
+/// The error returned by a `WriteAllBuf` future.
+///
+/// Along with the underlying I/O error this records how many bytes of the
+/// `Buf` were successfully written before the error happened, which makes it
+/// possible to construct a new `Buf` containing only the unwritten remainder
+/// when retrying.
+#[derive(Debug)]
+pub struct WriteAllBufError {
+    error: io::Error,
+    bytes_written: usize,
+}
+
+/// Creates a future that will write the entire contents of the `Buf` `buf` to
+/// the stream `a` provided.
+///
+/// The returned future will not return until all the data has been written,
+/// and the future will resolve to the stream as well as the (now empty)
+/// buffer.
+///
+/// Any error which happens during writing will cause both the stream and the
+/// buffer to get destroyed. The returned `WriteAllBufError` reports how many
+/// bytes were written before the error so the write can be resumed.
+///
+/// Unlike `write_all`, `buf` may be made up of multiple segments, for example
+/// a `Chain` of two buffers.
+pub fn write_all_buf<A, B>(a: A, buf: B) -> WriteAllBuf<A, B>
+    where A: AsyncWrite,
+          B: Buf,
+{
+    WriteAllBuf {
+        state: State::Writing {
+            a: a,
+            buf: buf,
+            written: 0,
+        },
+    }
+}
+
+impl<A, B> Future for WriteAllBuf<A, B>
+    where A: AsyncWrite,
+          B: Buf,
+{
+    type Item = (A, B);
+    type Error = WriteAllBufError;
+
+    fn poll(&mut self) -> Poll<(A, B), WriteAllBufError> {
+        match self.state {
+            State::Writing { ref mut a, ref mut buf, ref mut written } => {
+                while buf.has_remaining() {
+                    let res = match a.write_buf(buf) {
+                        Ok(Async::Ready(0)) => {
+                            Err(io::Error::new(io::ErrorKind::WriteZero, "zero-length write"))
+                        }
+                        Ok(Async::Ready(n)) => Ok(n),
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                        Err(e) => Err(e),
+                    };
+
+                    match res {
+                        Ok(n) => *written += n,
+                        Err(e) => {
+                            return Err(WriteAllBufError {
+                                error: e,
+                                bytes_written: *written,
+                            })
+                        }
+                    }
+                }
+            }
+            State::Empty => panic!("poll a WriteAllBuf after it's done"),
+        }
+
+        match mem::replace(&mut self.state, State::Empty) {
+            State::Writing { a, buf, .. } => Ok((a, buf).into()),
+            State::Empty => panic!(),
+        }
+    }
+}
+
+// ===== impl WriteAllBufError =====
+
+impl WriteAllBufError {
+    /// Returns the number of bytes of the buffer that were written before the
+    /// error happened.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Returns a reference to the underlying I/O error.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Consumes the `WriteAllBufError`, returning the underlying I/O error.
+    pub fn into_inner(self) -> io::Error {
+        self.error
+    }
+}
+
+impl From<WriteAllBufError> for io::Error {
+    fn from(src: WriteAllBufError) -> io::Error {
+        src.error
+    }
+}
+
+impl fmt::Display for WriteAllBufError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (after writing {} bytes)", self.error, self.bytes_written)
+    }
+}
+
+impl StdError for WriteAllBufError {
+    fn description(&self) -> &str {
+        self.error.description()
+    }
+
+    fn cause(&self) -> Option<&StdError> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,92 @@
+use std::io;
+
+use bytes::Bytes;
+use futures::{Async, Future, Poll, Stream};
+
+use AsyncWrite;
+use instrument::{self, Site};
+
+/// A future which writes every chunk yielded by a stream to an I/O object.
+///
+/// Created by the [`write_stream`] function.
+///
+/// [`write_stream`]: fn.write_stream.html
+#[derive(Debug)]
+pub struct WriteStream<W, S> {
+    writer: Option<W>,
+    stream: S,
+    chunk: Option<Bytes>,
+    amt: u64,
+    done: bool,
+}
+
+/// Creates a future which writes all chunks of `stream` to `w`, flushing once
+/// the stream has ended.
+///
+/// Each chunk is written out in full before the next one is pulled from the
+/// stream. The returned future resolves to the writer along with the total
+/// number of bytes written once the last chunk has been written and the
+/// writer flushed.
+///
+/// Errors from either the stream or the writer cause the future to resolve to
+/// that error, discarding the writer and the stream.
+pub fn write_stream<W, S>(w: W, stream: S) -> WriteStream<W, S>
+    where W: AsyncWrite,
+          S: Stream<Item = Bytes, Error = io::Error>,
+{
+    WriteStream {
+        writer: Some(w),
+        stream: stream,
+        chunk: None,
+        amt: 0,
+        done: false,
+    }
+}
+
+impl<W, S> Future for WriteStream<W, S>
+    where W: AsyncWrite,
+          S: Stream<Item = Bytes, Error = io::Error>,
+{
+    type Item = (W, u64);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<(W, u64), io::Error> {
+        instrument::poll(Site::WriteStream);
+
+        loop {
+            // Finish writing out the current chunk, if any.
+            if let Some(ref mut chunk) = self.chunk {
+                let writer = self.writer.as_mut().expect("poll a WriteStream after it's done");
+
+                while !chunk.is_empty() {
+                    let n = try_nb_traced!(Site::WriteStream,
+                                           writer.write(chunk),
+                                           "write_stream: would block; amt={}", self.amt);
+                    if n == 0 {
+                        return Err(io::Error::new(io::ErrorKind::WriteZero,
+                                                  "write zero byte into writer"));
+                    }
+                    chunk.advance(n);
+                    self.amt += n as u64;
+                }
+            }
+            self.chunk = None;
+
+            if !self.done {
+                match try_ready!(self.stream.poll()) {
+                    Some(chunk) => self.chunk = Some(chunk),
+                    None => self.done = true,
+                }
+                continue;
+            }
+
+            // The stream has ended and everything has been written, so flush
+            // and finish.
+            try_nb_traced!(Site::WriteStream,
+                           self.writer.as_mut().expect("poll a WriteStream after it's done").flush(),
+                           "write_stream: flush would block; amt={}", self.amt);
+            let writer = self.writer.take().unwrap();
+            return Ok(Async::Ready((writer, self.amt)));
+        }
+    }
+}
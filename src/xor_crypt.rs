@@ -0,0 +1,110 @@
+use std::io::{self, Read, Write};
+
+use futures::Poll;
+
+use {AsyncRead, AsyncWrite};
+
+/// An I/O object which XORs all data read from and written to the underlying
+/// object with a repeating key.
+///
+/// **This is not encryption.** A repeating XOR key is trivially broken and
+/// this type must never be used to protect real data. It exists to exercise
+/// codecs and other combinators over a link which transforms the bytes in
+/// flight, for example in tests, without pulling in a cryptography
+/// dependency. Since XOR is its own inverse, wrapping both ends of a link in
+/// an `XorCrypt` with the same key recovers the original bytes.
+///
+/// The keystream is applied positionally and the read and write sides each
+/// track their own position, so the two directions stay in sync
+/// independently of each other.
+///
+/// `AsyncWrite::write_bytes` and `AsyncWrite::write_buf_vectored` aren't
+/// passed through to the underlying object, since every byte written has to
+/// be XORed into a new buffer anyway. They use the default implementations,
+/// which copy the data.
+#[derive(Debug)]
+pub struct XorCrypt<T> {
+    inner: T,
+    key: Vec<u8>,
+    read_pos: usize,
+    write_pos: usize,
+}
+
+impl<T> XorCrypt<T> {
+    /// Creates a new `XorCrypt` wrapping `inner` with the given `key`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `key` is empty.
+    pub fn new(inner: T, key: Vec<u8>) -> XorCrypt<T> {
+        assert!(!key.is_empty(), "XorCrypt key must not be empty");
+
+        XorCrypt {
+            inner: inner,
+            key: key,
+            read_pos: 0,
+            write_pos: 0,
+        }
+    }
+
+    /// Returns a reference to the underlying I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying I/O object.
+    ///
+    /// Note that reading or writing through this reference bypasses the
+    /// keystream and will put it out of sync with the peer.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes the `XorCrypt`, returning the underlying I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn apply(key: &[u8], pos: usize, buf: &mut [u8]) {
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b ^= key[(pos + i) % key.len()];
+    }
+}
+
+impl<T: Read> Read for XorCrypt<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.inner.read(buf));
+        apply(&self.key, self.read_pos, &mut buf[..n]);
+        self.read_pos = (self.read_pos + n) % self.key.len();
+        Ok(n)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for XorCrypt<T> {
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        // Only the bytes the inner object reports as read are transformed,
+        // so whatever it requires of the buffer is all that's needed here.
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: Write> Write for XorCrypt<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut data = buf.to_vec();
+        apply(&self.key, self.write_pos, &mut data);
+        let n = try!(self.inner.write(&data));
+        self.write_pos = (self.write_pos + n) % self.key.len();
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for XorCrypt<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
@@ -147,3 +147,66 @@ fn read_buf_translate_wouldblock_to_not_ready() {
     let mut buf = BytesMut::with_capacity(65);
     assert!(!R.read_buf(&mut buf).unwrap().is_ready());
 }
+
+#[test]
+fn read_buf_empty() {
+    let mut buf = BytesMut::with_capacity(64);
+
+    let n = match io::empty().read_buf(&mut buf).unwrap() {
+        Async::Ready(n) => n,
+        _ => panic!(),
+    };
+
+    assert_eq!(0, n);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn read_buf_full_reads_multiple_chunks() {
+    struct R {
+        chunks: Vec<&'static [u8]>,
+    }
+
+    impl Read for R {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.chunks.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "would block"));
+            }
+            let chunk = self.chunks.remove(0);
+            let n = ::std::cmp::min(buf.len(), chunk.len());
+            buf[..n].copy_from_slice(&chunk[..n]);
+            if n < chunk.len() {
+                self.chunks.insert(0, &chunk[n..]);
+            }
+            Ok(n)
+        }
+    }
+
+    impl AsyncRead for R {}
+
+    // Large enough that `BytesMut` allocates exactly the requested capacity
+    let mut r = R { chunks: vec![&[1; 20], &[2; 30], &[3; 30], &[4; 5]] };
+
+    // Fills the buffer from several chunks
+    let mut buf = BytesMut::with_capacity(64);
+    assert_eq!(Async::Ready(64), r.read_buf_full(&mut buf).unwrap());
+    assert_eq!(buf[..], [&[1; 20][..], &[2; 30][..], &[3; 14][..]].concat()[..]);
+
+    // Stops when the reader blocks
+    let mut buf = BytesMut::with_capacity(64);
+    assert_eq!(Async::Ready(21), r.read_buf_full(&mut buf).unwrap());
+    assert_eq!(buf[..], [&[3; 16][..], &[4; 5][..]].concat()[..]);
+
+    // Blocks if nothing could be read
+    assert_eq!(Async::NotReady, r.read_buf_full(&mut buf).unwrap());
+}
+
+#[test]
+fn read_buf_full_stops_at_eof() {
+    let mut r = &b"abc"[..];
+
+    let mut buf = BytesMut::with_capacity(8);
+    assert_eq!(Async::Ready(3), r.read_buf_full(&mut buf).unwrap());
+    assert_eq!(buf[..], b"abc"[..]);
+    assert_eq!(Async::Ready(0), r.read_buf_full(&mut buf).unwrap());
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "bencode")]
+
+extern crate tokio_io;
+extern crate bytes;
+
+use tokio_io::codec::{decode_all, encode_all, Decoder, Encoder};
+use tokio_io::codec::bencode::{BencodeCodec, BencodeValue};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use std::collections::BTreeMap;
+use std::io;
+
+fn bytes(s: &'static str) -> BencodeValue {
+    BencodeValue::Bytes(Bytes::from_static(s.as_bytes()))
+}
+
+#[test]
+fn bencode_decoder() {
+    let mut codec = BencodeCodec::new();
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    buf.put("4:spami-42el4:spami");
+    assert_eq!(Some(bytes("spam")), codec.decode(buf).unwrap());
+    assert_eq!(Some(BencodeValue::Integer(-42)), codec.decode(buf).unwrap());
+
+    // The list isn't complete yet
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.put("7ee");
+    let list = vec![bytes("spam"), BencodeValue::Integer(7)];
+    assert_eq!(Some(BencodeValue::List(list)), codec.decode(buf).unwrap());
+
+    buf.put("d3:cow3:moo4:spaml1:a1:bee");
+    let mut dict = BTreeMap::new();
+    dict.insert(Bytes::from_static(b"cow"), bytes("moo"));
+    dict.insert(Bytes::from_static(b"spam"), BencodeValue::List(vec![bytes("a"), bytes("b")]));
+    assert_eq!(Some(BencodeValue::Dict(dict)), codec.decode(buf).unwrap());
+
+    assert_eq!(None, codec.decode(buf).unwrap());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn bencode_decoder_split_string() {
+    let mut codec = BencodeCodec::new();
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    buf.put("11:hello");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.put(" world");
+    assert_eq!(Some(bytes("hello world")), codec.decode(buf).unwrap());
+}
+
+#[test]
+fn bencode_decoder_malformed() {
+    for input in &["i03e", "i-0e", "ie", "x", "d1:ai1ei2ei3ee", "1a:", "i1-2e", "d3:fooe", "d1:ai1e1:be"] {
+        let mut codec = BencodeCodec::new();
+        let buf = &mut BytesMut::from(&input[..]);
+        let err = codec.decode(buf).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind(), "{}", input);
+    }
+}
+
+#[test]
+fn bencode_encoder() {
+    let mut codec = BencodeCodec::new();
+    let mut buf = BytesMut::new();
+
+    let mut dict = BTreeMap::new();
+    dict.insert(Bytes::from_static(b"spam"), BencodeValue::List(vec![bytes("a"), BencodeValue::Integer(-3)]));
+    dict.insert(Bytes::from_static(b"cow"), bytes("moo"));
+
+    codec.encode(BencodeValue::Dict(dict), &mut buf).unwrap();
+    assert_eq!(&buf[..], &b"d3:cow3:moo4:spaml1:ai-3eee"[..]);
+}
+
+#[test]
+fn bencode_round_trip() {
+    let mut dict = BTreeMap::new();
+    dict.insert(Bytes::from_static(b"list"), BencodeValue::List(vec![
+        BencodeValue::Integer(-3),
+        bytes("spam"),
+    ]));
+    dict.insert(Bytes::from_static(b"n"), BencodeValue::Integer(42));
+    let values = vec![BencodeValue::Dict(dict), bytes(""), BencodeValue::List(vec![])];
+
+    let buf = encode_all(BencodeCodec::new(), values.clone()).unwrap();
+    assert_eq!(decode_all(BencodeCodec::new(), &buf).unwrap(), values);
+}
+
+#[test]
+fn bencode_decoder_max_frame_len() {
+    // A declared length past the max fails without waiting for the data
+    let mut codec = BencodeCodec::new();
+    let buf = &mut BytesMut::from(&b"99999999999:"[..]);
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+
+    let mut codec = BencodeCodec::new();
+    codec.set_max_frame_length(10);
+    assert_eq!(10, codec.max_frame_length());
+
+    let buf = &mut BytesMut::from(&b"l3:abc3:defe"[..]);
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+
+    let buf = &mut BytesMut::from(&b"l3:abc"[..]);
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"i1e");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"e");
+    let list = vec![bytes("abc"), BencodeValue::Integer(1)];
+    assert_eq!(Some(BencodeValue::List(list)), codec.decode(buf).unwrap());
+}
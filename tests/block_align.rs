@@ -0,0 +1,61 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::{write_all, BlockAlign};
+
+use futures::{Async, Future, Poll};
+
+use std::io::{self, Write};
+
+// Records the size of every write
+#[derive(Debug, Default)]
+struct Recorder {
+    data: Vec<u8>,
+    writes: Vec<usize>,
+}
+
+impl Write for Recorder {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(src);
+        self.writes.push(src.len());
+        Ok(src.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Recorder {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Async::Ready(()))
+    }
+}
+
+#[test]
+fn block_align_unaligned_tail() {
+    let wr = BlockAlign::new(Recorder::default(), 4);
+    let (mut wr, _) = write_all(wr, b"hello").wait().unwrap();
+    let (mut wr, _) = write_all(wr, b" world").wait().unwrap();
+    assert_eq!(wr.get_ref().writes, [4, 4]);
+
+    wr.flush().unwrap();
+    let rec = wr.into_inner();
+    assert_eq!(rec.writes, [4, 4, 3]);
+    assert_eq!(&rec.data[..], b"hello world");
+}
+
+#[test]
+fn block_align_padded_tail() {
+    let mut wr = BlockAlign::new(Recorder::default(), 4);
+    wr.set_pad_on_flush(true);
+    wr.set_filler(b'-');
+
+    let (mut wr, _) = write_all(wr, b"hello world").wait().unwrap();
+    assert!(wr.shutdown().unwrap().is_ready());
+
+    let rec = wr.into_inner();
+    assert_eq!(rec.writes, [4, 4, 4]);
+    assert_eq!(&rec.data[..], b"hello world-");
+}
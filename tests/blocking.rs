@@ -0,0 +1,92 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_io::io::{BlockingRead, BlockingWrite};
+
+use futures::{task, Async, Poll};
+
+use std::io::{self, Read, Write};
+use std::sync::mpsc;
+use std::thread;
+
+// Blocks on every other call, notifying the task from another thread so the
+// blocked caller is woken up
+struct Flaky {
+    blocked: bool,
+    data: Vec<u8>,
+}
+
+impl Flaky {
+    fn block(&mut self) -> bool {
+        self.blocked = !self.blocked;
+        if self.blocked {
+            let task = task::current();
+            thread::spawn(move || task.notify());
+        }
+        self.blocked
+    }
+}
+
+impl Read for Flaky {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.block() {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+        }
+        let n = self.data.len().min(buf.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data.drain(..n);
+        Ok(n)
+    }
+}
+
+impl AsyncRead for Flaky {}
+
+impl Write for Flaky {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.block() {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+        }
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Flaky {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Async::Ready(()))
+    }
+}
+
+#[test]
+fn blocking_read_parks_until_notified() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut rd = BlockingRead::new(Flaky { blocked: false, data: b"hello world".to_vec() });
+        let mut buf = String::new();
+        rd.read_to_string(&mut buf).unwrap();
+        tx.send(buf).unwrap();
+    });
+
+    assert_eq!(rx.recv().unwrap(), "hello world");
+}
+
+#[test]
+fn blocking_write_parks_until_notified() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut wr = BlockingWrite::new(Flaky { blocked: false, data: vec![] });
+        wr.write_all(b"hello").unwrap();
+        wr.write_all(b" world").unwrap();
+        wr.flush().unwrap();
+        tx.send(wr.into_inner().data).unwrap();
+    });
+
+    assert_eq!(rx.recv().unwrap(), b"hello world");
+}
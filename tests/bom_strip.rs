@@ -0,0 +1,108 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::{read_to_end, Bom, BomStrip};
+
+use futures::Future;
+
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+// Yields scripted chunks; `None` entries would block
+struct Mock {
+    chunks: VecDeque<Option<&'static [u8]>>,
+}
+
+fn mock(chunks: Vec<Option<&'static [u8]>>) -> Mock {
+    Mock { chunks: chunks.into_iter().collect() }
+}
+
+impl Read for Mock {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.chunks.pop_front() {
+            Some(Some(data)) => {
+                let n = ::std::cmp::min(buf.len(), data.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                if n < data.len() {
+                    self.chunks.push_front(Some(&data[n..]));
+                }
+                Ok(n)
+            }
+            Some(None) => Err(io::Error::new(io::ErrorKind::WouldBlock, "blocked")),
+            None => Ok(0),
+        }
+    }
+}
+
+impl AsyncRead for Mock {}
+
+fn read_all(rd: &mut BomStrip<Mock>) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut buf = [0; 64];
+    loop {
+        match rd.read(&mut buf) {
+            Ok(0) => return out,
+            Ok(n) => out.extend_from_slice(&buf[..n]),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+#[test]
+fn strips_each_bom() {
+    let cases: [(&'static [u8], Bom); 3] = [
+        (b"\xEF\xBB\xBFtext", Bom::Utf8),
+        (b"\xFF\xFEt\x00", Bom::Utf16Le),
+        (b"\xFE\xFF\x00t", Bom::Utf16Be),
+    ];
+
+    for &(data, bom) in cases.iter() {
+        let mut rd = BomStrip::new(mock(vec![Some(data)]));
+        assert_eq!(read_all(&mut rd), &data[bom.as_bytes().len()..]);
+        assert_eq!(rd.detected_bom(), Some(bom));
+    }
+}
+
+#[test]
+fn bom_split_across_reads() {
+    let mut rd = BomStrip::new(mock(vec![
+        Some(b"\xEF"),
+        None,
+        Some(b"\xBB"),
+        Some(b"\xBFhello"),
+    ]));
+
+    let mut buf = [0; 16];
+    assert_eq!(rd.read(&mut buf).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(rd.detected_bom(), None);
+
+    assert_eq!(read_all(&mut rd), b"hello");
+    assert_eq!(rd.detected_bom(), Some(Bom::Utf8));
+}
+
+#[test]
+fn passes_through_non_bom_prefix() {
+    // Looks like the start of a UTF-8 mark but isn't
+    let mut rd = BomStrip::new(mock(vec![Some(b"\xEF"), Some(b"\xBBx"), Some(b"yz")]));
+    assert_eq!(read_all(&mut rd), b"\xEF\xBBxyz");
+    assert_eq!(rd.detected_bom(), None);
+
+    let mut rd = BomStrip::new(mock(vec![Some(b"plain")]));
+    assert_eq!(read_all(&mut rd), b"plain");
+    assert_eq!(rd.detected_bom(), None);
+
+    // EOF in the middle of a possible mark
+    let mut rd = BomStrip::new(mock(vec![Some(b"\xFE")]));
+    assert_eq!(read_all(&mut rd), b"\xFE");
+    assert_eq!(rd.detected_bom(), None);
+}
+
+#[test]
+fn works_with_read_to_end() {
+    let rd = BomStrip::new(mock(vec![Some(b"\xFE"), Some(b"\xFF\x00a\x00b")]));
+    let (rd, buf) = read_to_end(rd, Vec::new()).wait().unwrap();
+    assert_eq!(buf, b"\x00a\x00b");
+    assert_eq!(rd.detected_bom(), Some(Bom::Utf16Be));
+}
@@ -0,0 +1,154 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::{Checksum, ChecksumTrailer, ChecksumVerify, Crc32};
+
+use futures::Async;
+
+use std::io::{self, Read, Write};
+
+#[test]
+fn crc32_known_value() {
+    let mut crc = Crc32::new();
+    crc.update(b"hello ");
+    crc.update(b"world");
+    assert_eq!(0x0d4a1185, crc.value());
+    assert_eq!(vec![0x0d, 0x4a, 0x11, 0x85], crc.digest());
+}
+
+// Accepts at most `limit` bytes per write, and blocks every other write
+struct Throttled {
+    written: Vec<u8>,
+    limit: usize,
+    block: bool,
+    shut_down: bool,
+}
+
+impl Write for Throttled {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        self.block = !self.block;
+
+        if self.block {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "would block"));
+        }
+
+        let n = ::std::cmp::min(src.len(), self.limit);
+        self.written.extend_from_slice(&src[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Throttled {
+    fn shutdown(&mut self) -> futures::Poll<(), io::Error> {
+        self.shut_down = true;
+        Ok(Async::Ready(()))
+    }
+}
+
+#[test]
+fn trailer_appended_on_shutdown() {
+    let wr = Throttled { written: Vec::new(), limit: 3, block: true, shut_down: false };
+    let mut wr = ChecksumTrailer::new(wr);
+
+    let mut data = &b"hello world"[..];
+    while !data.is_empty() {
+        match wr.write(data) {
+            Ok(n) => data = &data[n..],
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    let mut expected = Crc32::new();
+    expected.update(b"hello world");
+    assert_eq!(expected.digest(), wr.current_checksum());
+
+    // The trailer is written across several calls to `shutdown`
+    let mut polls = 0;
+    while wr.shutdown().unwrap().is_not_ready() {
+        polls += 1;
+        assert!(!wr.get_ref().shut_down);
+    }
+    assert!(polls > 0);
+    assert!(wr.write(b"more").is_err());
+
+    let wr = wr.into_inner();
+    assert!(wr.shut_down);
+    assert_eq!(&b"hello world\x0d\x4a\x11\x85"[..], &wr.written[..]);
+}
+
+// Yields the data in the given chunks
+struct Chunked {
+    chunks: Vec<&'static [u8]>,
+}
+
+impl Read for Chunked {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        if self.chunks.is_empty() {
+            return Ok(0);
+        }
+
+        let chunk = self.chunks.remove(0);
+        dst[..chunk.len()].copy_from_slice(chunk);
+        Ok(chunk.len())
+    }
+}
+
+fn read_all<R: Read>(mut rd: R) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut buf = [0; 16];
+
+    loop {
+        match try!(rd.read(&mut buf)) {
+            0 => return Ok(data),
+            n => data.extend_from_slice(&buf[..n]),
+        }
+    }
+}
+
+#[test]
+fn verify_valid_trailer() {
+    let rd = ChecksumVerify::new(&b"hello world\x0d\x4a\x11\x85"[..]);
+    assert_eq!(&b"hello world"[..], &read_all(rd).unwrap()[..]);
+
+    // The trailer is split across reads, and a read holds nothing but part
+    // of it
+    let rd = ChecksumVerify::new(Chunked {
+        chunks: vec![b"hel", b"lo world\x0d", b"\x4a", b"\x11\x85"],
+    });
+    assert_eq!(&b"hello world"[..], &read_all(rd).unwrap()[..]);
+
+    let rd = ChecksumVerify::new(&b"\x00\x00\x00\x00"[..]);
+    assert!(read_all(rd).unwrap().is_empty());
+}
+
+#[test]
+fn verify_corrupted_trailer() {
+    let mut rd = ChecksumVerify::new(&b"hello world\x0d\x4a\x11\x86"[..]);
+    let mut buf = [0; 32];
+    assert_eq!(11, rd.read(&mut buf).unwrap());
+    assert_eq!(io::ErrorKind::InvalidData, rd.read(&mut buf).unwrap_err().kind());
+    assert_eq!(io::ErrorKind::InvalidData, rd.read(&mut buf).unwrap_err().kind());
+
+    let rd = ChecksumVerify::new(&b"hellO world\x0d\x4a\x11\x85"[..]);
+    assert_eq!(io::ErrorKind::InvalidData, read_all(rd).unwrap_err().kind());
+
+    let rd = ChecksumVerify::new(&b"\x0d\x4a\x11"[..]);
+    assert_eq!(io::ErrorKind::UnexpectedEof, read_all(rd).unwrap_err().kind());
+}
+
+#[test]
+fn trailer_round_trip() {
+    let wr = Throttled { written: Vec::new(), limit: 64, block: true, shut_down: false };
+    let mut wr = ChecksumTrailer::new(wr);
+    while wr.write(b"round trip").is_err() {}
+    while wr.shutdown().unwrap().is_not_ready() {}
+
+    let data = wr.into_inner().written;
+    assert_eq!(&b"round trip"[..], &read_all(ChecksumVerify::new(&data[..])).unwrap()[..]);
+}
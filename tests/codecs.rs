@@ -2,7 +2,13 @@ extern crate tokio_io;
 extern crate bytes;
 
 use bytes::{BytesMut, Bytes, BufMut};
-use tokio_io::codec::{BytesCodec, LinesCodec, Decoder, Encoder};
+use tokio_io::codec::{BytesCodec, Codec, LinesCodec, FilterFrames, Sequenced, SequenceGap, Decoder, Encoder};
+use tokio_io::codec::{decode_all, encode_all, poll_decode_from, Alternating, AndThen, BitFrame, BitOrder, BitPacked, BitReader, BitWriter, ByteQuota, CountErrors, Csv, Fragment, HeaderBody, KeyValueHeaders, LayeredError, MinFrameLen, Rle, StaleFrameGuard, SyncFramed, Validated};
+use tokio_io::codec::slip::SlipCodec;
+use tokio_io::codec::asn1::BerCodec;
+use tokio_io::codec::text_command::{Frame, TextCommandCodec};
+
+use std::io;
 
 #[test]
 fn bytes_decoder() {
@@ -74,3 +80,939 @@ fn lines_encoder() {
     let mut buf = BytesMut::with_capacity(INITIAL_CAPACITY);
     codec.encode(Bytes::from_static(&[b'a'; INITIAL_CAPACITY + 1]), &mut buf).unwrap();
 }
+
+#[test]
+fn filter_frames_decoder() {
+    let mut codec = FilterFrames::new(LinesCodec::new(), |line: &String| line != "ping");
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+    buf.put("ping\nline 1\nping\nping\nline 2\nping");
+    assert_eq!("line 1", codec.decode(buf).unwrap().unwrap());
+    assert_eq!("line 2", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+}
+
+#[test]
+fn slip_decoder() {
+    let mut codec = SlipCodec::new();
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+    buf.put(&b"\xc0\xc0abc\xc0\x01\xdb\xdc\x02\xdb\xdd\xc0bad\xdb\x00\xc0ok\xc0pa"[..]);
+    assert_eq!("abc", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(&b"\x01\xc0\x02\xdb"[..], codec.decode(buf).unwrap().unwrap());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!("ok", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.put(&b"rtial\xc0"[..]);
+    assert_eq!("partial", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn slip_encoder() {
+    let mut codec = SlipCodec::new();
+    let mut buf = BytesMut::new();
+    codec.encode(Bytes::from_static(b"\x01\xc0\x02\xdb"), &mut buf).unwrap();
+    assert_eq!(&b"\xc0\x01\xdb\xdc\x02\xdb\xdd\xc0"[..], &buf[..]);
+}
+
+#[test]
+fn sequenced_in_order() {
+    let mut codec = Sequenced::new(BytesCodec::new());
+    let buf = &mut BytesMut::new();
+
+    for payload in &["abc", "def"] {
+        codec.encode(Bytes::from(*payload), buf).unwrap();
+    }
+    assert_eq!(&b"\0\0\0\0abc\0\0\0\x01def"[..], &buf[..]);
+
+    let mut decoder = Sequenced::new(BytesCodec::new());
+    let buf = &mut BytesMut::new();
+    buf.put(&b"\0\0\0\0abc"[..]);
+    assert_eq!("abc", decoder.decode(buf).unwrap().unwrap());
+    buf.put(&b"\0\0\0\x01def"[..]);
+    assert_eq!("def", decoder.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn sequenced_gap() {
+    let mut codec = Sequenced::new(BytesCodec::new());
+    let buf = &mut BytesMut::new();
+    buf.put(&b"\0\0\0\x02abc"[..]);
+
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    let gap = err.get_ref().unwrap().downcast_ref::<SequenceGap>().unwrap();
+    assert_eq!(0, gap.expected());
+    assert_eq!(2, gap.got());
+
+    // Decoding resumes after the sequence number that was received
+    buf.put(&b"\0\0\0\x03def"[..]);
+    assert_eq!("def", codec.decode(buf).unwrap().unwrap());
+
+    codec.reset_sequence();
+    buf.put(&b"\0\0\0\0ghi"[..]);
+    assert_eq!("ghi", codec.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn sequenced_wraparound() {
+    let mut codec = Sequenced::with_initial_sequence(BytesCodec::new(), u32::max_value());
+    let buf = &mut BytesMut::new();
+    codec.encode(Bytes::from_static(b"a"), buf).unwrap();
+    codec.encode(Bytes::from_static(b"b"), buf).unwrap();
+    assert_eq!(&b"\xff\xff\xff\xffa\0\0\0\0b"[..], &buf[..]);
+
+    let mut decoder = Sequenced::with_initial_sequence(BytesCodec::new(), u32::max_value());
+    let frame = &mut BytesMut::new();
+    frame.put(&buf[..5]);
+    assert_eq!("a", decoder.decode(frame).unwrap().unwrap());
+    frame.put(&buf[5..]);
+    assert_eq!("b", decoder.decode(frame).unwrap().unwrap());
+}
+
+#[test]
+fn and_then_layered_errors() {
+    let mut codec = AndThen::new(SlipCodec::new(), LinesCodec::new());
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+    buf.put(&b"hello\xc0bad\xdb\x00\xc0\xff\xfe\xc0world\xc0"[..]);
+
+    assert_eq!("hello", codec.decode(buf).unwrap().unwrap());
+    match codec.decode(buf).unwrap_err() {
+        LayeredError::Framing(e) => assert_eq!(io::ErrorKind::InvalidData, e.kind()),
+        LayeredError::Payload(_) => panic!(),
+    }
+    match codec.decode(buf).unwrap_err() {
+        LayeredError::Payload(e) => assert_eq!(io::ErrorKind::InvalidData, e.kind()),
+        LayeredError::Framing(_) => panic!(),
+    }
+    assert_eq!("world", codec.decode(buf).unwrap().unwrap());
+    assert!(codec.decode(buf).unwrap().is_none());
+
+    let mut buf = BytesMut::new();
+    codec.encode("hi".to_string(), &mut buf).unwrap();
+    assert_eq!(&b"\xc0hi\n\xc0"[..], &buf[..]);
+}
+
+// Body length is `count * size + 1`, from a 2 byte header
+fn two_field_len(header: &[u8]) -> usize {
+    header[0] as usize * header[1] as usize + 1
+}
+
+#[test]
+fn header_body_decoder() {
+    let mut codec = HeaderBody::new(2, two_field_len);
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+    buf.put(&b"\x02\x03abcdefg\x00\x00"[..]);
+    let (header, body) = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(&b"\x02\x03"[..], &header[..]);
+    assert_eq!("abcdefg", body);
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.put("z");
+    let (header, body) = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(&b"\x00\x00"[..], &header[..]);
+    assert_eq!("z", body);
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn header_body_max_frame_len() {
+    let mut codec = HeaderBody::new(2, two_field_len);
+    codec.set_max_frame_length(8);
+    let buf = &mut BytesMut::new();
+    buf.put(&b"\x02\x03"[..]);
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+
+    let mut codec = HeaderBody::new(1, |_: &[u8]| usize::max_value());
+    let buf = &mut BytesMut::new();
+    buf.put(&b"\x00"[..]);
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+}
+
+#[test]
+fn stale_frame_guard_times_out_partial_frame() {
+    let mut codec = StaleFrameGuard::new(LinesCodec::new(), 2);
+    let buf = &mut BytesMut::new();
+    buf.reserve(200);
+
+    // No partial frame, so generations passing don't matter
+    for _ in 0..5 {
+        codec.advance_generation();
+    }
+    assert_eq!(None, codec.decode(buf).unwrap());
+
+    // A frame completing within the threshold resets the marker
+    buf.put("line 1");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    codec.advance_generation();
+    codec.advance_generation();
+    buf.put("\nline 2");
+    assert_eq!("line 1", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+
+    codec.advance_generation();
+    codec.advance_generation();
+    assert_eq!(None, codec.decode(buf).unwrap());
+    codec.advance_generation();
+    assert_eq!(io::ErrorKind::TimedOut, codec.decode(buf).unwrap_err().kind());
+}
+
+// Writes each fragment as a FIN byte followed by the fragment bytes
+struct FinCodec;
+
+impl Encoder for FinCodec {
+    type Item = (bool, Bytes);
+    type Error = io::Error;
+
+    fn encode(&mut self, (fin, data): (bool, Bytes), dst: &mut BytesMut) -> io::Result<()> {
+        dst.reserve(1 + data.len());
+        dst.put_u8(fin as u8);
+        dst.put_slice(&data);
+        Ok(())
+    }
+}
+
+#[test]
+fn fragment_encoder() {
+    let mut codec = Fragment::new(FinCodec, 4);
+    let mut buf = BytesMut::new();
+
+    // Smaller than the fragment size
+    codec.encode(Bytes::from_static(b"ab"), &mut buf).unwrap();
+    assert_eq!(&b"\x01ab"[..], &buf[..]);
+    buf.clear();
+
+    // Equal to the fragment size
+    codec.encode(Bytes::from_static(b"abcd"), &mut buf).unwrap();
+    assert_eq!(&b"\x01abcd"[..], &buf[..]);
+    buf.clear();
+
+    // Larger than the fragment size
+    codec.encode(Bytes::from_static(b"abcdefghij"), &mut buf).unwrap();
+    assert_eq!(&b"\x00abcd\x00efgh\x01ij"[..], &buf[..]);
+    buf.clear();
+
+    // Empty messages still produce a final fragment
+    codec.encode(Bytes::new(), &mut buf).unwrap();
+    assert_eq!(&b"\x01"[..], &buf[..]);
+}
+
+#[test]
+fn min_frame_len_decoder() {
+    let mut codec = MinFrameLen::new(LinesCodec::new(), 3);
+    assert_eq!(codec.min_frame_length(), 3);
+
+    let buf = &mut BytesMut::new();
+    buf.put_slice(b"abc\nab\nabcd\nab");
+
+    // Exactly the minimum
+    assert_eq!("abc", codec.decode(buf).unwrap().unwrap());
+
+    // Below the minimum
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Above the minimum
+    assert_eq!("abcd", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+
+    // Trailing frame at EOF
+    let err = codec.decode_eof(buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn decode_all_frames() {
+    let lines = decode_all(LinesCodec::new(), b"one\ntwo\r\nthree").unwrap();
+    assert_eq!(lines, vec!["one", "two", "three"]);
+
+    assert!(decode_all(LinesCodec::new(), b"").unwrap().is_empty());
+
+    let frames = decode_all(SlipCodec::new(), b"\xC0ab\xC0").unwrap();
+    assert_eq!(frames, vec![Bytes::from_static(b"ab")]);
+
+    // Leftover bytes are reported by `decode_eof`
+    let err = decode_all(SlipCodec::new(), b"\xC0ab\xC0cd").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+fn encode_all_frames() {
+    let buf = encode_all(LinesCodec::new(), vec!["one".to_string(), "two".to_string()]).unwrap();
+    assert_eq!(&buf[..], b"one\ntwo\n");
+
+    assert!(encode_all(LinesCodec::new(), vec![]).unwrap().is_empty());
+}
+
+#[test]
+fn encode_all_round_trips() {
+    let lines = vec!["one".to_string(), "".to_string(), "three".to_string()];
+    let buf = encode_all(LinesCodec::new(), lines.clone()).unwrap();
+    assert_eq!(decode_all(LinesCodec::new(), &buf).unwrap(), lines);
+
+    let frames = vec![Bytes::from_static(b"\x01\xc0"), Bytes::from_static(b"\xdb\x02")];
+    let buf = encode_all(SlipCodec::new(), frames.clone()).unwrap();
+    assert_eq!(decode_all(SlipCodec::new(), &buf).unwrap(), frames);
+
+    // `BytesCodec` yields everything read so far as a single frame
+    let chunks = vec![Bytes::from_static(b"ab"), Bytes::from_static(b"cd")];
+    let buf = encode_all(BytesCodec::new(), chunks).unwrap();
+    assert_eq!(decode_all(BytesCodec::new(), &buf).unwrap(), vec![BytesMut::from(&b"abcd"[..])]);
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn key_value_headers_decoder() {
+    let mut codec = KeyValueHeaders::new();
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(b"Host: example.com\r\nEmpty:\r\nRatio:  a:b \n");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"\r\n\nKey: v");
+    assert_eq!(pairs(&[("Host", "example.com"), ("Empty", ""), ("Ratio", "a:b")]),
+               codec.decode(buf).unwrap().unwrap());
+
+    // A block with no headers
+    assert_eq!(Vec::<(String, String)>::new(), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+
+    let err = codec.decode_eof(buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn key_value_headers_rejects_folded_lines() {
+    let mut codec = KeyValueHeaders::new();
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"Key: first\r\n  continued\r\n\r\nKey: v\r\n\r\n");
+
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // The rest of the broken block is decoded as its own block
+    assert_eq!(Vec::<(String, String)>::new(), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(pairs(&[("Key", "v")]), codec.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn key_value_headers_limits() {
+    let mut codec = KeyValueHeaders::new();
+    codec.set_max_headers(2);
+    codec.set_max_line_length(8);
+
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"a: 1\nb: 2\nc: 3\n");
+    assert_eq!(codec.decode(buf).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"a: 123456\r\n");
+    assert_eq!(codec.decode(buf).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+    // Detected before the line ending arrives
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"a: 12345\r");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"6");
+    assert_eq!(codec.decode(buf).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"no colon\n");
+    assert_eq!(codec.decode(buf).unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn key_value_headers_encoder() {
+    let mut codec = KeyValueHeaders::new();
+    let mut buf = BytesMut::new();
+
+    codec.encode(pairs(&[("Host", "example.com"), ("Empty", "")]), &mut buf).unwrap();
+    assert_eq!(&buf[..], &b"Host: example.com\r\nEmpty: \r\n\r\n"[..]);
+    assert_eq!(pairs(&[("Host", "example.com"), ("Empty", "")]),
+               codec.decode(&mut buf).unwrap().unwrap());
+
+    let err = codec.encode(pairs(&[("Key", "a\r\nb")]), &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = codec.encode(pairs(&[(" Key", "v")]), &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn key_value_headers_encoder_writes_nothing_on_error() {
+    let mut codec = KeyValueHeaders::new();
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(b"Sent: 1\r\n\r\n");
+
+    let err = codec.encode(pairs(&[("A", "1"), ("Key", "a\r\nb")]), &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(&buf[..], &b"Sent: 1\r\n\r\n"[..]);
+}
+
+#[test]
+fn rle_decoder() {
+    let mut codec = Rle::new(SlipCodec::new());
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"\xc0\x03a\x01b\x02c\xc0\xc0\x03a\x01\xc0\xc0\x03a\x00b\xc0");
+
+    assert_eq!("aaabcc", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn rle_max_expanded_length() {
+    let mut codec = Rle::new(SlipCodec::new());
+    codec.set_max_expanded_length(300);
+
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"\xc0\xffa\x2db\xc0\xc0\xffa\x2eb\xc0");
+    assert_eq!(300, codec.decode(buf).unwrap().unwrap().len());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+}
+
+#[test]
+fn rle_round_trip() {
+    let mut long = vec![b'x'; 300];
+    long.extend_from_slice(b"yz");
+    let items = vec![Bytes::from(long), Bytes::from_static(b"abbccc")];
+
+    let buf = encode_all(Rle::new(SlipCodec::new()), items.clone()).unwrap();
+    assert_eq!(&buf[..], &b"\xc0\xffx\x2dx\x01y\x01z\xc0\xc0\x01a\x02b\x03c\xc0"[..]);
+    assert_eq!(decode_all(Rle::new(SlipCodec::new()), &buf).unwrap(), items);
+}
+
+fn round_trip<C: Codec>(codec: &mut C, item: <C as Decoder>::Item) -> Option<<C as Decoder>::Item>
+    where <C as Decoder>::Error: ::std::fmt::Debug,
+{
+    let mut buf = BytesMut::new();
+    codec.encode(item, &mut buf).unwrap();
+    codec.decode(&mut buf).unwrap()
+}
+
+#[test]
+fn codec_implemented_for_full_codecs() {
+    assert_eq!(Some("line".to_string()), round_trip(&mut LinesCodec::new(), "line".to_string()));
+    assert_eq!(Some(pairs(&[("Key", "value")])),
+               round_trip(&mut KeyValueHeaders::new(), pairs(&[("Key", "value")])));
+}
+
+#[test]
+fn sync_framed_decoder() {
+    let mut codec = SyncFramed::new(b"\xaa\x55", 0x7d);
+    let buf = &mut BytesMut::new();
+
+    // Line noise, then a frame whose payload is stuffed
+    buf.extend_from_slice(b"\x01\x55\xaa\x55\x00\x04a\x7d\x8a\x7d\x5dz");
+    assert_eq!(&b"a\xaa\x7dz"[..], &codec.decode(buf).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode(buf).unwrap());
+
+    // A frame split across reads, including within the sync word
+    buf.extend_from_slice(b"\xaa");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"\x55\x00\x02h");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"i");
+    assert_eq!("hi", codec.decode(buf).unwrap().unwrap());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn sync_framed_decoder_byte_by_byte() {
+    let mut codec = SyncFramed::new(b"\xaa\x55", 0x7d);
+    let buf = &mut BytesMut::new();
+
+    let mut frame = vec![];
+    for i in 0..1_000u32 {
+        frame.push((i % 251) as u8);
+    }
+    frame.extend_from_slice(b"\xaa\x7d");
+    let wire = encode_all(SyncFramed::new(b"\xaa\x55", 0x7d), vec![Bytes::from(frame.clone())]).unwrap();
+
+    let (last, rest) = wire.split_last().unwrap();
+    for &b in rest {
+        buf.extend_from_slice(&[b]);
+        assert_eq!(None, codec.decode(buf).unwrap());
+    }
+    buf.extend_from_slice(&[*last]);
+    assert_eq!(&frame[..], &codec.decode(buf).unwrap().unwrap()[..]);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn sync_framed_resyncs_after_errors() {
+    let mut codec = SyncFramed::new(b"\xaa\x55", 0x7d);
+    codec.set_max_frame_length(16);
+    let buf = &mut BytesMut::new();
+
+    // Invalid escape, truncated frame, oversized frame, then a good one
+    buf.extend_from_slice(b"\xaa\x55\x00\x02\x7d\x01garbage");
+    buf.extend_from_slice(b"\xaa\x55\x00\x05ab");
+    buf.extend_from_slice(b"\xaa\x55\x01\x00");
+    buf.extend_from_slice(b"\xaa\x55\x00\x02ok");
+
+    for _ in 0..3 {
+        assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    }
+    assert_eq!("ok", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn sync_framed_round_trip() {
+    let mut codec = SyncFramed::new(b"\xaa\x55", 0x7d);
+    codec.set_length_field_length(1);
+
+    let frames = vec![Bytes::from_static(b"\xaa\x55\x7d"), Bytes::new(), Bytes::from_static(b"plain")];
+    let buf = encode_all(SyncFramed::new(b"\xaa\x55", 0x7d), frames.clone()).unwrap();
+    assert_eq!(&buf[..9], &b"\xaa\x55\x00\x03\x7d\x8a\x55\x7d\x5d"[..]);
+    assert_eq!(decode_all(SyncFramed::new(b"\xaa\x55", 0x7d), &buf).unwrap(), frames);
+
+    // The length has to fit the length field
+    let mut buf = BytesMut::new();
+    let err = codec.encode(Bytes::from(vec![0; 256]), &mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn text_command_decoder() {
+    let mut codec = TextCommandCodec::new();
+    let buf = &mut BytesMut::new();
+
+    // Heartbeats before the frame and `\r\n` line endings are accepted
+    buf.extend_from_slice(b"\n\r\nSEND\r\ndestination:/queue/a\r\nreceipt:7\r\n\r\nhel");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"lo\0\nCONNECT\n\n\0");
+
+    let frame = codec.decode(buf).unwrap().unwrap();
+    assert_eq!("SEND", frame.command);
+    assert_eq!(frame.headers, pairs(&[("destination", "/queue/a"), ("receipt", "7")]));
+    assert_eq!(&b"hello"[..], &frame.body[..]);
+
+    let frame = codec.decode(buf).unwrap().unwrap();
+    assert_eq!("CONNECT", frame.command);
+    assert!(frame.headers.is_empty());
+    assert!(frame.body.is_empty());
+
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"\n");
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+}
+
+#[test]
+fn text_command_decoder_limits() {
+    let mut codec = TextCommandCodec::new();
+    codec.set_max_headers(1);
+    codec.set_max_frame_length(32);
+    let buf = &mut BytesMut::new();
+
+    // Too many headers, a missing colon, then a good frame
+    buf.extend_from_slice(b"SEND\na:1\nb:2\n\n\0SEND\nnocolon\n\n\0SEND\na:1\n\nok\0");
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!("ok", codec.decode(buf).unwrap().unwrap().body);
+
+    // An oversized frame is skipped up to its terminator
+    buf.extend_from_slice(&[b'x'; 40]);
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    buf.extend_from_slice(b"xxx\0SEND\n\nnext\0");
+    assert_eq!("next", codec.decode(buf).unwrap().unwrap().body);
+
+    // As is an unterminated frame at EOF
+    buf.extend_from_slice(b"SEND\n");
+    assert_eq!(io::ErrorKind::UnexpectedEof, codec.decode_eof(buf).unwrap_err().kind());
+}
+
+#[test]
+fn text_command_round_trip() {
+    let frames = vec![
+        Frame {
+            command: "SEND".to_string(),
+            headers: pairs(&[("destination", "/queue/a"), ("content-type", "text/plain")]),
+            body: Bytes::from_static(b"hello\nqueue"),
+        },
+        Frame {
+            command: "DISCONNECT".to_string(),
+            headers: vec![],
+            body: Bytes::new(),
+        },
+    ];
+
+    let buf = encode_all(TextCommandCodec::new(), frames.clone()).unwrap();
+    assert_eq!(&buf[..39], &b"SEND\ndestination:/queue/a\ncontent-type:"[..]);
+    assert_eq!(decode_all(TextCommandCodec::new(), &buf).unwrap(), frames);
+
+    // A NUL byte in the body can't be encoded
+    let mut codec = TextCommandCodec::new();
+    let mut buf = BytesMut::new();
+    let frame = Frame {
+        command: "SEND".to_string(),
+        headers: vec![],
+        body: Bytes::from_static(b"a\0b"),
+    };
+    assert_eq!(io::ErrorKind::InvalidInput, codec.encode(frame, &mut buf).unwrap_err().kind());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn alternating_accepts_lockstep() {
+    let mut codec = Alternating::new(LinesCodec::new(), |line: &String| line.starts_with("REQ"));
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"REQ 1\nRES 1\nREQ 2\nRES 2\n");
+
+    for line in &["REQ 1", "RES 1", "REQ 2", "RES 2"] {
+        assert_eq!(*line, codec.decode(buf).unwrap().unwrap());
+    }
+    assert_eq!(Some(&false), codec.last_category());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn alternating_rejects_repeat_and_resets() {
+    let mut codec = Alternating::new(LinesCodec::new(), |line: &String| line.starts_with("REQ"));
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"REQ 1\nREQ 2\nRES 1\nRES 2\nRES 3\n");
+
+    assert_eq!("REQ 1", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!("RES 1", codec.decode(buf).unwrap().unwrap());
+
+    // After a reset any category is accepted once
+    codec.reset();
+    assert_eq!("RES 2", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+}
+
+fn fields(record: &[&str]) -> Vec<String> {
+    record.iter().map(|f| f.to_string()).collect()
+}
+
+#[test]
+fn csv_decodes_quoted_commas() {
+    let mut codec = Csv::new();
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"a,\"b,c\",d\r\n\n1,,\"\"\n");
+
+    assert_eq!(fields(&["a", "b,c", "d"]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(fields(&[]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(fields(&["1", "", ""]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn csv_decodes_embedded_newlines_across_calls() {
+    let mut codec = Csv::new();
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(b"x,\"first\nse");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"cond\n");
+    assert_eq!(None, codec.decode(buf).unwrap());
+    buf.extend_from_slice(b"third\"\ny\n");
+
+    assert_eq!(fields(&["x", "first\nsecond\nthird"]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(fields(&["y"]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(buf).unwrap());
+}
+
+#[test]
+fn csv_decodes_escaped_quotes() {
+    let mut codec = Csv::new();
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"\"say \"\"hi\"\"\",\"\"\"\"\n");
+
+    assert_eq!(fields(&["say \"hi\"", "\""]), codec.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn csv_rejects_malformed_quoting() {
+    let mut codec = Csv::new();
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"a\"b\n\"a\"b\nok\n\"open");
+
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!(fields(&["ok"]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(io::ErrorKind::UnexpectedEof, codec.decode_eof(buf).unwrap_err().kind());
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn csv_custom_delimiter_and_quote() {
+    let mut codec = Csv::new();
+    codec.set_delimiter(b';');
+    codec.set_quote(b'\'');
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"a;'b;''c'''\nlast;row");
+
+    assert_eq!(fields(&["a", "b;'c'"]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(fields(&["last", "row"]), codec.decode_eof(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+}
+
+#[test]
+fn csv_encodes_with_quotes_where_needed() {
+    let mut codec = Csv::new();
+    let buf = &mut BytesMut::new();
+
+    codec.encode(fields(&["plain", "a,b", "say \"hi\"", "two\nlines", ""]), buf).unwrap();
+    codec.encode(fields(&[""]), buf).unwrap();
+    codec.encode(fields(&[]), buf).unwrap();
+    assert_eq!(&b"plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\n\"\"\n\n"[..], &buf[..]);
+
+    assert_eq!(fields(&["plain", "a,b", "say \"hi\"", "two\nlines", ""]),
+               codec.decode(buf).unwrap().unwrap());
+    assert_eq!(fields(&[""]), codec.decode(buf).unwrap().unwrap());
+    assert_eq!(fields(&[]), codec.decode(buf).unwrap().unwrap());
+}
+
+#[test]
+fn byte_quota_stops_decoding_past_quota() {
+    let mut codec = ByteQuota::new(LinesCodec::new(), 10);
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"abc\ndefg\nhijk\n");
+
+    assert_eq!("abc", codec.decode(buf).unwrap().unwrap());
+    assert_eq!("defg", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(9, codec.bytes_consumed());
+    assert_eq!(1, codec.remaining_quota());
+
+    // The next frame would take the total to 14 bytes
+    let err = codec.decode(buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!(0, codec.remaining_quota());
+
+    buf.extend_from_slice(b"l\n");
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!(None, codec.decode_eof(&mut BytesMut::new()).unwrap());
+}
+
+#[test]
+fn count_errors_counts_and_passes_through() {
+    // Short lines are rejected, and decoding resumes at the next line
+    let mut codec = CountErrors::new(MinFrameLen::new(LinesCodec::new(), 3));
+    let buf = &mut BytesMut::new();
+    buf.extend_from_slice(b"abc\nx\ndef\ny\nghi\n");
+
+    assert_eq!("abc", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!("def", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(io::ErrorKind::InvalidData, codec.decode(buf).unwrap_err().kind());
+    assert_eq!("ghi", codec.decode(buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode_eof(buf).unwrap());
+
+    assert_eq!(2, codec.error_count());
+}
+
+#[test]
+fn poll_decode_from_external_buffer() {
+    let mut codec = LinesCodec::new();
+    let mut buf = BytesMut::new();
+
+    assert_eq!(None, poll_decode_from(&mut codec, &mut buf, false).unwrap());
+
+    // Bytes appended between calls are picked up
+    buf.extend_from_slice(b"one\ntw");
+    assert_eq!("one", poll_decode_from(&mut codec, &mut buf, false).unwrap().unwrap());
+    assert_eq!(None, poll_decode_from(&mut codec, &mut buf, false).unwrap());
+    buf.extend_from_slice(b"o\nthree");
+    assert_eq!("two", poll_decode_from(&mut codec, &mut buf, false).unwrap().unwrap());
+    assert_eq!(None, poll_decode_from(&mut codec, &mut buf, false).unwrap());
+
+    // At EOF the unterminated line is yielded, then the stream ends
+    assert_eq!("three", poll_decode_from(&mut codec, &mut buf, true).unwrap().unwrap());
+    assert_eq!(None, poll_decode_from(&mut codec, &mut buf, true).unwrap());
+    assert_eq!(None, poll_decode_from(&mut codec, &mut buf, true).unwrap());
+}
+
+#[test]
+fn poll_decode_from_trailing_bytes_at_eof() {
+    // Decoders relying on the default `decode_eof` report leftovers
+    let mut codec = MinFrameLen::new(BytesCodec::new(), 0);
+    let mut buf = BytesMut::new();
+    assert_eq!(None, poll_decode_from(&mut codec, &mut buf, true).unwrap());
+
+    let mut codec = SlipCodec::new();
+    buf.extend_from_slice(b"\xC0ab\xC0cd");
+    assert_eq!(&b"ab"[..], &poll_decode_from(&mut codec, &mut buf, false).unwrap().unwrap()[..]);
+    assert_eq!(None, poll_decode_from(&mut codec, &mut buf, false).unwrap());
+    assert!(poll_decode_from(&mut codec, &mut buf, true).is_err());
+}
+
+#[test]
+fn ber_short_form_length() {
+    let mut codec = BerCodec::new();
+    let mut buf = BytesMut::new();
+
+    buf.extend_from_slice(b"\x04\x05hel");
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+    buf.extend_from_slice(b"lo\x05\x00\x30");
+    assert_eq!(&b"\x04\x05hello"[..], &codec.decode(&mut buf).unwrap().unwrap()[..]);
+    assert_eq!(&b"\x05\x00"[..], &codec.decode(&mut buf).unwrap().unwrap()[..]);
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+    assert_eq!(&b"\x30"[..], &buf[..]);
+}
+
+#[test]
+fn ber_long_form_length() {
+    let mut codec = BerCodec::new();
+    let mut buf = BytesMut::new();
+
+    // Two length octets, split across reads
+    buf.extend_from_slice(b"\x30\x81");
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+    buf.extend_from_slice(b"\xC8");
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+    buf.extend_from_slice(&[0xAA; 199]);
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+    buf.extend_from_slice(b"\xAA");
+    let frame = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(203, frame.len());
+    assert_eq!(&b"\x30\x81\xC8"[..], &frame[..3]);
+
+    // Three length octets
+    buf.extend_from_slice(b"\x04\x82\x01");
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+    buf.extend_from_slice(b"\x00");
+    buf.extend_from_slice(&[0xBB; 256]);
+    let frame = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(260, frame.len());
+    assert_eq!(&b"\x04\x82\x01\x00"[..], &frame[..4]);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn ber_multi_octet_tag() {
+    let mut codec = BerCodec::new();
+    let mut buf = BytesMut::new();
+
+    buf.extend_from_slice(b"\x5F\x81");
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+    buf.extend_from_slice(b"\x01\x02ab");
+    assert_eq!(&b"\x5F\x81\x01\x02ab"[..], &codec.decode(&mut buf).unwrap().unwrap()[..]);
+}
+
+#[test]
+fn ber_invalid_lengths() {
+    let mut codec = BerCodec::new();
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(b"\x30\x80\x04\x00\x00\x00");
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+    let mut codec = BerCodec::new();
+    codec.set_max_frame_length(255);
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(b"\x04\x82\x01\x00");
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+}
+
+#[test]
+fn bit_fields_straddle_bytes_msb_first() {
+    // 10101 1011010 1001
+    let bytes = [0xAD, 0xA9];
+    let mut rd = BitReader::new(&bytes, BitOrder::MsbFirst);
+    assert_eq!(Some(0x15), rd.read(5));
+    assert_eq!(Some(0x5A), rd.read(7));
+    assert_eq!(Some(0x9), rd.read(4));
+    assert_eq!(None, rd.read(1));
+
+    let mut wr = BitWriter::new(BitOrder::MsbFirst);
+    wr.write(0x15, 5);
+    wr.write(0x5A, 7);
+    wr.write(0x9, 4);
+    assert_eq!(16, wr.bit_len());
+    assert_eq!(vec![0xAD, 0xA9], wr.into_bytes());
+}
+
+#[test]
+fn bit_fields_straddle_bytes_lsb_first() {
+    let bytes = [0x55, 0x9B];
+    let mut rd = BitReader::new(&bytes, BitOrder::LsbFirst);
+    assert_eq!(Some(0x15), rd.read(5));
+    assert_eq!(Some(0x5A), rd.read(7));
+    assert_eq!(Some(0x9), rd.read(4));
+    assert_eq!(0, rd.remaining());
+
+    let mut wr = BitWriter::new(BitOrder::LsbFirst);
+    wr.write(0x15, 5);
+    wr.write(0x5A, 7);
+    wr.write(0x9, 4);
+    assert_eq!(vec![0x55, 0x9B], wr.into_bytes());
+}
+
+#[test]
+fn bit_packed_codec() {
+    for &order in &[BitOrder::MsbFirst, BitOrder::LsbFirst] {
+        let mut codec = BitPacked::new(vec![3, 13, 2], order);
+        codec.set_length_field(1);
+        assert_eq!(3, codec.header_len());
+
+        let mut buf = BytesMut::new();
+        let frame = BitFrame {
+            fields: vec![5, 0, 3],
+            payload: BytesMut::from(&b"hello"[..]),
+        };
+        codec.encode(frame, &mut buf).unwrap();
+        assert_eq!(8, buf.len());
+
+        let mut partial = buf.split_to(6);
+        assert_eq!(None, codec.decode(&mut partial).unwrap());
+        partial.unsplit(buf);
+
+        let frame = codec.decode(&mut partial).unwrap().unwrap();
+        assert_eq!(vec![5, 5, 3], frame.fields);
+        assert_eq!(&b"hello"[..], &frame.payload[..]);
+        assert!(partial.is_empty());
+
+        let frame = BitFrame { fields: vec![8, 0, 0], payload: BytesMut::new() };
+        let err = codec.encode(frame, &mut partial).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+}
+
+#[test]
+fn bit_packed_max_frame_length() {
+    let mut codec = BitPacked::new(vec![32], BitOrder::MsbFirst);
+    codec.set_length_field(0);
+    assert_eq!(8 * 1_024 * 1_024, codec.max_frame_length());
+    codec.set_max_frame_length(4);
+
+    let mut buf = BytesMut::from(&b"\x00\x00\x00\x04abcd"[..]);
+    let frame = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(&b"abcd"[..], &frame.payload[..]);
+
+    // Rejected from the header alone, without waiting for the payload
+    let mut buf = BytesMut::from(&b"\xff\xff\xff\xff"[..]);
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+}
+
+#[test]
+fn validated_rejects_invalid_frames() {
+    let mut codec = Validated::new(LinesCodec::new(), |line: &String| {
+        if line.chars().all(|c| c.is_digit(10)) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData, "not a number"))
+        }
+    });
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(b"123\nabc\n456\n");
+
+    assert_eq!("123", codec.decode(&mut buf).unwrap().unwrap());
+
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    assert_eq!("not a number", err.to_string());
+
+    // Only the rejected frame was consumed
+    assert_eq!("456", codec.decode(&mut buf).unwrap().unwrap());
+    assert_eq!(None, codec.decode(&mut buf).unwrap());
+}
@@ -0,0 +1,73 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::{concat_readers, read_to_end, Either};
+
+use futures::{future, stream, Future, Stream};
+
+use std::io::{self, Read};
+
+#[test]
+fn reads_each_reader_in_turn() {
+    let readers = stream::iter_ok::<_, io::Error>(vec![&b"hello"[..], &b""[..], &b" wor"[..], &b"ld"[..]]);
+    let (_, data) = read_to_end(concat_readers(readers), Vec::new()).wait().unwrap();
+    assert_eq!(b"hello world", &data[..]);
+}
+
+#[test]
+fn empty_stream_is_eof() {
+    let readers = stream::iter_ok::<Vec<&[u8]>, io::Error>(Vec::new());
+    let (_, data) = read_to_end(concat_readers(readers), Vec::new()).wait().unwrap();
+    assert!(data.is_empty());
+}
+
+struct Failing;
+
+impl Read for Failing {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::Other, "reader failed"))
+    }
+}
+
+impl AsyncRead for Failing {}
+
+#[test]
+fn reader_error_is_propagated() {
+    let readers = stream::iter_ok::<_, io::Error>(vec![
+        Either::Left(&b"abc"[..]),
+        Either::Right(Failing),
+        Either::Left(&b"def"[..]),
+    ]);
+    let mut rd = concat_readers(readers);
+
+    future::lazy(|| {
+        let mut buf = [0; 8];
+        assert_eq!(3, rd.read(&mut buf).unwrap());
+        assert_eq!(b"abc", &buf[..3]);
+        let err = rd.read(&mut buf).unwrap_err();
+        assert_eq!("reader failed", err.to_string());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn stream_error_is_propagated() {
+    let readers = stream::iter_ok::<_, io::Error>(vec![&b"abc"[..]])
+        .chain(stream::once(Err(io::Error::new(io::ErrorKind::Other, "no more readers"))))
+        .chain(stream::iter_ok(vec![&b"def"[..]]));
+    let mut rd = concat_readers(readers);
+
+    future::lazy(|| {
+        let mut buf = [0; 8];
+        assert_eq!(3, rd.read(&mut buf).unwrap());
+        let err = rd.read(&mut buf).unwrap_err();
+        assert_eq!("no more readers", err.to_string());
+
+        // Reading again moves on to the next reader
+        assert_eq!(3, rd.read(&mut buf).unwrap());
+        assert_eq!(b"def", &buf[..3]);
+        assert_eq!(0, rd.read(&mut buf).unwrap());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
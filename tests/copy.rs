@@ -0,0 +1,138 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_io::io::copy;
+
+use futures::{Future, Poll};
+use futures::Async::{Ready, NotReady};
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+macro_rules! mock {
+    ($($x:expr,)*) => {{
+        let mut v = VecDeque::new();
+        v.extend(vec![$($x),*]);
+        Mock { calls: v, written: vec![] }
+    }};
+}
+
+#[test]
+fn on_idle_when_reader_blocks() {
+    let rd = mock! {
+        Ok(b"hello".to_vec()),
+        Err(would_block()),
+        Ok(b" world".to_vec()),
+    };
+    let wr = mock! {};
+
+    let idle = Cell::new(0);
+    let mut copy = copy(rd, wr).on_idle(|| idle.set(idle.get() + 1));
+
+    assert!(!copy.poll().unwrap().is_ready());
+    assert_eq!(1, idle.get());
+
+    match copy.poll().unwrap() {
+        Ready((n, _, wr)) => {
+            assert_eq!(11, n);
+            assert_eq!(&wr.written[..], &b"hello world"[..]);
+        }
+        NotReady => panic!(),
+    }
+    assert_eq!(1, idle.get());
+}
+
+#[test]
+fn on_idle_not_fired_while_writes_pending() {
+    let rd = mock! {
+        Ok(b"hello".to_vec()),
+    };
+    let wr = mock! {
+        Err(would_block()),
+    };
+
+    let idle = Cell::new(0);
+    let mut copy = copy(rd, wr).on_idle(|| idle.set(idle.get() + 1));
+
+    assert!(!copy.poll().unwrap().is_ready());
+    assert_eq!(0, idle.get());
+    assert!(copy.poll().unwrap().is_ready());
+    assert_eq!(0, idle.get());
+}
+
+#[test]
+fn retries_interrupted_writes() {
+    let rd = mock! {
+        Ok(b"hello".to_vec()),
+    };
+    let wr = mock! {
+        Err(interrupted()),
+        Err(interrupted()),
+    };
+
+    match copy(rd, wr).poll().unwrap() {
+        Ready((n, _, wr)) => {
+            assert_eq!(5, n);
+            assert_eq!(&wr.written[..], &b"hello"[..]);
+        }
+        NotReady => panic!(),
+    }
+}
+
+// ===== Mock ======
+
+fn would_block() -> io::Error {
+    io::Error::new(io::ErrorKind::WouldBlock, "would block")
+}
+
+fn interrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "interrupted")
+}
+
+#[derive(Debug)]
+struct Mock {
+    calls: VecDeque<io::Result<Vec<u8>>>,
+    written: Vec<u8>,
+}
+
+impl Read for Mock {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        match self.calls.pop_front() {
+            Some(Ok(data)) => {
+                debug_assert!(dst.len() >= data.len());
+                dst[..data.len()].copy_from_slice(&data[..]);
+                Ok(data.len())
+            }
+            Some(Err(e)) => Err(e),
+            None => Ok(0),
+        }
+    }
+}
+
+impl AsyncRead for Mock {
+}
+
+impl Write for Mock {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        match self.calls.pop_front() {
+            Some(Ok(_)) => panic!(),
+            Some(Err(e)) => Err(e),
+            None => {
+                self.written.extend_from_slice(src);
+                Ok(src.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Mock {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Ready(()))
+    }
+}
@@ -0,0 +1,120 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_io::io::Either;
+
+use bytes::BytesMut;
+use futures::{Async, Poll};
+
+use std::io::{self, Read, Write};
+
+// Reads `data` and records writes; leaves read buffers uninitialized
+struct Mock {
+    data: &'static [u8],
+    written: Vec<u8>,
+    shutdown: bool,
+}
+
+impl Read for Mock {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = ::std::cmp::min(buf.len(), self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+impl AsyncRead for Mock {
+    unsafe fn prepare_uninitialized_buffer(&self, _: &mut [u8]) -> bool {
+        false
+    }
+}
+
+impl Write for Mock {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Mock {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.shutdown = true;
+        Ok(Async::Ready(()))
+    }
+}
+
+// Always blocks
+struct Blocking;
+
+impl Read for Blocking {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"))
+    }
+}
+
+impl AsyncRead for Blocking {}
+
+impl Write for Blocking {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Blocking {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Async::NotReady)
+    }
+}
+
+#[test]
+fn either_left_forwards() {
+    let mut io: Either<Mock, Blocking> = Either::Left(Mock {
+        data: b"hello",
+        written: Vec::new(),
+        shutdown: false,
+    });
+
+    let mut buf = [0xff; 8];
+    assert!(!unsafe { io.prepare_uninitialized_buffer(&mut buf) });
+    assert_eq!(buf, [0xff; 8]);
+
+    let mut buf = BytesMut::with_capacity(16);
+    assert_eq!(Async::Ready(5), io.read_buf(&mut buf).unwrap());
+    assert_eq!(&buf[..], b"hello");
+
+    assert_eq!(Async::Ready(5), io.write_buf(&mut io::Cursor::new(b"world")).unwrap());
+    assert_eq!(Async::Ready(()), io.shutdown().unwrap());
+
+    match io {
+        Either::Left(ref mock) => {
+            assert_eq!(&mock.written[..], b"world");
+            assert!(mock.shutdown);
+        }
+        Either::Right(_) => panic!("variant changed"),
+    }
+}
+
+#[test]
+fn either_right_forwards() {
+    let mut io: Either<Mock, Blocking> = Either::Right(Blocking);
+
+    let mut buf = [0xff; 8];
+    assert!(unsafe { io.prepare_uninitialized_buffer(&mut buf) });
+    assert_eq!(buf, [0; 8]);
+
+    let mut buf = BytesMut::with_capacity(16);
+    assert_eq!(Async::NotReady, io.read_buf(&mut buf).unwrap());
+    assert_eq!(Async::NotReady, io.write_buf(&mut io::Cursor::new(b"world")).unwrap());
+    assert_eq!(Async::NotReady, io.shutdown().unwrap());
+}
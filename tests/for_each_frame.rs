@@ -0,0 +1,62 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::codec::{Framed, FramedParts, LinesCodec};
+
+use bytes::BytesMut;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Counts every allocation made by the process
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[test]
+fn for_each_frame_does_not_allocate_per_frame() {
+    const FRAMES: usize = 10_000;
+
+    let mut data = Vec::new();
+    for i in 0..FRAMES {
+        data.extend_from_slice(format!("line {}\n", i % 10).as_bytes());
+    }
+
+    let parts = FramedParts {
+        inner: Cursor::new(data),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+    let mut framed = Framed::from_parts(parts, LinesCodec::new());
+
+    let mut count = 0;
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let res = framed.for_each_frame(|line| {
+        assert!(line.starts_with(b"line "));
+        count += 1;
+        Ok(())
+    });
+    let allocations = ALLOCATIONS.load(Ordering::SeqCst) - before;
+
+    assert!(res.unwrap().is_ready());
+    assert_eq!(FRAMES, count);
+
+    // Only the read buffer may be reallocated now and then
+    assert!(allocations < FRAMES / 100, "allocations={}", allocations);
+}
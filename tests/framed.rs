@@ -2,10 +2,10 @@ extern crate tokio_io;
 extern crate bytes;
 extern crate futures;
 
-use futures::{Stream, Future};
-use std::io::{self, Read};
-use tokio_io::codec::{Framed, FramedParts, Decoder, Encoder};
-use tokio_io::AsyncRead;
+use futures::{future, Async, Future, Poll, Sink, Stream};
+use std::io::{self, Cursor, Read, Write};
+use tokio_io::codec::{framed_split, ErrorDirection, Framed, Framed2, FramedParts, FramedRead, FramedWrite, LinesCodec, WithRaw, Decoder, Encoder};
+use tokio_io::{AsyncRead, AsyncWrite};
 use bytes::{BytesMut, Buf, BufMut, IntoBuf, BigEndian};
 
 const INITIAL_CAPACITY: usize = 8 * 1024;
@@ -69,6 +69,16 @@ fn can_read_from_existing_buf() {
     assert_eq!(num, 42);
 }
 
+#[test]
+fn new_reads_and_writes() {
+    let mut framed = Framed::new(Cursor::new(vec![0, 0, 0, 42]), U32Codec);
+
+    assert_eq!(Async::Ready(Some(42)), framed.poll().unwrap());
+    assert!(framed.start_send(7).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(&framed.get_ref().get_ref()[..], &[0, 0, 0, 42, 0, 0, 0, 7][..]);
+}
+
 #[test]
 fn external_buf_grows_to_init() {
     let parts = FramedParts {
@@ -95,3 +105,863 @@ fn external_buf_does_not_shrink() {
     assert_eq!(readbuf.capacity(), INITIAL_CAPACITY * 2);
 }
 
+
+#[test]
+fn paused_yields_buffered_frames_only() {
+    let parts = FramedParts {
+        inner: DontReadIntoThis,
+        readbuf: vec![0, 0, 0, 42, 0, 0, 0, 43].into(),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec);
+    framed.pause_reading(true);
+
+    future::lazy(|| {
+        assert_eq!(Async::Ready(Some(42)), framed.poll().unwrap());
+        assert_eq!(Async::Ready(Some(43)), framed.poll().unwrap());
+        assert_eq!(Async::NotReady, framed.poll().unwrap());
+
+        framed.pause_reading(false);
+        assert_eq!(io::ErrorKind::Other, framed.poll().unwrap_err().kind());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn max_in_flight_pauses_until_release() {
+    let parts = FramedParts {
+        inner: DontReadIntoThis,
+        readbuf: vec![0, 0, 0, 42, 0, 0, 0, 43, 0, 0, 0, 44].into(),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec).with_max_in_flight(2);
+
+    future::lazy(|| {
+        let first = match framed.poll().unwrap() {
+            Async::Ready(Some((42, release))) => release,
+            _ => panic!("expected a frame"),
+        };
+        let second = match framed.poll().unwrap() {
+            Async::Ready(Some((43, release))) => release,
+            _ => panic!("expected a frame"),
+        };
+        assert_eq!(2, framed.in_flight());
+        assert!(framed.poll().unwrap().is_not_ready());
+
+        first.release();
+        let third = match framed.poll().unwrap() {
+            Async::Ready(Some((44, release))) => release,
+            _ => panic!("expected a frame"),
+        };
+        assert!(framed.poll().unwrap().is_not_ready());
+
+        drop(second);
+        drop(third);
+        assert_eq!(0, framed.in_flight());
+        assert_eq!(io::ErrorKind::Other, framed.poll().unwrap_err().kind());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn max_in_flight_released_inside_combinator() {
+    let framed = Framed::new(Cursor::new(vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]), U32Codec);
+
+    // Each handle is dropped as the frame is processed, after the stream was
+    // moved into the combinator
+    let frames = framed.with_max_in_flight(1)
+        .map(|(n, release)| {
+            drop(release);
+            n
+        })
+        .collect()
+        .wait()
+        .unwrap();
+    assert_eq!(vec![1, 2, 3], frames);
+}
+
+#[test]
+fn poll_ready_reports_each_direction() {
+    // Blocks on reads until `readable` is set and on writes until `writable`
+    // is set
+    struct ReadyMock {
+        readable: bool,
+        writable: bool,
+    }
+
+    impl Read for ReadyMock {
+        fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+            if !self.readable {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+            }
+            dst[..4].copy_from_slice(&[0, 0, 0, 42]);
+            Ok(4)
+        }
+    }
+
+    impl AsyncRead for ReadyMock {}
+
+    impl Write for ReadyMock {
+        fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+            if !self.writable {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+            }
+            Ok(src.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl AsyncWrite for ReadyMock {
+        fn shutdown(&mut self) -> Poll<(), io::Error> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    let parts = FramedParts {
+        inner: ReadyMock { readable: false, writable: false },
+        readbuf: BytesMut::with_capacity(0),
+        writebuf: vec![0; INITIAL_CAPACITY].into(),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec);
+
+    future::lazy(|| {
+        assert_eq!(Async::NotReady, framed.poll_ready().unwrap());
+
+        framed.get_mut().readable = true;
+        match framed.poll_ready().unwrap() {
+            Async::Ready(ready) => {
+                assert!(ready.is_readable());
+                assert!(!ready.is_writable());
+            }
+            Async::NotReady => panic!("expected to be readable"),
+        }
+
+        // Probing readiness doesn't consume the frame
+        framed.get_mut().readable = false;
+        framed.get_mut().writable = true;
+        match framed.poll_ready().unwrap() {
+            Async::Ready(ready) => {
+                assert!(ready.is_readable());
+                assert!(ready.is_writable());
+            }
+            Async::NotReady => panic!("expected to be ready"),
+        }
+        assert_eq!(Async::Ready(Some(42)), framed.poll().unwrap());
+        assert_eq!(Async::NotReady, framed.poll().unwrap());
+
+        match framed.poll_ready().unwrap() {
+            Async::Ready(ready) => {
+                assert!(!ready.is_readable());
+                assert!(ready.is_writable());
+            }
+            Async::NotReady => panic!("expected to be writable"),
+        }
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn ensure_flushed_waits_for_flush() {
+    struct FlushMock {
+        written: Vec<u8>,
+        flush_calls: usize,
+        flushed: bool,
+    }
+
+    impl Write for FlushMock {
+        fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(src);
+            Ok(src.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flush_calls += 1;
+
+            // Block on the first flush
+            if self.flush_calls == 1 {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"));
+            }
+
+            self.flushed = true;
+            Ok(())
+        }
+    }
+
+    impl AsyncWrite for FlushMock {
+        fn shutdown(&mut self) -> Poll<(), io::Error> {
+            panic!("ensure_flushed must not shut down");
+        }
+    }
+
+    let parts = FramedParts {
+        inner: FlushMock { written: vec![], flush_calls: 0, flushed: false },
+        readbuf: BytesMut::with_capacity(0),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec);
+
+    assert!(framed.start_send(42).unwrap().is_ready());
+    assert_eq!(Async::NotReady, framed.ensure_flushed().unwrap());
+    assert!(!framed.get_ref().flushed);
+    assert_eq!(Async::Ready(()), framed.ensure_flushed().unwrap());
+    assert!(framed.get_ref().flushed);
+    assert_eq!(&framed.get_ref().written[..], &[0, 0, 0, 42][..]);
+}
+
+#[test]
+fn encode_into_buffer_returns_encoded_len() {
+    // Encodes frames with a big-endian u16 length prefix, with no `Decoder`
+    // implementation
+    struct LengthPrefixed;
+
+    impl Encoder for LengthPrefixed {
+        type Item = &'static [u8];
+        type Error = io::Error;
+
+        fn encode(&mut self, item: &'static [u8], dst: &mut BytesMut) -> io::Result<()> {
+            dst.reserve(2 + item.len());
+            dst.put_u16::<BigEndian>(item.len() as u16);
+            dst.put_slice(item);
+            Ok(())
+        }
+    }
+
+    let mut framed = Framed::from_parts(FramedParts {
+        inner: Cursor::new(Vec::new()),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    }, LengthPrefixed);
+
+    assert_eq!(framed.encode_into_buffer(b"hello").unwrap(), 2 + 5);
+    assert_eq!(framed.encode_into_buffer(b"").unwrap(), 2);
+    assert_eq!(framed.write_buffer_len(), 9);
+
+    // Nothing is written until the buffer is flushed
+    assert!(framed.get_ref().get_ref().is_empty());
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(framed.write_buffer_len(), 0);
+    assert_eq!(&framed.get_ref().get_ref()[..], b"\x00\x05hello\x00\x00");
+}
+
+#[test]
+fn with_errors_continues_after_decode_error() {
+    // Decodes single byte frames, rejecting (and skipping) zero bytes
+    struct NonZeroCodec;
+
+    impl Decoder for NonZeroCodec {
+        type Item = u8;
+        type Error = io::Error;
+
+        fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<u8>> {
+            if buf.is_empty() {
+                return Ok(None);
+            }
+
+            match buf.split_to(1)[0] {
+                0 => Err(io::Error::new(io::ErrorKind::InvalidData, "zero byte")),
+                n => Ok(Some(n)),
+            }
+        }
+    }
+
+    let parts = FramedParts {
+        inner: &[][..],
+        readbuf: vec![1, 0, 2].into(),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut stream = Framed::from_parts(parts, NonZeroCodec).with_errors();
+
+    match stream.poll().unwrap() {
+        Async::Ready(Some(Ok(1))) => {}
+        _ => panic!(),
+    }
+    match stream.poll().unwrap() {
+        Async::Ready(Some(Err(e))) => assert_eq!(io::ErrorKind::InvalidData, e.kind()),
+        _ => panic!(),
+    }
+    match stream.poll().unwrap() {
+        Async::Ready(Some(Ok(2))) => {}
+        _ => panic!(),
+    }
+    match stream.poll().unwrap() {
+        Async::Ready(None) => {}
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn with_raw_yields_consumed_bytes() {
+    let parts = FramedParts {
+        inner: &[][..],
+        readbuf: vec![0, 0, 0, 42, 0, 0, 1, 0].into(),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec).with_raw();
+
+    match framed.poll().unwrap() {
+        Async::Ready(Some((42, ref raw))) => assert_eq!(&raw[..], &[0, 0, 0, 42][..]),
+        _ => panic!(),
+    }
+    match framed.poll().unwrap() {
+        Async::Ready(Some((256, ref raw))) => assert_eq!(&raw[..], &[0, 0, 1, 0][..]),
+        _ => panic!(),
+    }
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+fn with_raw_frame_split_across_reads() {
+    let mut codec = WithRaw::new(U32Codec);
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(&[0, 0]);
+    assert!(codec.decode(buf).unwrap().is_none());
+
+    buf.extend_from_slice(&[0, 42, 0]);
+    let (n, raw) = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(42, n);
+    assert_eq!(&raw[..], &[0, 0, 0, 42][..]);
+
+    buf.extend_from_slice(&[0, 1, 0]);
+    let (n, raw) = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(256, n);
+    assert_eq!(&raw[..], &[0, 0, 1, 0][..]);
+    assert!(codec.decode(buf).unwrap().is_none());
+}
+
+#[test]
+fn with_raw_keeps_bytes_consumed_while_waiting() {
+    // Strips a one byte length as soon as it arrives, then waits for the
+    // payload
+    struct LenPrefixed(Option<usize>);
+
+    impl Decoder for LenPrefixed {
+        type Item = BytesMut;
+        type Error = io::Error;
+
+        fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+            let len = match self.0 {
+                Some(len) => len,
+                None if buf.is_empty() => return Ok(None),
+                None => buf.split_to(1)[0] as usize,
+            };
+
+            if buf.len() < len {
+                self.0 = Some(len);
+                return Ok(None);
+            }
+
+            self.0 = None;
+            Ok(Some(buf.split_to(len)))
+        }
+    }
+
+    let mut codec = WithRaw::new(LenPrefixed(None));
+    let buf = &mut BytesMut::new();
+
+    buf.extend_from_slice(b"\x04ab");
+    assert!(codec.decode(buf).unwrap().is_none());
+
+    buf.extend_from_slice(b"cd\x01z");
+    let (frame, raw) = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(&frame[..], b"abcd");
+    assert_eq!(&raw[..], b"\x04abcd");
+
+    let (frame, raw) = codec.decode(buf).unwrap().unwrap();
+    assert_eq!(&frame[..], b"z");
+    assert_eq!(&raw[..], b"\x01z");
+    assert!(codec.decode(buf).unwrap().is_none());
+}
+
+#[test]
+fn profile_decode_reports_elapsed_time() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // Each reading of the mock clock advances it by 250ns
+    let now = Rc::new(Cell::new(1_000));
+    let clock = {
+        let now = now.clone();
+        move || {
+            let t = now.get();
+            now.set(t + 250);
+            t
+        }
+    };
+    let timings = Rc::new(Cell::new(Vec::new()));
+    let sink = {
+        let timings = timings.clone();
+        move |nanos| {
+            let mut v = timings.take();
+            v.push(nanos);
+            timings.set(v);
+        }
+    };
+
+    let parts = FramedParts {
+        inner: &[][..],
+        readbuf: vec![0, 0, 0, 42, 0, 0].into(),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec).profile_decode(clock, sink);
+
+    assert_eq!(Async::Ready(Some(42)), framed.poll().unwrap());
+    assert!(framed.poll().is_err());
+
+    // One successful decode, one which needed more data, and the decode at
+    // EOF which found a partial frame
+    assert_eq!(vec![250, 250, 250], timings.take());
+    assert_eq!(2_500, now.get());
+}
+
+#[test]
+fn eof_marker_yielded_once_before_none() {
+    let parts = FramedParts {
+        inner: &[0, 0, 0, 2][..],
+        readbuf: vec![0, 0, 0, 1].into(),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut framed = Framed::from_parts(parts, U32Codec).with_eof_marker(|| 0xffff_ffff);
+
+    assert_eq!(Async::Ready(Some(1)), framed.poll().unwrap());
+    assert_eq!(Async::Ready(Some(2)), framed.poll().unwrap());
+    assert_eq!(Async::Ready(Some(0xffff_ffff)), framed.poll().unwrap());
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+fn framed_split_reads_and_writes_separately() {
+    let rd = &[0, 0, 0, 42][..];
+    let wr = Cursor::new(Vec::new());
+    let mut framed = framed_split(rd, wr, U32Codec);
+
+    assert_eq!(Async::Ready(Some(42)), framed.poll().unwrap());
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+
+    assert!(framed.start_send(7).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    let (_, wr) = framed.into_inner();
+    assert_eq!(&wr.into_inner()[..], &[0, 0, 0, 7][..]);
+}
+
+#[test]
+fn framed2_from_read_write_keeps_buffers() {
+    let mut read = FramedRead::new(&[0, 0, 0, 2][..], U32Codec);
+    read.set_read_buffer(BytesMut::from(&[0, 0, 0, 1][..]));
+
+    let mut write = FramedWrite::new(Cursor::new(Vec::new()), LinesCodec::new());
+    assert!(write.start_send("queued".to_string()).unwrap().is_ready());
+
+    let mut framed = Framed2::from_read_write(read, write);
+
+    assert_eq!(Async::Ready(Some(1)), framed.poll().unwrap());
+    assert_eq!(Async::Ready(Some(2)), framed.poll().unwrap());
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+
+    assert!(framed.start_send("sent".to_string()).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    let (_, wr) = framed.into_inner();
+    assert_eq!(&wr.into_inner()[..], &b"queued\nsent\n"[..]);
+}
+
+#[test]
+fn framed2_from_read_write_keeps_settings() {
+    // Returns a spurious zero length read before its data
+    struct Spurious(Vec<Vec<u8>>);
+
+    impl Read for Spurious {
+        fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Ok(0);
+            }
+            let chunk = self.0.remove(0);
+            dst[..chunk.len()].copy_from_slice(&chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    impl AsyncRead for Spurious {}
+
+    // Never accepts any data
+    struct Blocked;
+
+    impl Write for Blocked {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl AsyncWrite for Blocked {
+        fn shutdown(&mut self) -> Poll<(), io::Error> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    let mut read = FramedRead::new(Spurious(vec![vec![], vec![0, 0, 0, 3]]), U32Codec);
+    read.set_strict_eof(true);
+
+    let mut write = FramedWrite::new(Blocked, LinesCodec::new());
+    write.set_max_pending_frames(1);
+
+    let mut framed = Framed2::from_read_write(read, write);
+
+    // The spurious EOF doesn't end the stream
+    assert_eq!(Async::Ready(Some(3)), framed.poll().unwrap());
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+
+    // The second frame waits for the first to be written
+    assert!(framed.start_send("one".to_string()).unwrap().is_ready());
+    assert!(framed.start_send("two".to_string()).unwrap().is_not_ready());
+}
+
+#[test]
+fn collect_frames_returns_reader() {
+    // Yields the data in small chunks
+    struct Chunked {
+        chunks: Vec<&'static [u8]>,
+    }
+
+    impl Read for Chunked {
+        fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+            if self.chunks.is_empty() {
+                return Ok(0);
+            }
+            let chunk = self.chunks.remove(0);
+            dst[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    impl AsyncRead for Chunked {}
+
+    let parts = FramedParts {
+        inner: Chunked { chunks: vec![b"\x00\x00", b"\x00\x01\x00\x00\x00", b"\x02\x00\x00\x00\x03"] },
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+
+    let (frames, rd) = Framed::from_parts(parts, U32Codec).collect_frames().wait().unwrap();
+    assert_eq!(frames, vec![1, 2, 3]);
+    assert!(rd.chunks.is_empty());
+}
+
+#[test]
+fn collect_frames_stops_at_error() {
+    let parts = FramedParts {
+        inner: Cursor::new(b"\x00\x00\x00\x01\x00\x00".to_vec()),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+
+    // Leftover bytes at EOF
+    let err = Framed::from_parts(parts, U32Codec).collect_frames().wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+fn into_parts_hands_off_leftover_bytes() {
+    use tokio_io::io::read_to_end;
+
+    // One frame followed by raw bytes, all read off the wire at once
+    let inner = Cursor::new(b"\x00\x00\x00\x2araw body".to_vec());
+    let mut framed = Framed::new(inner, U32Codec);
+    assert_eq!(Async::Ready(Some(42)), framed.poll().unwrap());
+
+    let parts = framed.into_parts();
+    assert_eq!(&parts.readbuf[..], b"raw body");
+    assert_eq!(parts.inner.position(), 12);
+
+    let raw = Read::chain(Cursor::new(parts.readbuf), parts.inner);
+    let (_, body) = read_to_end(raw, Vec::new()).wait().unwrap();
+    assert_eq!(&body[..], b"raw body");
+}
+
+fn interleaved_keys() -> Cursor<Vec<u8>> {
+    // The high half of each frame is its key
+    let mut data = vec![];
+    for &n in &[0x1_0001u32, 0x2_0001, 0x2_0002, 0x1_0002, 0x2_0003] {
+        data.extend_from_slice(&[(n >> 24) as u8, (n >> 16) as u8, (n >> 8) as u8, n as u8]);
+    }
+    Cursor::new(data)
+}
+
+#[test]
+fn route_by_tags_frames_in_order() {
+    let parts = FramedParts {
+        inner: interleaved_keys(),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+
+    let frames = Framed::from_parts(parts, U32Codec)
+        .route_by(|n: &u32| *n >> 16)
+        .collect()
+        .wait()
+        .unwrap();
+
+    assert_eq!(frames, vec![(1, 0x1_0001), (2, 0x2_0001), (2, 0x2_0002), (1, 0x1_0002), (2, 0x2_0003)]);
+}
+
+#[test]
+fn route_by_poll_key_buffers_other_keys() {
+    let parts = FramedParts {
+        inner: interleaved_keys(),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+
+    let mut router = Framed::from_parts(parts, U32Codec).route_by(|n: &u32| *n >> 16);
+
+    // Skipping ahead to key 1 buffers the frames for key 2, in order
+    assert_eq!(Async::Ready(Some(0x1_0001)), router.poll_key(&1).unwrap());
+    assert_eq!(Async::Ready(Some(0x1_0002)), router.poll_key(&1).unwrap());
+    assert_eq!(2, router.buffered_len());
+    assert_eq!(Async::Ready(Some(0x2_0001)), router.poll_key(&2).unwrap());
+    assert_eq!(Async::Ready(None), router.poll_key(&1).unwrap());
+
+    assert_eq!(Async::Ready(Some((2, 0x2_0002))), router.poll().unwrap());
+    assert_eq!(Async::Ready(Some((2, 0x2_0003))), router.poll().unwrap());
+    assert_eq!(Async::Ready(None), router.poll().unwrap());
+}
+
+#[test]
+fn for_each_frame_borrows_lines() {
+    use tokio_io::codec::LinesCodec;
+
+    let parts = FramedParts {
+        inner: Cursor::new(b"one\r\ntwo\n\nthree".to_vec()),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+    let mut framed = Framed::from_parts(parts, LinesCodec::new());
+
+    let mut lines = vec![];
+    let res = framed.for_each_frame(|line| {
+        lines.push(line.to_vec());
+        Ok(())
+    });
+    assert!(res.unwrap().is_ready());
+    assert_eq!(lines, vec![b"one".to_vec(), b"two".to_vec(), vec![], b"three".to_vec()]);
+}
+
+#[test]
+fn for_each_frame_after_stream_poll() {
+    use tokio_io::codec::LinesCodec;
+
+    let parts = FramedParts {
+        inner: Cursor::new(b"one\ntwo\nthree\n".to_vec()),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+    let mut framed = Framed::from_parts(parts, LinesCodec::new());
+
+    // Every line is buffered by the first read, only one is taken here
+    assert_eq!(Async::Ready(Some("one".to_string())), framed.poll().unwrap());
+
+    let mut lines = vec![];
+    let res = framed.for_each_frame(|line| {
+        lines.push(line.to_vec());
+        Ok(())
+    });
+    assert!(res.unwrap().is_ready());
+    assert_eq!(lines, vec![b"two".to_vec(), b"three".to_vec()]);
+}
+
+#[test]
+fn for_each_frame_stops_at_callback_error() {
+    use tokio_io::codec::LinesCodec;
+
+    let parts = FramedParts {
+        inner: Cursor::new(b"ok\nbad\nlater\n".to_vec()),
+        readbuf: BytesMut::new(),
+        writebuf: BytesMut::new(),
+    };
+    let mut framed = Framed::from_parts(parts, LinesCodec::new());
+
+    let err = framed.for_each_frame(|line| {
+        if line == b"bad" {
+            Err(io::Error::new(io::ErrorKind::InvalidData, "bad line"))
+        } else {
+            Ok(())
+        }
+    }).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+    // The failed frame was consumed, the following ones are still there
+    assert_eq!(Async::Ready(Some("later".to_string())), framed.poll().unwrap());
+}
+
+#[test]
+fn transform_round_trip() {
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: u16,
+        y: u16,
+    }
+
+    fn decode(n: u32) -> Point {
+        Point { x: (n >> 16) as u16, y: n as u16 }
+    }
+
+    fn encode(p: Point) -> u32 {
+        (p.x as u32) << 16 | p.y as u32
+    }
+
+    let framed = Framed::new(Cursor::new(Vec::new()), U32Codec).transform(decode, encode);
+    let framed = framed.send(Point { x: 1, y: 2 }).wait().unwrap();
+    let framed = framed.send(Point { x: 3, y: 4 }).wait().unwrap();
+    let data = framed.into_inner().into_inner();
+    assert_eq!(vec![0, 1, 0, 2, 0, 3, 0, 4], data);
+
+    let framed = Framed::new(Cursor::new(data), U32Codec).transform(decode, encode);
+    let points = framed.collect().wait().unwrap();
+    assert_eq!(vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }], points);
+}
+
+#[test]
+fn one_at_a_time_waits_for_ack() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Counting(Rc<Cell<usize>>);
+
+    impl Decoder for Counting {
+        type Item = u32;
+        type Error = io::Error;
+
+        fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<u32>> {
+            self.0.set(self.0.get() + 1);
+            U32Codec.decode(buf)
+        }
+    }
+
+    let decodes = Rc::new(Cell::new(0));
+    let parts = FramedParts {
+        inner: &[][..],
+        readbuf: vec![0, 0, 0, 1, 0, 0, 0, 2].into(),
+        writebuf: BytesMut::with_capacity(0),
+    };
+    let mut framed = Framed::from_parts(parts, Counting(decodes.clone())).one_at_a_time();
+
+    future::lazy(|| {
+        let (first, ack) = match framed.poll().unwrap() {
+            Async::Ready(Some(frame)) => frame,
+            _ => panic!("expected a frame"),
+        };
+        assert_eq!(1, first);
+        assert_eq!(1, decodes.get());
+
+        // The second frame is buffered, but isn't decoded until the ack
+        assert!(framed.awaiting_ack());
+        assert!(framed.poll().unwrap().is_not_ready());
+        assert!(framed.poll().unwrap().is_not_ready());
+        assert_eq!(1, decodes.get());
+
+        ack.ack();
+        assert!(!framed.awaiting_ack());
+        match framed.poll().unwrap() {
+            Async::Ready(Some((2, _))) => {}
+            _ => panic!("expected the second frame"),
+        }
+        assert_eq!(2, decodes.get());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
+
+#[test]
+fn chunk_frames_exact_multiple() {
+    let framed = Framed::new(Cursor::new(vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]), U32Codec);
+    let batches = framed.chunk_frames(2).collect().wait().unwrap();
+    assert_eq!(vec![vec![1, 2], vec![3, 4]], batches);
+}
+
+#[test]
+fn chunk_frames_trailing_partial_batch() {
+    let framed = Framed::new(Cursor::new(vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]), U32Codec);
+    let mut framed = framed.chunk_frames(2);
+    assert_eq!(Async::Ready(Some(vec![1, 2])), framed.poll().unwrap());
+    assert_eq!(Async::Ready(Some(vec![3])), framed.poll().unwrap());
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+    assert_eq!(Async::Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+fn on_error_reports_decode_error_context() {
+    // Decodes single byte frames, failing on 0xff without consuming it
+    struct RejectFfCodec;
+
+    impl Decoder for RejectFfCodec {
+        type Item = u8;
+        type Error = io::Error;
+
+        fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<u8>> {
+            match buf.first() {
+                None => Ok(None),
+                Some(&0xff) => Err(io::Error::new(io::ErrorKind::InvalidData, "0xff byte")),
+                Some(_) => Ok(Some(buf.split_to(1)[0])),
+            }
+        }
+    }
+
+    let mut data = vec![1, 0xff, 0xde, 0xad, 0xbe, 0xef];
+    data.extend(vec![0; 10]);
+
+    let mut reports = Vec::new();
+    {
+        let parts = FramedParts {
+            inner: &[][..],
+            readbuf: data.into(),
+            writebuf: BytesMut::with_capacity(0),
+        };
+        let mut framed = Framed::from_parts(parts, RejectFfCodec).on_error(|cx| {
+            reports.push((cx.direction(), cx.buffer_len(), cx.hex_preview()));
+        });
+        framed.set_preview_len(5);
+
+        assert_eq!(Async::Ready(Some(1)), framed.poll().unwrap());
+        let err = framed.poll().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    assert_eq!(vec![(ErrorDirection::Decode, 15, "ff de ad be ef".to_string())], reports);
+}
+
+#[test]
+fn framed_types_named_without_decoder_bound() {
+    // Generic code naming these types doesn't need `U: Decoder`
+    fn write_buffer_len<T, U>(framed: &Framed<T, U>) -> usize {
+        framed.write_buffer_len()
+    }
+
+    fn decoder<T, D>(framed: &FramedRead<T, D>) -> &D {
+        framed.decoder()
+    }
+
+    let framed = Framed::new(Cursor::new(Vec::new()), U32Codec);
+    assert_eq!(0, write_buffer_len(&framed));
+
+    let framed = FramedRead::new(Cursor::new(Vec::new()), U32Codec);
+    decoder(&framed);
+}
+
+#[test]
+fn framed_types_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let source = |min: usize| BytesMut::with_capacity(min);
+    let framed = Framed::with_buffer_source(Cursor::new(Vec::new()), LinesCodec::new(), source);
+    assert_send_sync(&framed);
+
+    let framed = FramedRead::with_buffer_source(Cursor::new(Vec::new()), LinesCodec::new(), source);
+    assert_send_sync(&framed);
+}
@@ -3,14 +3,16 @@ extern crate bytes;
 extern crate futures;
 
 use tokio_io::AsyncRead;
-use tokio_io::codec::{FramedRead, Decoder};
+use tokio_io::codec::{BatchDecoder, Batched, FramedRead, Decoder, LinesCodec};
 
 use bytes::{BytesMut, Buf, IntoBuf, BigEndian};
 use futures::Stream;
 use futures::Async::{Ready, NotReady};
 
-use std::io::{self, Read};
+use std::io::{self, Cursor, Read};
 use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 macro_rules! mock {
     ($($x:expr,)*) => {{
@@ -191,6 +193,164 @@ fn multi_frames_on_eof() {
     assert_eq!(Ready(None), framed.poll().unwrap());
 }
 
+#[test]
+fn read_buffer_shrinks_after_large_frame() {
+    fn read_lines(shrink: bool) -> usize {
+        let mut data = vec![b'x'; 100_000];
+        data.extend_from_slice(b"\na\nb\nc\n");
+
+        let mut framed = FramedRead::new(Cursor::new(data), LinesCodec::new());
+        if shrink {
+            framed.set_read_buffer_shrink_threshold(Some(16 * 1024));
+            framed.set_read_buffer_baseline_capacity(1024);
+        }
+
+        match framed.poll().unwrap() {
+            Ready(Some(line)) => assert_eq!(line.len(), 100_000),
+            _ => panic!("expected a line"),
+        }
+        assert_eq!(Ready(Some("a".to_string())), framed.poll().unwrap());
+        assert_eq!(Ready(Some("b".to_string())), framed.poll().unwrap());
+        assert_eq!(Ready(Some("c".to_string())), framed.poll().unwrap());
+        framed.read_buffer_capacity()
+    }
+
+    // The buffer grew past the threshold to fit the long line
+    assert!(read_lines(false) > 16 * 1024);
+    assert!(read_lines(true) <= 1024);
+}
+
+#[test]
+fn read_decode_many_batches() {
+    // Unpacks each 3 byte record into 3 items
+    struct Records;
+
+    impl BatchDecoder for Records {
+        type Item = u8;
+        type Error = io::Error;
+
+        fn decode_many(&mut self, buf: &mut BytesMut, dst: &mut Vec<u8>) -> io::Result<usize> {
+            if buf.len() < 3 {
+                return Ok(0);
+            }
+
+            dst.extend_from_slice(&buf.split_to(3));
+            Ok(3)
+        }
+    }
+
+    let mock = mock! {
+        Ok(b"\x01\x02\x03\x04"[..].into()),
+        Ok(b"\x05\x06"[..].into()),
+    };
+    let mut framed = FramedRead::new(mock, Batched::new(Records));
+
+    for i in 1..7 {
+        assert_eq!(Ready(Some(i)), framed.poll().unwrap());
+    }
+    assert_eq!(Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+fn read_with_buffer_source() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x00".to_vec()),
+        Ok(b"\x00\x00\x00\x01".to_vec()),
+    };
+
+    let taken = Arc::new(AtomicUsize::new(0));
+    let source = {
+        let taken = taken.clone();
+        move |min: usize| {
+            taken.fetch_add(1, Ordering::SeqCst);
+            BytesMut::with_capacity(min)
+        }
+    };
+
+    let mut framed = FramedRead::with_buffer_source(mock, U32Decoder, source);
+    assert_eq!(1, taken.load(Ordering::SeqCst));
+    assert_eq!(Ready(Some(0)), framed.poll().unwrap());
+    assert_eq!(Ready(Some(1)), framed.poll().unwrap());
+    assert_eq!(Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+fn read_with_buffer_source_refills() {
+    let data = [0; 16 * 1024];
+
+    let taken = Arc::new(AtomicUsize::new(0));
+    let source = {
+        let taken = taken.clone();
+        move |min: usize| {
+            taken.fetch_add(1, Ordering::SeqCst);
+            BytesMut::with_capacity(min)
+        }
+    };
+
+    let mut framed = FramedRead::with_buffer_source(&data[..], U32Decoder, source);
+    for _ in 0..(4 * 1024) {
+        assert_eq!(Ready(Some(0)), framed.poll().unwrap());
+    }
+    assert_eq!(Ready(None), framed.poll().unwrap());
+
+    // Once the first buffer has been split into frames, a fresh one is taken
+    assert!(taken.load(Ordering::SeqCst) > 1);
+}
+
+#[test]
+fn read_into_installed_buffer() {
+    struct Recorder {
+        lens: Vec<usize>,
+    }
+
+    impl Read for Recorder {
+        fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+            self.lens.push(dst.len());
+            Ok(0)
+        }
+    }
+
+    impl AsyncRead for Recorder {
+    }
+
+    let recorder = Recorder { lens: vec![] };
+    let mut framed = FramedRead::new(recorder, U32Decoder);
+    framed.set_read_buffer(BytesMut::with_capacity(64 * 1024));
+    assert_eq!(64 * 1024, framed.read_buffer_capacity());
+
+    assert_eq!(Ready(None), framed.poll().unwrap());
+    assert_eq!(64 * 1024, framed.get_ref().lens[0]);
+}
+
+#[test]
+fn installed_buffer_data_is_decoded() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x02".to_vec()),
+    };
+
+    let mut buf = BytesMut::with_capacity(64);
+    buf.extend_from_slice(b"\x00\x00\x00\x01");
+
+    let mut framed = FramedRead::new(mock, U32Decoder);
+    framed.set_read_buffer(buf);
+    assert_eq!(Ready(Some(1)), framed.poll().unwrap());
+    assert_eq!(Ready(Some(2)), framed.poll().unwrap());
+    assert_eq!(Ready(None), framed.poll().unwrap());
+}
+
+#[test]
+#[should_panic]
+fn set_read_buffer_with_pending_data_panics() {
+    let mock = mock! {
+        Ok(b"\x00\x00".to_vec()),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+    };
+
+    let mut framed = FramedRead::new(mock, U32Decoder);
+    assert_eq!(NotReady, framed.poll().unwrap());
+    framed.set_read_buffer(BytesMut::with_capacity(64));
+}
+
 // ===== Mock ======
 
 struct Mock {
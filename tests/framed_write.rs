@@ -3,10 +3,11 @@ extern crate bytes;
 extern crate futures;
 
 use tokio_io::AsyncWrite;
-use tokio_io::codec::{Encoder, FramedWrite};
+use tokio_io::io::BytesQueue;
+use tokio_io::codec::{Encoder, FramedWrite, HeaderEncoder};
 
-use futures::{Sink, Poll};
-use bytes::{BytesMut, BufMut, BigEndian};
+use futures::{Async, Future, Sink, Poll};
+use bytes::{Buf, Bytes, BytesMut, BufMut, BigEndian};
 
 use std::io::{self, Write};
 use std::collections::VecDeque;
@@ -33,6 +34,28 @@ impl Encoder for U32Encoder {
     }
 }
 
+// Frames are the payload prefixed with its length as a `u32`
+struct LengthPrefixed;
+
+impl Encoder for LengthPrefixed {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> io::Result<()> {
+        try!(self.encode_header(item.len(), dst));
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+impl HeaderEncoder for LengthPrefixed {
+    fn encode_header(&mut self, len: usize, dst: &mut BytesMut) -> io::Result<()> {
+        dst.reserve(4);
+        dst.put_u32::<BigEndian>(len as u32);
+        Ok(())
+    }
+}
+
 #[test]
 fn write_multi_frame_in_packet() {
     let mock = mock! {
@@ -53,6 +76,271 @@ fn write_multi_frame_in_packet() {
     assert_eq!(0, framed.get_ref().calls.len());
 }
 
+#[test]
+fn queue_encoded_preserves_order() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02\x00\x00\x00\x03".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    assert!(framed.start_send(0).unwrap().is_ready());
+
+    let buf = framed.encode(1).unwrap();
+    assert_eq!(&buf[..], b"\x00\x00\x00\x01");
+    framed.queue_encoded(buf);
+
+    let mut buf = BytesMut::with_capacity(4);
+    buf.put_u32::<BigEndian>(2);
+    framed.queue_encoded(buf);
+    assert!(framed.start_send(3).unwrap().is_ready());
+
+    // Nothing written yet
+    assert_eq!(1, framed.get_ref().calls.len());
+
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn pace_gates_each_frame() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let mock = mock! {
+        // The first frame is only partially written at first
+        Ok(b"\x00\x00".to_vec()),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Ok(b"\x00\x00".to_vec()),
+        Ok(b"\x00\x00\x00\x01".to_vec()),
+    };
+
+    let open = Rc::new(Cell::new(true));
+    let gate = open.clone();
+    let mut framed = FramedWrite::new(mock, U32Encoder).pace(move || gate.get());
+
+    assert!(framed.start_send(0).unwrap().is_ready());
+    assert!(!framed.poll_complete().unwrap().is_ready());
+
+    // The gate is closed, but the partially written frame is completed
+    open.set(false);
+    assert!(!framed.start_send(1).unwrap().is_ready());
+    assert_eq!(1, framed.get_ref().get_ref().calls.len());
+
+    open.set(true);
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().get_ref().calls.len());
+}
+
+#[test]
+fn heartbeat_on_idle_ticks() {
+    let mock = mock! {
+        Ok(b"\xff\xff\xff\xff\xff\xff\xff\xff".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder).with_heartbeat(|| 0xffff_ffff);
+
+    framed.tick().unwrap();
+    framed.tick().unwrap();
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().get_ref().calls.len());
+}
+
+#[test]
+fn heartbeat_skipped_after_send() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x01".to_vec()),
+        Ok(b"\xff\xff\xff\xff".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder).with_heartbeat(|| 0xffff_ffff);
+
+    assert!(framed.start_send(1).unwrap().is_ready());
+    framed.tick().unwrap();
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    // Nothing was queued by the tick
+    assert_eq!(1, framed.get_ref().get_ref().calls.len());
+
+    // The link is idle again by the following tick
+    framed.tick().unwrap();
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().get_ref().calls.len());
+}
+
+#[test]
+fn dedup_consecutive_drops_repeats() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x0a\x00\x00\x00\x0b\x00\x00\x00\x0a".to_vec()),
+        Ok(b"\x00\x00\x00\x0a".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder).dedup_consecutive();
+
+    // A, A, B, A: only the repeat right after the first A is dropped
+    for &item in &[0xa, 0xa, 0xb, 0xa] {
+        assert!(framed.start_send(item).unwrap().is_ready());
+    }
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(1, framed.get_ref().get_ref().calls.len());
+
+    // After a flush the last frame is forgotten
+    assert!(framed.start_send(0xa).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().get_ref().calls.len());
+}
+
+// Records how many buffers each vectored write is handed, writing at most
+// `max` bytes per call
+struct VectoredMock {
+    written: Vec<u8>,
+    submissions: Vec<usize>,
+    max: usize,
+}
+
+impl Write for VectoredMock {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        panic!("unexpected non-vectored write");
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for VectoredMock {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+
+    fn write_buf_vectored(&mut self, bufs: &mut BytesQueue) -> Poll<usize, io::Error> {
+        self.submissions.push(bufs.chunks().len());
+
+        let mut n = 0;
+        for buf in bufs.chunks() {
+            let len = std::cmp::min(buf.len(), self.max - n);
+            self.written.extend_from_slice(&buf[..len]);
+            n += len;
+        }
+
+        bufs.advance(n);
+        Ok(n.into())
+    }
+}
+
+#[test]
+fn write_vectored_keeps_frames_apart() {
+    let mock = VectoredMock { written: Vec::new(), submissions: Vec::new(), max: 6 };
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    framed.set_vectored_writes(true);
+
+    assert!(framed.start_send(0).unwrap().is_ready());
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert!(framed.start_send(2).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    // Each frame is submitted as its own buffer, and partial writes resume
+    // mid-frame
+    assert_eq!(vec![3, 2], framed.get_ref().submissions);
+    assert_eq!(&b"\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02"[..],
+               &framed.get_ref().written[..]);
+}
+
+#[test]
+fn write_vectored_through_wrappers() {
+    use tokio_io::io::{ChecksumTrailer, Either, HexDump};
+
+    let mock = VectoredMock { written: Vec::new(), submissions: Vec::new(), max: 64 };
+    let io = Either::Left::<_, VectoredMock>(HexDump::new(ChecksumTrailer::new(mock)));
+    let mut framed = FramedWrite::new(io, U32Encoder);
+    framed.set_vectored_writes(true);
+
+    assert!(framed.start_send(0).unwrap().is_ready());
+    assert!(framed.start_send(1).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    match *framed.get_ref() {
+        Either::Left(ref io) => assert_eq!(vec![2], io.get_ref().get_ref().submissions),
+        Either::Right(_) => unreachable!(),
+    }
+}
+
+#[test]
+fn write_vectored_checksums_written_bytes() {
+    use tokio_io::io::ChecksumTrailer;
+
+    let mut queue = BytesQueue::new();
+    queue.push(Bytes::from_static(b"hello "));
+    queue.push(Bytes::from_static(b"world"));
+
+    let mut vectored = ChecksumTrailer::new(io::Cursor::new(Vec::new()));
+    while !queue.is_empty() {
+        assert!(vectored.write_buf_vectored(&mut queue).unwrap().is_ready());
+    }
+    assert!(vectored.shutdown().unwrap().is_ready());
+
+    let mut plain = ChecksumTrailer::new(io::Cursor::new(Vec::new()));
+    plain.write_all(b"hello world").unwrap();
+    assert!(plain.shutdown().unwrap().is_ready());
+
+    assert_eq!(plain.into_inner().into_inner(), vectored.into_inner().into_inner());
+}
+
+#[test]
+fn write_vectored_fallback_concatenates() {
+    let mock = mock! {
+        Ok(b"\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    framed.set_vectored_writes(true);
+
+    assert!(framed.start_send(0).unwrap().is_ready());
+    framed.queue_encoded(BytesMut::from(&b"\x00\x00\x00\x01"[..]));
+    assert!(framed.start_send(2).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_max_pending_frames() {
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Ok(b"\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02".to_vec()),
+        Ok(b"\x00\x00\x00\x03".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    framed.set_max_pending_frames(3);
+
+    for i in 0..3 {
+        assert!(framed.start_send(i).unwrap().is_ready());
+    }
+
+    // The fourth frame is rejected while the others can't be written
+    assert!(!framed.start_send(3).unwrap().is_ready());
+    assert_eq!(2, framed.get_ref().calls.len());
+
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert!(framed.start_send(3).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
+#[test]
+fn write_retries_interrupted() {
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted")),
+        Ok(b"\x00\x00\x00\x00".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, U32Encoder);
+    assert!(framed.start_send(0).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
 #[test]
 fn write_hits_backpressure() {
     const ITER: usize = 2 * 1024;
@@ -102,6 +390,42 @@ fn write_hits_backpressure() {
     assert_eq!(0, framed.get_ref().calls.len());
 }
 
+#[test]
+fn write_eof_sentinel_once_on_close() {
+    struct EofEncoder;
+
+    impl Encoder for EofEncoder {
+        type Item = u32;
+        type Error = io::Error;
+
+        fn encode(&mut self, item: u32, dst: &mut BytesMut) -> io::Result<()> {
+            U32Encoder.encode(item, dst)
+        }
+
+        fn encode_eof(&mut self, dst: &mut BytesMut) -> io::Result<()> {
+            dst.reserve(3);
+            dst.put_slice(b"END");
+            Ok(())
+        }
+    }
+
+    let mock = mock! {
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "not ready")),
+        Ok(b"\x00\x00\x00\x01END".to_vec()),
+    };
+
+    let mut framed = FramedWrite::new(mock, EofEncoder);
+    assert!(framed.start_send(1).unwrap().is_ready());
+
+    // The first write blocks, so the close can't complete yet
+    assert!(!framed.close().unwrap().is_ready());
+    assert!(framed.close().unwrap().is_ready());
+
+    // Closing again must not write the sentinel a second time
+    assert!(framed.close().unwrap().is_ready());
+    assert_eq!(0, framed.get_ref().calls.len());
+}
+
 // ===== Mock ======
 
 struct Mock {
@@ -131,3 +455,56 @@ impl AsyncWrite for Mock {
         Ok(().into())
     }
 }
+
+#[test]
+fn chunked_send_buffers_one_chunk_at_a_time() {
+    // Accepts up to 10,000 bytes per write, blocking every other write, and
+    // records the most data it was ever handed at once
+    struct Throttled {
+        written: Vec<u8>,
+        max_buffered: usize,
+        block: bool,
+    }
+
+    impl Write for Throttled {
+        fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+            self.max_buffered = ::std::cmp::max(self.max_buffered, src.len());
+            self.block = !self.block;
+
+            if self.block {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock, "would block"));
+            }
+
+            let n = ::std::cmp::min(src.len(), 10_000);
+            self.written.extend_from_slice(&src[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl AsyncWrite for Throttled {
+        fn shutdown(&mut self) -> Poll<(), io::Error> {
+            Ok(Async::Ready(()))
+        }
+    }
+
+    let payload = (0..1024 * 1024).map(|i| i as u8).collect::<Vec<_>>();
+    let wr = Throttled { written: Vec::new(), max_buffered: 0, block: false };
+    let mut framed = FramedWrite::new(wr, LengthPrefixed);
+    assert!(framed.start_send(Bytes::from_static(b"hi")).unwrap().is_ready());
+
+    let mut send = framed.chunked_send(Bytes::from(payload.clone()), 64 * 1024);
+    let framed = loop {
+        if let Async::Ready(framed) = send.poll().unwrap() {
+            break framed;
+        }
+    };
+
+    let wr = framed.into_inner();
+    assert!(wr.max_buffered <= 64 * 1024);
+    assert_eq!(&b"\x00\x00\x00\x02hi\x00\x10\x00\x00"[..], &wr.written[..10]);
+    assert_eq!(&payload[..], &wr.written[10..]);
+}
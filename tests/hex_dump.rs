@@ -0,0 +1,57 @@
+extern crate tokio_io;
+extern crate futures;
+extern crate log;
+
+use tokio_io::io::{read_to_end, write_all, HexDump};
+
+use futures::Future;
+use log::{Log, Metadata, Record};
+
+use std::io::Cursor;
+use std::sync::Mutex;
+
+struct Capture(Mutex<Vec<String>>);
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target().starts_with("tokio_io::hex_dump") {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Capture = Capture(Mutex::new(Vec::new()));
+
+#[test]
+fn hex_dump_logs_traffic() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let wr = HexDump::new(Cursor::new(Vec::new()));
+    let (wr, _) = write_all(wr, b"hello").wait().unwrap();
+    let (wr, _) = write_all(wr, b"\x00world, hex\r\n").wait().unwrap();
+    let data = wr.into_inner().into_inner();
+
+    let mut rd = HexDump::new(Cursor::new(data));
+    rd.set_max_dump_len(4);
+    assert_eq!(Some(4), rd.max_dump_len());
+    let (_, buf) = read_to_end(rd, Vec::new()).wait().unwrap();
+    assert_eq!(&buf[..], &b"hello\x00world, hex\r\n"[..]);
+
+    let logged = LOGGER.0.lock().unwrap();
+    assert_eq!(logged[0], "> wrote 5 bytes at offset 0\n\
+                           00000000  68 65 6c 6c 6f                                   |hello|");
+    assert_eq!(logged[1], "> wrote 13 bytes at offset 5\n\
+                           00000005  00 77 6f 72 6c 64 2c 20 68 65 78 0d 0a           |.world, hex..|");
+    assert_eq!(logged[2], "< read 18 bytes at offset 0\n\
+                           00000000  68 65 6c 6c                                      |hell|\n\
+                           ... 14 more bytes");
+    assert_eq!(logged[3], "< read 0 bytes at offset 18");
+    assert_eq!(logged.len(), 4);
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "instrument")]
+
+extern crate futures;
+extern crate tokio_io;
+
+use std::io::{self, Read};
+use std::sync::Arc;
+
+use futures::{Async, Future};
+use tokio_io::AsyncRead;
+use tokio_io::io::instrument::{self, Counters, Site};
+
+// Yields one byte per read, failing with `WouldBlock` before each
+struct Trickle {
+    data: &'static [u8],
+    ready: bool,
+}
+
+impl Read for Trickle {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        if !self.ready {
+            self.ready = true;
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        self.ready = false;
+        if self.data.is_empty() {
+            return Ok(0);
+        }
+        dst[0] = self.data[0];
+        self.data = &self.data[1..];
+        Ok(1)
+    }
+}
+
+impl AsyncRead for Trickle {}
+
+#[test]
+fn read_exact_counts_polls_and_would_blocks() {
+    let counters = Arc::new(Counters::new());
+    instrument::register(Site::ReadExact, counters.clone());
+
+    let rd = Trickle { data: b"ab", ready: false };
+    let mut fut = tokio_io::io::read_exact(rd, [0; 2]);
+    assert!(fut.poll().unwrap().is_not_ready());
+    assert!(fut.poll().unwrap().is_not_ready());
+    match fut.poll().unwrap() {
+        Async::Ready((_, buf)) => assert_eq!(&buf, b"ab"),
+        Async::NotReady => panic!("expected the read to finish"),
+    }
+
+    assert_eq!(3, counters.polls());
+    assert_eq!(2, counters.would_blocks());
+
+    // Other sites and unregistered counters aren't updated
+    let rd = Trickle { data: b"", ready: false };
+    assert!(tokio_io::io::read_to_end(rd, vec![]).poll().unwrap().is_not_ready());
+    assert!(instrument::unregister(Site::ReadExact).is_some());
+    let rd = Trickle { data: b"a", ready: false };
+    assert!(tokio_io::io::read_exact(rd, [0; 1]).poll().unwrap().is_not_ready());
+    assert_eq!(3, counters.polls());
+    assert_eq!(2, counters.would_blocks());
+}
@@ -1,9 +1,11 @@
 extern crate tokio_io;
+extern crate bytes;
 extern crate futures;
 
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio_io::codec::length_delimited::*;
 
+use bytes::Bytes;
 use futures::{Stream, Sink, Poll};
 use futures::Async::*;
 
@@ -185,6 +187,33 @@ fn read_max_frame_len() {
     assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
 }
 
+#[test]
+fn read_single_frame_length_scaled() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .length_scale(4)
+        .new_read(mock! {
+            Ok(b"\x00\x02abcdefgh"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abcdefgh"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_length_scaled_max_frame_len() {
+    // The field is within the limit, but the scaled length isn't
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .length_scale(4)
+        .max_frame_length(6)
+        .new_read(mock! {
+            Ok(b"\x00\x02abcdefgh"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
 #[test]
 fn read_update_max_frame_len_at_rest() {
     let mut io = Builder::new()
@@ -281,6 +310,377 @@ fn read_single_multi_frame_one_packet_length_includes_head() {
     assert_eq!(io.poll().unwrap(), Ready(None));
 }
 
+#[test]
+fn read_single_frame_ascii_length() {
+    let mut io = Builder::new()
+        .ascii_length_field(4)
+        .new_read(mock! {
+            Ok(b"0009abcdefghi"[..].into()),
+            Ok(b"0011hello world"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abcdefghi"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"hello world"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_ascii_length_non_digit() {
+    let mut io = Builder::new()
+        .ascii_length_field(4)
+        .new_read(mock! {
+            Ok(b"00x9abcdefghi"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_ascii_length_max_frame_len() {
+    let mut io = Builder::new()
+        .ascii_length_field(4)
+        .max_frame_length(5)
+        .new_read(mock! {
+            Ok(b"0009abcdefghi"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_single_frame_header_checksum() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .header_checksum()
+        .new_read(mock! {
+            Ok(b"\x00\x09\x09abcdefghi"[..].into()),
+            Ok(b"\x01\x02\x03"[..].into()),
+            Ok(vec![b'x'; 258].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abcdefghi"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(Some(vec![b'x'; 258][..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_header_checksum_mismatch() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .header_checksum()
+        .new_read(mock! {
+            Ok(b"\xff\x09\x09abcdefghi"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_header_checksum_max_frame_len() {
+    // The checksum is valid, so the length is trusted and then rejected
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .header_checksum()
+        .max_frame_length(5)
+        .new_read(mock! {
+            Ok(b"\x00\x09\x09abcdefghi"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_captures_header_flags() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .num_skip(4)
+        .capture_header(2..4)
+        .new_read_with_header(mock! {
+            Ok(b"\x00\x09\xab\xcdabcdefghi"[..].into()),
+            Ok(b"\x00\x02\x00"[..].into()),
+            Ok(b"\x01hi"[..].into()),
+        });
+
+    match io.poll().unwrap() {
+        Ready(Some((flags, payload))) => {
+            assert_eq!(&flags[..], &b"\xab\xcd"[..]);
+            assert_eq!(&payload[..], &b"abcdefghi"[..]);
+        }
+        _ => panic!("expected a frame"),
+    }
+    match io.poll().unwrap() {
+        Ready(Some((flags, payload))) => {
+            assert_eq!(&flags[..], &b"\x00\x01"[..]);
+            assert_eq!(&payload[..], &b"hi"[..]);
+        }
+        _ => panic!("expected a frame"),
+    }
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_streaming_large_frame_in_chunks() {
+    let mut head = b"\x00\x00\x4e\x20".to_vec();
+    head.extend_from_slice(&[b'a'; 4_000]);
+
+    let mut io = Builder::new()
+        .new_read_streaming(mock! {
+            Ok(head.into()),
+            Ok(vec![b'b'; 4_000].into()),
+            Ok(vec![b'c'; 4_000].into()),
+            Ok(vec![b'd'; 4_000].into()),
+            Ok(vec![b'e'; 4_000].into()),
+            Ok(b"\x00\x00\x00\x00"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::Header(FrameHeader { len: 20_000 }))));
+
+    for &b in b"abcde" {
+        match io.poll().unwrap() {
+            Ready(Some(FramePart::Chunk(chunk))) => assert_eq!(&chunk[..], &[b; 4_000][..]),
+            other => panic!("expected a chunk; {:?}", other),
+        }
+    }
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::End)));
+
+    // An empty frame has no chunks
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::Header(FrameHeader { len: 0 }))));
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::End)));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_streaming_incomplete_frame() {
+    let mut io = Builder::new()
+        .new_read_streaming(mock! {
+            Ok(b"\x00\x00\x00\x09abc"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::Header(FrameHeader { len: 9 }))));
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::Chunk(b"abc"[..].into()))));
+    assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn read_streaming_max_frame_len() {
+    let mut io = Builder::new()
+        .max_frame_length(5)
+        .new_read_streaming(mock! {
+            Ok(b"\x00\x00\x00\x09abcdefghi"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn read_frame_trailer_excluded() {
+    let mut io = Builder::new()
+        .frame_trailer_len(4)
+        .new_read(mock! {
+            Ok(b"\x00\x00\x00\x03abcCRC1\x00\x00\x00\x02deCRC2"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abc"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"de"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_frame_trailer_multi_packet() {
+    let mut io = Builder::new()
+        .frame_trailer_len(4)
+        .new_read(mock! {
+            Ok(b"\x00\x00\x00\x03abc"[..].into()),
+            Err(would_block()),
+            Ok(b"CR"[..].into()),
+            Err(would_block()),
+            Ok(b"C1\x00\x00\x00\x02deCRC"[..].into()),
+            Err(would_block()),
+            Ok(b"2"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abc"[..].into())));
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"de"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_frame_trailer_incomplete() {
+    let mut io = Builder::new()
+        .frame_trailer_len(4)
+        .new_read(mock! {
+            Ok(b"\x00\x00\x00\x03abcCR"[..].into()),
+        });
+
+    assert!(io.poll().is_err());
+}
+
+#[test]
+fn read_has_complete_frame() {
+    let mut io = FramedRead::new(mock! {
+        Ok(b"\x00\x00\x00\x03abc\x00\x00\x00\x02de\x00\x00\x00\x04f"[..].into()),
+        Err(would_block()),
+        Ok(b"ghi"[..].into()),
+    });
+
+    // Nothing has been read yet
+    assert!(!io.has_complete_frame());
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abc"[..].into())));
+    assert!(io.has_complete_frame());
+    assert!(io.has_complete_frame());
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"de"[..].into())));
+    assert!(!io.has_complete_frame());
+
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert!(!io.has_complete_frame());
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"fghi"[..].into())));
+    assert!(!io.has_complete_frame());
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_datagram_per_frame() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .datagram_mode()
+        .new_read(mock! {
+            Ok(b"\x00\x03abc"[..].into()),
+            Err(would_block()),
+            Ok(b"\x00\x00"[..].into()),
+            Ok(b"\x00\x02de"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abc"[..].into())));
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert_eq!(io.poll().unwrap(), Ready(Some(b""[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"de"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_datagram_length_mismatch() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .datagram_mode()
+        .new_read(mock! {
+            // Shorter than its header declares, rather than waiting for more
+            Ok(b"\x00\x05abc"[..].into()),
+            // Longer than its header declares
+            Ok(b"\x00\x01abc"[..].into()),
+            Ok(b"\x00"[..].into()),
+            Ok(b"\x00\x02de"[..].into()),
+        });
+
+    for _ in 0..3 {
+        assert_eq!(io.poll().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    // Each bad datagram is discarded on its own
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"de"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_spurious_zero_length_read() {
+    // A single zero length read ends the stream by default
+    let mut io = Builder::new()
+        .new_read(mock! {
+            Ok(b"\x00\x00\x00\x03ab"[..].into()),
+            Ok(b""[..].into()),
+            Ok(b"c"[..].into()),
+        });
+
+    assert!(io.poll().is_err());
+
+    let mut io = Builder::new()
+        .strict_eof(true)
+        .new_read(mock! {
+            Ok(b"\x00\x00\x00\x03ab"[..].into()),
+            Ok(b""[..].into()),
+            Ok(b"c\x00\x00\x00"[..].into()),
+            Ok(b""[..].into()),
+            Err(would_block()),
+            Ok(b"\x01d"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"abc"[..].into())));
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert_eq!(io.poll().unwrap(), Ready(Some(b"d"[..].into())));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_streaming_frame_trailer() {
+    let mut io = Builder::new()
+        .frame_trailer_len(4)
+        .new_read_streaming(mock! {
+            Ok(b"\x00\x00\x00\x03abcCR"[..].into()),
+            Err(would_block()),
+            Ok(b"C1"[..].into()),
+        });
+
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::Header(FrameHeader { len: 3 }))));
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::Chunk(b"abc"[..].into()))));
+    assert_eq!(io.poll().unwrap(), NotReady);
+    assert_eq!(io.poll().unwrap(), Ready(Some(FramePart::End)));
+    assert_eq!(io.poll().unwrap(), Ready(None));
+}
+
+#[test]
+fn read_reports_progress() {
+    use std::sync::{Arc, Mutex};
+
+    let mut head = b"\x00\x00\x4e\x20".to_vec();
+    head.extend_from_slice(&[b'a'; 4_000]);
+
+    let mut io = FramedRead::new(mock! {
+        Ok(head.into()),
+        Ok(vec![b'b'; 6_000].into()),
+        Err(would_block()),
+        Ok(vec![b'c'; 10_000].into()),
+    });
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    {
+        let seen = seen.clone();
+        io.on_progress(move |received, declared| {
+            seen.lock().unwrap().push((received, declared));
+        });
+    }
+
+    assert_eq!(io.poll().unwrap(), NotReady);
+    match io.poll().unwrap() {
+        Ready(Some(frame)) => assert_eq!(20_000, frame.len()),
+        other => panic!("expected a frame; {:?}", other),
+    }
+    assert_eq!(io.poll().unwrap(), Ready(None));
+
+    let seen = seen.lock().unwrap();
+    assert!(seen.len() >= 2);
+    assert!(seen.iter().all(|&(_, declared)| declared == 20_000));
+    assert!(seen.windows(2).all(|w| w[0].0 <= w[1].0));
+    assert_eq!(10_000, seen.last().unwrap().0);
+}
+
+#[test]
+fn read_with_progress_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let mut io = FramedRead::new(io::Cursor::new(vec![]));
+    io.on_progress(|_, _| {});
+    assert_send_sync(&io);
+
+    let io: Framed<io::Cursor<Vec<u8>>> = Framed::new(io::Cursor::new(vec![]));
+    assert_send_sync(&io);
+}
+
 #[test]
 fn write_single_frame_length_adjusted() {
     let mut io = Builder::new()
@@ -451,6 +851,150 @@ fn write_update_max_frame_len_in_flight() {
     assert!(io.get_ref().calls.is_empty());
 }
 
+#[test]
+fn write_single_frame_ascii_length() {
+    let mut io = Builder::new()
+        .ascii_length_field(4)
+        .new_write(mock! {
+            Ok(b"0009"[..].into()),
+            Ok(b"abcdefghi"[..].into()),
+            Ok(Flush),
+        });
+
+    assert!(io.start_send("abcdefghi").unwrap().is_ready());
+    assert!(io.poll_complete().unwrap().is_ready());
+    assert!(io.get_ref().calls.is_empty());
+}
+
+#[test]
+fn write_single_frame_header_checksum() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .header_checksum()
+        .new_write(mock! {
+            Ok(b"\x01\x02\x03"[..].into()),
+            Ok(vec![b'x'; 258].into()),
+            Ok(Flush),
+        });
+
+    assert!(io.start_send(vec![b'x'; 258]).unwrap().is_ready());
+    assert!(io.poll_complete().unwrap().is_ready());
+    assert!(io.get_ref().calls.is_empty());
+}
+
+#[test]
+fn write_ascii_length_max_frame_len() {
+    let mut io = Builder::new()
+        .ascii_length_field(4)
+        .max_frame_length(5)
+        .new_write(mock! { });
+
+    assert_eq!(io.start_send("abcdef").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert!(io.get_ref().calls.is_empty());
+}
+
+#[test]
+fn write_ascii_length_too_wide() {
+    let mut io = Builder::new()
+        .ascii_length_field(1)
+        .new_write(mock! { });
+
+    assert_eq!(io.start_send("abcdefghij").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert!(io.get_ref().calls.is_empty());
+}
+
+#[test]
+fn write_single_frame_length_scaled() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .length_scale(4)
+        .new_write(mock! {
+            Ok(b"\x00\x02"[..].into()),
+            Ok(b"abcdefgh"[..].into()),
+            Ok(Flush),
+        });
+
+    assert!(io.start_send("abcdefgh").unwrap().is_ready());
+    assert!(io.poll_complete().unwrap().is_ready());
+    assert!(io.get_ref().calls.is_empty());
+}
+
+#[test]
+fn write_length_scaled_not_multiple() {
+    let mut io = Builder::new()
+        .length_field_length(2)
+        .length_scale(4)
+        .new_write(mock! { });
+
+    assert_eq!(io.start_send("abcdefghi").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert!(io.get_ref().calls.is_empty());
+}
+
+#[test]
+fn write_streamed_frame_in_chunks() {
+    let chunks = ["hello", " streamed", " world"];
+
+    let mut single = Builder::new().length_field_length(2).new_write(io::Cursor::new(vec![]));
+    assert!(single.start_send(Bytes::from(chunks.concat())).unwrap().is_ready());
+    assert!(single.poll_complete().unwrap().is_ready());
+
+    let mut streamed: FramedWrite<_, Bytes> =
+        Builder::new().length_field_length(2).new_write(io::Cursor::new(vec![]));
+    assert!(streamed.begin_frame(20).unwrap().is_ready());
+    for chunk in &chunks {
+        assert!(streamed.write_frame_chunk(Bytes::from(*chunk)).unwrap().is_ready());
+    }
+    streamed.end_frame().unwrap();
+    assert!(streamed.poll_complete().unwrap().is_ready());
+
+    assert_eq!(&b"\x00\x14hello streamed world"[..], &streamed.get_ref().get_ref()[..]);
+    assert_eq!(single.get_ref().get_ref(), streamed.get_ref().get_ref());
+}
+
+#[test]
+fn write_streamed_frame_would_block() {
+    let mut io: FramedWrite<_, Bytes> = Builder::new()
+        .length_field_length(2)
+        .new_write(mock! {
+            Ok(b"\x00\x06"[..].into()),
+            Ok(b"ab"[..].into()),
+            Err(would_block()),
+            Ok(b"c"[..].into()),
+            Ok(b"def"[..].into()),
+            Ok(Flush),
+        });
+
+    assert!(io.begin_frame(6).unwrap().is_ready());
+    assert!(io.write_frame_chunk(Bytes::from("abc")).unwrap().is_ready());
+
+    // The first chunk blocks partway
+    assert!(!io.write_frame_chunk(Bytes::from("def")).unwrap().is_ready());
+    assert!(io.write_frame_chunk(Bytes::from("def")).unwrap().is_ready());
+    io.end_frame().unwrap();
+    assert!(io.poll_complete().unwrap().is_ready());
+    assert!(io.get_ref().calls.is_empty());
+}
+
+#[test]
+fn write_streamed_frame_length_mismatch() {
+    let mut io: FramedWrite<_, Bytes> = Builder::new().new_write(io::Cursor::new(vec![]));
+
+    assert_eq!(io.write_frame_chunk(Bytes::from("a")).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+    assert!(io.begin_frame(4).unwrap().is_ready());
+    assert_eq!(io.write_frame_chunk(Bytes::from("abcde")).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert!(io.write_frame_chunk(Bytes::from("abc")).unwrap().is_ready());
+    assert_eq!(io.end_frame().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+    // Frames can't be interleaved with the streamed one
+    assert_eq!(io.start_send(Bytes::from("x")).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(io.begin_frame(1).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+    assert!(io.write_frame_chunk(Bytes::from("d")).unwrap().is_ready());
+    io.end_frame().unwrap();
+    assert!(io.start_send(Bytes::from("x")).unwrap().is_ready());
+}
+
 // ===== Test utils =====
 
 fn would_block() -> io::Error {
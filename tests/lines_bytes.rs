@@ -0,0 +1,26 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::lines_bytes;
+
+use futures::{Future, Stream};
+
+use std::io::Cursor;
+
+#[test]
+fn lines_bytes_strips_terminators() {
+    let data = Cursor::new(&b"line 1\nline 2\r\n\xff\xfe\nlast"[..]);
+    let lines = lines_bytes(data).collect().wait().unwrap();
+
+    assert_eq!(lines, vec![&b"line 1"[..], &b"line 2"[..], &b"\xff\xfe"[..], &b"last"[..]]);
+}
+
+#[test]
+fn lines_bytes_keep_carriage_return() {
+    let data = Cursor::new(&b"line 1\r\nline 2\r\n"[..]);
+    let mut lines = lines_bytes(data);
+    lines.set_strip_carriage_return(false);
+    let lines = lines.collect().wait().unwrap();
+
+    assert_eq!(lines, vec![&b"line 1\r"[..], &b"line 2\r"[..]]);
+}
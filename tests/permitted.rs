@@ -0,0 +1,91 @@
+extern crate tokio_io;
+
+use tokio_io::io::{Permitted, ReadPermit};
+
+use std::cell::Cell;
+use std::io::{self, Read};
+use std::rc::Rc;
+
+// Bytes available in a pool shared by all readers
+struct Permit {
+    pool: Rc<Cell<usize>>,
+    len: usize,
+}
+
+impl ReadPermit for Permit {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn shrink(&mut self, len: usize) {
+        self.pool.set(self.pool.get() + self.len - len);
+        self.len = len;
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.pool.set(self.pool.get() + self.len);
+    }
+}
+
+fn acquire(pool: &Rc<Cell<usize>>, max: usize) -> Option<Permit> {
+    let len = ::std::cmp::min(pool.get(), max);
+
+    if len == 0 {
+        return None;
+    }
+
+    pool.set(pool.get() - len);
+    Some(Permit { pool: pool.clone(), len: len })
+}
+
+#[test]
+fn reads_are_bounded_by_permits() {
+    let pool = Rc::new(Cell::new(6));
+    let pool2 = pool.clone();
+    let mut rd = Permitted::new(&b"hello world"[..], move || acquire(&pool2, 4));
+    let mut buf = [0; 16];
+
+    assert_eq!(4, rd.read(&mut buf).unwrap());
+    assert_eq!(b"hell", &buf[..4]);
+    assert_eq!(2, rd.read(&mut buf).unwrap());
+    assert_eq!(b"o ", &buf[..2]);
+    assert_eq!(6, rd.held_len());
+    assert_eq!(0, pool.get());
+
+    // Nothing is read until permits are released
+    let err = rd.read(&mut buf).unwrap_err();
+    assert_eq!(io::ErrorKind::WouldBlock, err.kind());
+
+    // Consuming part of a permit releases that part
+    rd.consume(3);
+    assert_eq!(3, pool.get());
+    assert_eq!(3, rd.held_len());
+
+    assert_eq!(3, rd.read(&mut buf).unwrap());
+    assert_eq!(b"wor", &buf[..3]);
+
+    rd.consume(6);
+    assert_eq!(6, pool.get());
+    assert_eq!(0, rd.held_len());
+}
+
+#[test]
+fn unused_permits_are_released() {
+    let pool = Rc::new(Cell::new(8));
+    let pool2 = pool.clone();
+    let mut rd = Permitted::new(&b"abc"[..], move || acquire(&pool2, 8));
+    let mut buf = [0; 16];
+
+    // A short read shrinks the permit to the bytes read
+    assert_eq!(3, rd.read(&mut buf).unwrap());
+    assert_eq!(5, pool.get());
+
+    // Nothing is held for EOF
+    assert_eq!(0, rd.read(&mut buf).unwrap());
+    assert_eq!(5, pool.get());
+
+    drop(rd);
+    assert_eq!(8, pool.get());
+}
@@ -0,0 +1,59 @@
+extern crate tokio_io;
+
+use tokio_io::io::{Phase, PhasePolicy, Phased};
+
+use std::io::{Cursor, Read, Write};
+
+#[test]
+fn operations_allowed_by_phase() {
+    let mut io = Phased::new(Cursor::new(b"hello".to_vec()), Phase::ReadOnly);
+    let mut buf = [0; 2];
+    assert_eq!(2, io.read(&mut buf).unwrap());
+
+    assert_eq!(Phase::ReadOnly, io.set_phase(Phase::WriteOnly));
+    assert_eq!(1, io.write(b"!").unwrap());
+    io.flush().unwrap();
+
+    io.set_phase(Phase::ReadWrite);
+    assert_eq!(2, io.read(&mut buf).unwrap());
+    assert_eq!(b"he!lo", &io.into_inner().into_inner()[..]);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "write during ReadOnly phase"))]
+fn write_during_read_only_phase() {
+    let mut io = Phased::new(Cursor::new(Vec::new()), Phase::ReadOnly);
+    let err = io.write(b"hello").unwrap_err();
+    assert_eq!("write during ReadOnly phase", err.to_string());
+    assert!(io.get_ref().get_ref().is_empty());
+}
+
+#[derive(Debug)]
+enum Handshake {
+    AwaitingHello,
+    SendingReply,
+}
+
+impl PhasePolicy for Handshake {
+    fn can_read(&self) -> bool {
+        match *self {
+            Handshake::AwaitingHello => true,
+            Handshake::SendingReply => false,
+        }
+    }
+
+    fn can_write(&self) -> bool {
+        !self.can_read()
+    }
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "read during SendingReply phase"))]
+fn custom_policy() {
+    let mut io = Phased::new(Cursor::new(b"hello".to_vec()), Handshake::AwaitingHello);
+    let mut buf = [0; 5];
+    assert_eq!(5, io.read(&mut buf).unwrap());
+
+    io.set_phase(Handshake::SendingReply);
+    assert!(io.read(&mut buf).is_err());
+}
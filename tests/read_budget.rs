@@ -0,0 +1,49 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::codec::{FramedRead, BytesCodec};
+use tokio_io::io::ReadBudget;
+
+use futures::{future, Future, Stream};
+
+use std::io::{self, Read};
+
+// Yields the data one byte at a time
+struct Chunked {
+    remaining: usize,
+    reads: usize,
+}
+
+impl Read for Chunked {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        self.reads += 1;
+
+        if self.remaining == 0 {
+            return Ok(0);
+        }
+
+        self.remaining -= 1;
+        dst[0] = b'a';
+        Ok(1)
+    }
+}
+
+impl AsyncRead for Chunked {}
+
+#[test]
+fn read_calls_per_poll_are_capped() {
+    let rd = ReadBudget::new(Chunked { remaining: 10, reads: 0 }, 3);
+
+    // Never yields a frame until EOF
+    let mut framed = FramedRead::new(rd, BytesCodec::new())
+        .filter(|_| false);
+
+    future::lazy(|| {
+        for i in 1..4 {
+            assert!(!framed.poll().unwrap().is_ready());
+            assert_eq!(3 * i, framed.get_ref().get_ref().get_ref().reads);
+        }
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
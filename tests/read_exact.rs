@@ -0,0 +1,67 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_exact_at, read_into_window, Window};
+
+use futures::Future;
+
+use std::io::{self, Cursor};
+
+#[test]
+fn read_exact_at_offset() {
+    let mut buf = [0xff; 10];
+    buf[..6].copy_from_slice(b"header");
+
+    let (rd, buf) = read_exact_at(Cursor::new(&b"bodyrest"[..]), buf, 6).wait().unwrap();
+
+    assert_eq!(&buf[..], &b"headerbody"[..]);
+    assert_eq!(rd.position(), 4);
+}
+
+#[test]
+fn read_exact_at_early_eof() {
+    let err = read_exact_at(Cursor::new(&b"bo"[..]), [0; 10], 6).wait().unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+// Yields its data a few bytes at a time
+#[derive(Debug)]
+struct Chunked<'a>(&'a [u8]);
+
+impl<'a> io::Read for Chunked<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = std::cmp::min(3, std::cmp::min(buf.len(), self.0.len()));
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+impl<'a> tokio_io::AsyncRead for Chunked<'a> {}
+
+#[test]
+fn read_into_window_fills_region() {
+    let mut buf = [0xff; 16];
+    let mut window = Window::new(&mut buf[..]);
+    window.set_start(4).set_end(12);
+
+    let (rd, window) = read_into_window(Chunked(b"fragmentrest"), window).wait().unwrap();
+    assert_eq!(window.start(), 12);
+    assert_eq!(window.end(), 12);
+    assert_eq!(rd.0, b"rest");
+
+    assert_eq!(&buf[..4], &[0xff; 4]);
+    assert_eq!(&buf[4..12], b"fragment");
+    assert_eq!(&buf[12..], &[0xff; 4]);
+}
+
+#[test]
+fn read_into_window_early_eof() {
+    let mut buf = [0; 16];
+    let mut window = Window::new(&mut buf[..]);
+    window.set_start(4).set_end(12);
+
+    let err = read_into_window(Chunked(b"frag"), window).wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,97 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::codec::LinesCodec;
+use tokio_io::io::read_header_then_frame;
+
+use futures::{Future, Stream};
+
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+macro_rules! mock {
+    ($($x:expr,)*) => {{
+        let mut v = VecDeque::new();
+        v.extend(vec![$($x),*]);
+        Mock { calls: v }
+    }};
+}
+
+fn decode_version(header: &[u8]) -> io::Result<u8> {
+    if &header[..3] != b"VER" {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "bad header"));
+    }
+    Ok(header[3])
+}
+
+#[test]
+fn header_then_frames() {
+    let rd = &b"VER\x02hello\nworld\n"[..];
+
+    let (version, framed) = read_header_then_frame(rd, 4, decode_version, LinesCodec::new())
+        .wait()
+        .unwrap();
+    assert_eq!(2, version);
+
+    let lines = framed.collect().wait().unwrap();
+    assert_eq!(lines, vec!["hello".to_string(), "world".to_string()]);
+}
+
+#[test]
+fn header_split_across_reads() {
+    let rd = mock! {
+        Ok(b"VE".to_vec()),
+        Ok(b"R\x01hel".to_vec()),
+        Ok(b"lo\n".to_vec()),
+    };
+
+    let (version, framed) = read_header_then_frame(rd, 4, decode_version, LinesCodec::new())
+        .wait()
+        .unwrap();
+    assert_eq!(1, version);
+
+    // The bytes read along with the header aren't lost
+    let lines = framed.collect().wait().unwrap();
+    assert_eq!(lines, vec!["hello".to_string()]);
+}
+
+#[test]
+fn header_early_eof() {
+    let rd = &b"VE"[..];
+
+    let err = read_header_then_frame(rd, 4, decode_version, LinesCodec::new())
+        .wait()
+        .unwrap_err();
+    assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn header_decode_error() {
+    let rd = &b"BAD\x01hello\n"[..];
+
+    let err = read_header_then_frame(rd, 4, decode_version, LinesCodec::new())
+        .wait()
+        .unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+}
+
+struct Mock {
+    calls: VecDeque<io::Result<Vec<u8>>>,
+}
+
+impl Read for Mock {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        match self.calls.pop_front() {
+            Some(Ok(data)) => {
+                assert!(dst.len() >= data.len());
+                dst[..data.len()].copy_from_slice(&data[..]);
+                Ok(data.len())
+            }
+            Some(Err(e)) => Err(e),
+            None => Ok(0),
+        }
+    }
+}
+
+impl AsyncRead for Mock {}
@@ -0,0 +1,123 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::{read, read_exact, read_to_end, read_until};
+
+use bytes::BytesMut;
+use futures::{Async, Future};
+
+use std::collections::VecDeque;
+use std::io::{self, BufReader, Read};
+
+macro_rules! mock {
+    ($($x:expr,)*) => {{
+        let mut v = VecDeque::new();
+        v.extend(vec![$($x),*]);
+        Mock { calls: v }
+    }};
+}
+
+fn interrupted() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "interrupted")
+}
+
+#[test]
+fn read_retries_interrupted() {
+    let rd = mock! {
+        Err(interrupted()),
+        Ok(b"hello".to_vec()),
+    };
+
+    let (_, buf, n) = read(rd, [0; 8]).wait().unwrap();
+    assert_eq!(&buf[..n], b"hello");
+}
+
+#[test]
+fn read_exact_retries_interrupted() {
+    let rd = mock! {
+        Err(interrupted()),
+        Ok(b"hel".to_vec()),
+        Err(interrupted()),
+        Ok(b"lo".to_vec()),
+    };
+
+    let (_, buf) = read_exact(rd, [0; 5]).wait().unwrap();
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn read_to_end_retries_interrupted() {
+    let rd = mock! {
+        Err(interrupted()),
+        Ok(b"hello".to_vec()),
+        Err(interrupted()),
+    };
+
+    let (_, buf) = read_to_end(rd, vec![]).wait().unwrap();
+    assert_eq!(&buf[..], b"hello");
+}
+
+#[test]
+fn read_until_retries_interrupted() {
+    let rd = BufReader::new(mock! {
+        Err(interrupted()),
+        Ok(b"hello\nworld".to_vec()),
+    });
+
+    let (_, buf) = read_until(rd, b'\n', vec![]).wait().unwrap();
+    assert_eq!(&buf[..], b"hello\n");
+}
+
+#[test]
+fn read_buf_retries_interrupted() {
+    let mut rd = mock! {
+        Err(interrupted()),
+        Ok(b"hello".to_vec()),
+        Err(interrupted()),
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "would block")),
+    };
+
+    let mut buf = BytesMut::with_capacity(64);
+    assert_eq!(Async::Ready(5), rd.read_buf(&mut buf).unwrap());
+    assert_eq!(&buf[..], b"hello");
+
+    // `WouldBlock` is still reported as not ready
+    assert_eq!(Async::NotReady, rd.read_buf(&mut buf).unwrap());
+}
+
+#[test]
+fn error_after_interrupted_propagates() {
+    let rd = mock! {
+        Err(interrupted()),
+        Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset")),
+    };
+
+    let err = read_exact(rd, [0; 5]).wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+}
+
+// ===== Mock ======
+
+#[derive(Debug)]
+struct Mock {
+    calls: VecDeque<io::Result<Vec<u8>>>,
+}
+
+impl Read for Mock {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        match self.calls.pop_front() {
+            Some(Ok(data)) => {
+                debug_assert!(dst.len() >= data.len());
+                dst[..data.len()].copy_from_slice(&data[..]);
+                Ok(data.len())
+            }
+            Some(Err(e)) => Err(e),
+            None => Ok(0),
+        }
+    }
+}
+
+impl AsyncRead for Mock {
+}
@@ -0,0 +1,87 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::{select_read, Either};
+
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+// Yields scripted chunks; `None` entries would block
+struct Mock {
+    chunks: VecDeque<Option<&'static [u8]>>,
+}
+
+fn mock(chunks: Vec<Option<&'static [u8]>>) -> Mock {
+    Mock { chunks: chunks.into_iter().collect() }
+}
+
+impl Read for Mock {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.chunks.pop_front() {
+            Some(Some(data)) => {
+                buf[..data.len()].copy_from_slice(data);
+                Ok(data.len())
+            }
+            Some(None) => Err(io::Error::new(io::ErrorKind::WouldBlock, "blocked")),
+            None => Ok(0),
+        }
+    }
+}
+
+impl AsyncRead for Mock {}
+
+fn read(r: &mut Read) -> io::Result<Vec<u8>> {
+    let mut buf = [0; 64];
+    r.read(&mut buf).map(|n| buf[..n].to_vec())
+}
+
+#[test]
+fn races_both_readers() {
+    let a = mock(vec![None, Some(b"a1"), Some(b"a2")]);
+    let b = mock(vec![Some(b"b1"), None, Some(b"b2")]);
+    let mut r = select_read(a, b);
+
+    assert_eq!(read(&mut r).unwrap(), b"b1");
+    // b is tried first now
+    assert_eq!(read(&mut r).unwrap(), b"a1");
+    assert_eq!(read(&mut r).unwrap(), b"a2");
+    assert_eq!(read(&mut r).unwrap(), b"b2");
+    assert!(r.winner().is_none());
+
+    // EOF only once both are done
+    assert_eq!(read(&mut r).unwrap(), b"");
+}
+
+#[test]
+fn skips_reader_at_eof() {
+    let a = mock(vec![]);
+    let b = mock(vec![None, Some(b"b1")]);
+    let mut r = select_read(a, b);
+
+    assert_eq!(read(&mut r).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(read(&mut r).unwrap(), b"b1");
+    assert_eq!(read(&mut r).unwrap(), b"");
+}
+
+#[test]
+fn sticky_commits_to_winner() {
+    let a = mock(vec![None, Some(b"a1")]);
+    let b = mock(vec![Some(b"b1"), None, Some(b"b2")]);
+    let mut r = select_read(a, b);
+    r.set_sticky(true);
+
+    assert_eq!(read(&mut r).unwrap(), b"b1");
+    match r.winner() {
+        Some(Either::Right(_)) => {}
+        _ => panic!("expected b to win"),
+    }
+
+    // a has data, but b was committed to
+    assert_eq!(read(&mut r).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(read(&mut r).unwrap(), b"b2");
+    assert_eq!(read(&mut r).unwrap(), b"");
+
+    let (mut a, _) = r.into_inner();
+    assert_eq!(read(&mut a).unwrap(), b"a1");
+}
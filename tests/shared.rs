@@ -0,0 +1,94 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_io::io::{read_exact, write_all, Shared};
+
+use futures::{Future, Poll};
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::thread;
+
+// Reads queued data and records writes
+struct Mock {
+    data: VecDeque<u8>,
+    written: Vec<u8>,
+}
+
+impl Read for Mock {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            match self.data.pop_front() {
+                Some(b) => buf[n] = b,
+                None => break,
+            }
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+impl AsyncRead for Mock {}
+
+impl Write for Mock {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Mock {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn handles_read_and_write() {
+    let a = Shared::new(Mock {
+        data: b"abcd".iter().cloned().collect(),
+        written: vec![],
+    });
+    let b = a.clone();
+
+    let (a, _) = write_all(a, b"one").wait().unwrap();
+    let (b, _) = write_all(b, b"two").wait().unwrap();
+
+    let (a, buf) = read_exact(a, [0; 2]).wait().unwrap();
+    assert_eq!(&buf, b"ab");
+    let (b, buf) = read_exact(b, [0; 2]).wait().unwrap();
+    assert_eq!(&buf, b"cd");
+
+    assert_eq!(&a.lock().unwrap().written[..], b"onetwo");
+    drop(b);
+}
+
+#[test]
+fn writes_from_threads_are_serialized() {
+    let shared = Shared::new(Mock { data: VecDeque::new(), written: vec![] });
+
+    let threads = (0..4u8).map(|i| {
+        let mut handle = shared.clone();
+        thread::spawn(move || {
+            for _ in 0..100 {
+                handle.write_all(&[i; 8]).unwrap();
+            }
+        })
+    }).collect::<Vec<_>>();
+
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let l = shared.lock().unwrap();
+    assert_eq!(l.written.len(), 4 * 100 * 8);
+    for chunk in l.written.chunks(8) {
+        assert!(chunk.iter().all(|b| *b == chunk[0]));
+    }
+}
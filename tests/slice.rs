@@ -0,0 +1,30 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_exact, shutdown, write_all};
+
+use futures::Future;
+
+#[test]
+fn read_from_slice() {
+    let data: &[u8] = b"hello world";
+    let (rest, buf) = read_exact(data, [0; 5]).wait().unwrap();
+    assert_eq!(&buf, b"hello");
+    assert_eq!(rest, b" world");
+}
+
+#[test]
+fn write_to_mut_slice() {
+    let mut out = [0; 8];
+
+    {
+        let (rest, _) = write_all(&mut out[..], b"hello").wait().unwrap();
+        assert_eq!(rest.len(), 3);
+        shutdown(rest).wait().unwrap();
+    }
+    assert_eq!(&out[..5], b"hello");
+
+    // Writing past the end of the slice fails
+    let err = write_all(&mut out[..], &b"too long for it"[..]).wait().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+}
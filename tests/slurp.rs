@@ -0,0 +1,63 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+use tokio_io::io::slurp;
+
+use futures::Future;
+
+use std::io::{self, Cursor, Read};
+
+const DATA: &'static [u8] = b"the quick brown fox jumps over the lazy dog";
+
+#[test]
+fn slurp_with_exact_size_hint() {
+    let (rd, buf) = slurp(Cursor::new(DATA), Some(DATA.len())).wait().unwrap();
+
+    assert_eq!(&buf[..], DATA);
+    assert_eq!(rd.position(), DATA.len() as u64);
+}
+
+#[test]
+fn slurp_grows_past_size_hint() {
+    let data = vec![7; 20_000];
+
+    let (_, buf) = slurp(Cursor::new(&data[..]), Some(10)).wait().unwrap();
+    assert_eq!(&buf[..], &data[..]);
+
+    let (_, buf) = slurp(Cursor::new(&data[..]), None).wait().unwrap();
+    assert_eq!(&buf[..], &data[..]);
+}
+
+#[test]
+fn slurp_max_size() {
+    let (_, buf) = slurp(Cursor::new(DATA), None).max_size(DATA.len()).wait().unwrap();
+    assert_eq!(&buf[..], DATA);
+
+    let err = slurp(Cursor::new(DATA), Some(1_000)).max_size(DATA.len() - 1).wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+// Fails the first read with `Interrupted`
+#[derive(Debug)]
+struct InterruptOnce<R>(R, bool);
+
+impl<R: Read> Read for InterruptOnce<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.1 {
+            self.1 = true;
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+        }
+        self.0.read(buf)
+    }
+}
+
+impl<R: Read> AsyncRead for InterruptOnce<R> {}
+
+#[test]
+fn slurp_retries_interrupted() {
+    let rd = InterruptOnce(Cursor::new(DATA), false);
+
+    let (_, buf) = slurp(rd, None).wait().unwrap();
+    assert_eq!(&buf[..], DATA);
+}
@@ -0,0 +1,47 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncRead;
+
+use futures::future;
+use futures::Future;
+
+use std::io::{self, Read, Write};
+
+// Readable asynchronously, but only writable synchronously
+struct Device {
+    written: Vec<u8>,
+}
+
+impl Read for Device {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl AsyncRead for Device {}
+
+impl Write for Device {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(src);
+        Ok(src.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn split_read_and_sync_write() {
+    future::lazy(|| {
+        let (mut rd, mut wr) = Device { written: vec![] }.split();
+
+        wr.write_all(b"hello").unwrap();
+        wr.flush().unwrap();
+
+        let mut buf = [0; 8];
+        assert_eq!(0, rd.read(&mut buf).unwrap());
+        Ok::<(), ()>(())
+    }).wait().unwrap();
+}
@@ -0,0 +1,109 @@
+extern crate tokio_io;
+
+use tokio_io::io::Transactional;
+
+use std::io::{self, Read};
+
+// Returns at most `chunk` bytes per read
+struct Chunked<'a> {
+    data: &'a [u8],
+    chunk: usize,
+}
+
+impl<'a> Read for Chunked<'a> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        let n = std::cmp::min(self.chunk, std::cmp::min(dst.len(), self.data.len()));
+        dst[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn rollback_rereads_bytes() {
+    let mut rd = Transactional::new(Chunked { data: b"hello world", chunk: 3 });
+    let mut buf = [0; 5];
+
+    rd.begin();
+    rd.read_exact(&mut buf).unwrap();
+    assert_eq!(b"hello", &buf);
+    rd.rollback();
+    assert!(!rd.is_active());
+
+    let mut all = Vec::new();
+    rd.read_to_end(&mut all).unwrap();
+    assert_eq!(&b"hello world"[..], &all[..]);
+}
+
+#[test]
+fn nested_rollback_over_replayed_bytes() {
+    let mut rd = Transactional::new(Chunked { data: b"abcdef", chunk: 6 });
+    let mut buf = [0; 4];
+
+    rd.begin();
+    rd.read_exact(&mut buf).unwrap();
+    rd.rollback();
+
+    // A second transaction covering replayed and fresh bytes
+    let mut two = [0; 2];
+    rd.read_exact(&mut two).unwrap();
+    assert_eq!(b"ab", &two);
+    rd.begin();
+    rd.read_exact(&mut buf).unwrap();
+    assert_eq!(b"cdef", &buf);
+    rd.rollback();
+
+    let mut all = Vec::new();
+    rd.read_to_end(&mut all).unwrap();
+    assert_eq!(&b"cdef"[..], &all[..]);
+}
+
+#[test]
+fn commit_discards_saved_bytes() {
+    let mut rd = Transactional::new(&b"abcdef"[..]);
+    let mut buf = [0; 3];
+
+    rd.begin();
+    rd.read_exact(&mut buf).unwrap();
+    rd.commit();
+
+    // Nothing to roll back to any more
+    rd.rollback();
+
+    let mut all = Vec::new();
+    rd.read_to_end(&mut all).unwrap();
+    assert_eq!(&b"def"[..], &all[..]);
+}
+
+#[test]
+fn transaction_limited_to_max_rollback_len() {
+    let mut rd = Transactional::new(&b"abcdef"[..]);
+    rd.set_max_rollback_len(4);
+    let mut buf = [0; 6];
+
+    rd.begin();
+    assert_eq!(4, rd.read(&mut buf).unwrap());
+    assert_eq!(io::ErrorKind::Other, rd.read(&mut buf).unwrap_err().kind());
+
+    // The transaction can still be rolled back in full
+    rd.rollback();
+    let mut all = Vec::new();
+    rd.read_to_end(&mut all).unwrap();
+    assert_eq!(&b"abcdef"[..], &all[..]);
+}
+
+#[test]
+fn max_rollback_len_lowered_below_saved() {
+    let mut rd = Transactional::new(&b"abcdef"[..]);
+    let mut buf = [0; 4];
+
+    rd.begin();
+    assert_eq!(4, rd.read(&mut buf).unwrap());
+    rd.set_max_rollback_len(2);
+    assert_eq!(io::ErrorKind::Other, rd.read(&mut buf).unwrap_err().kind());
+
+    rd.rollback();
+    let mut all = Vec::new();
+    rd.read_to_end(&mut all).unwrap();
+    assert_eq!(&b"abcdef"[..], &all[..]);
+}
@@ -0,0 +1,38 @@
+extern crate tokio_io;
+extern crate bytes;
+
+use tokio_io::io::Window;
+
+use bytes::{Bytes, BytesMut};
+
+// Long enough that slices aren't stored inline by `Bytes`
+const DATA: &'static [u8] = b"header:0123456789012345678901234567890123456789";
+
+#[test]
+fn window_as_bytes_shares_buffer() {
+    let buf = Bytes::from(DATA);
+    let mut window = Window::new(buf.clone());
+    window.set_start(7);
+
+    let payload = window.as_bytes();
+    assert_eq!(&payload[..], &DATA[7..]);
+    assert_eq!(payload.as_ptr(), buf[7..].as_ptr());
+
+    window.set_end(45);
+    let payload = window.into_bytes();
+    assert_eq!(&payload[..], &DATA[7..45]);
+    assert_eq!(payload.as_ptr(), buf[7..].as_ptr());
+}
+
+#[test]
+fn window_into_bytes_from_bytes_mut() {
+    let buf = BytesMut::from(DATA);
+    let ptr = buf[7..].as_ptr();
+
+    let mut window = Window::new(buf);
+    window.set_start(7);
+
+    let payload = window.into_bytes();
+    assert_eq!(&payload[..], &DATA[7..]);
+    assert_eq!(payload.as_ptr(), ptr);
+}
@@ -0,0 +1,46 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::write_all;
+
+use futures::{Future, Poll};
+
+use std::io::{self, Write};
+
+// Fails every other write with `Interrupted`
+struct Flaky {
+    interrupt: bool,
+    written: Vec<u8>,
+}
+
+impl Write for Flaky {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+        }
+
+        // Write two bytes at a time
+        let n = ::std::cmp::min(2, src.len());
+        self.written.extend_from_slice(&src[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Flaky {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn write_all_retries_interrupted() {
+    let wr = Flaky { interrupt: false, written: vec![] };
+    let (wr, _) = write_all(wr, b"hello").wait().unwrap();
+    assert_eq!(&wr.written[..], b"hello");
+}
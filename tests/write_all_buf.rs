@@ -0,0 +1,62 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::write_all_buf;
+
+use bytes::{Buf, IntoBuf};
+use futures::{Future, Poll};
+
+use std::cmp;
+use std::io::{self, Write};
+
+// Accepts up to `limit` bytes in total, then fails
+#[derive(Debug)]
+struct Limited {
+    written: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for Limited {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        let n = cmp::min(src.len(), self.limit - self.written.len());
+
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "limit reached"));
+        }
+
+        self.written.extend_from_slice(&src[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Limited {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn write_all_buf_chain() {
+    let buf = (&b"hello "[..]).into_buf().chain(&b"world"[..]);
+    let wr = Limited { written: vec![], limit: 64 };
+
+    let (wr, buf) = write_all_buf(wr, buf).wait().unwrap();
+    assert!(!buf.has_remaining());
+    assert_eq!(&wr.written[..], &b"hello world"[..]);
+}
+
+#[test]
+fn write_all_buf_reports_bytes_written() {
+    let buf = (&b"hello "[..]).into_buf().chain(&b"world"[..]);
+    let wr = Limited { written: vec![], limit: 8 };
+
+    let err = write_all_buf(wr, buf).wait().unwrap_err();
+    assert_eq!(err.bytes_written(), 8);
+    assert_eq!(err.get_ref().kind(), io::ErrorKind::Other);
+}
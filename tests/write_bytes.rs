@@ -0,0 +1,94 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+
+use bytes::Bytes;
+use futures::{Async, Poll};
+
+use std::io::{self, Cursor, Write};
+
+#[test]
+fn write_bytes_default_copies_and_advances() {
+    let mut dst = [0; 4];
+    let mut bytes = Bytes::from(&b"hello world"[..]);
+
+    {
+        let mut wr = Cursor::new(&mut dst[..]);
+        assert_eq!(Async::Ready(4), wr.write_bytes(&mut bytes).unwrap());
+    }
+    assert_eq!(&b"o world"[..], &bytes[..]);
+    assert_eq!(b"hell", &dst);
+
+    let mut wr = Cursor::new(Vec::new());
+
+    assert_eq!(Async::Ready(0), wr.write_bytes(&mut Bytes::new()).unwrap());
+}
+
+// Queues the written `Bytes` without copying them, only accepting data
+// through `write_bytes`
+struct Queue {
+    chunks: Vec<Bytes>,
+}
+
+impl Write for Queue {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        panic!("unexpected copying write");
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Queue {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+
+    fn write_bytes(&mut self, bytes: &mut Bytes) -> Poll<usize, io::Error> {
+        let n = bytes.len();
+        self.chunks.push(bytes.split_to(n));
+        Ok(Async::Ready(n))
+    }
+}
+
+#[test]
+fn write_bytes_override_is_zero_copy() {
+    let data = Bytes::from(vec![7; 64]);
+    let mut bytes = data.clone();
+
+    // Also check the call is forwarded through trait objects
+    let mut wr: Box<AsyncWrite> = Box::new(Queue { chunks: vec![] });
+    assert_eq!(Async::Ready(64), wr.write_bytes(&mut bytes).unwrap());
+    assert!(bytes.is_empty());
+
+    let mut queue = Queue { chunks: vec![] };
+    let mut bytes = data.clone();
+    assert_eq!(Async::Ready(64), (&mut queue).write_bytes(&mut bytes).unwrap());
+    assert_eq!(data.as_ptr(), queue.chunks[0].as_ptr());
+}
+
+#[test]
+fn framed_write_flushes_with_write_bytes() {
+    use futures::Sink;
+    use tokio_io::codec::{FramedWrite, LinesCodec};
+    use tokio_io::io::Either;
+
+    // Wrapped to check that `write_bytes` is forwarded
+    let queue = Either::Left::<_, Queue>(Queue { chunks: vec![] });
+    let mut framed = FramedWrite::new(queue, LinesCodec::new());
+
+    assert!(framed.start_send("one".to_string()).unwrap().is_ready());
+    assert!(framed.start_send("two".to_string()).unwrap().is_ready());
+    assert!(framed.poll_complete().unwrap().is_ready());
+
+    match *framed.get_ref() {
+        Either::Left(ref queue) => {
+            assert_eq!(1, queue.chunks.len());
+            assert_eq!(&b"one\ntwo\n"[..], &queue.chunks[0][..]);
+        }
+        Either::Right(_) => unreachable!(),
+    }
+}
@@ -0,0 +1,63 @@
+extern crate tokio_io;
+extern crate bytes;
+extern crate futures;
+
+use tokio_io::AsyncWrite;
+use tokio_io::io::write_stream;
+
+use bytes::Bytes;
+use futures::{stream, Future, Poll, Stream};
+
+use std::cmp;
+use std::io::{self, Write};
+
+// Accepts at most 3 bytes per write and records flushes
+#[derive(Debug)]
+struct Trickle {
+    written: Vec<u8>,
+    flushed: bool,
+}
+
+impl Write for Trickle {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        let n = cmp::min(src.len(), 3);
+        self.written.extend_from_slice(&src[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed = true;
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Trickle {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(().into())
+    }
+}
+
+#[test]
+fn write_stream_all_chunks() {
+    let chunks = vec![
+        Bytes::from_static(b"hello "),
+        Bytes::new(),
+        Bytes::from_static(b"world"),
+    ];
+    let wr = Trickle { written: vec![], flushed: false };
+
+    let (wr, amt) = write_stream(wr, stream::iter_ok(chunks)).wait().unwrap();
+    assert_eq!(amt, 11);
+    assert_eq!(&wr.written[..], &b"hello world"[..]);
+    assert!(wr.flushed);
+}
+
+#[test]
+fn write_stream_propagates_stream_error() {
+    let chunks = stream::iter_ok::<_, io::Error>(vec![Bytes::from_static(b"hello")])
+        .chain(stream::once(Err(io::Error::new(io::ErrorKind::Other, "boom"))));
+    let wr = Trickle { written: vec![], flushed: false };
+
+    let err = write_stream(wr, chunks).wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
@@ -0,0 +1,27 @@
+extern crate tokio_io;
+extern crate futures;
+
+use tokio_io::io::{read_to_end, write_all, XorCrypt};
+
+use futures::Future;
+
+use std::io::Cursor;
+
+#[test]
+fn xor_round_trip() {
+    let key = b"key".to_vec();
+
+    let wr = XorCrypt::new(Cursor::new(Vec::new()), key.clone());
+    let (wr, _) = write_all(wr, b"hello world").wait().unwrap();
+    let data = wr.into_inner().into_inner();
+
+    assert_eq!(data.len(), 11);
+    assert!(&data[..] != &b"hello world"[..]);
+    assert_eq!(data[0], b'h' ^ b'k');
+    assert_eq!(data[3], b'l' ^ b'k');
+
+    let rd = XorCrypt::new(Cursor::new(data), key);
+    let (_, buf) = read_to_end(rd, Vec::new()).wait().unwrap();
+
+    assert_eq!(&buf[..], &b"hello world"[..]);
+}